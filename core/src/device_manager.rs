@@ -1,6 +1,6 @@
 //! Device management abstraction for fingerprint operations.
 
-use crate::core::fprintd;
+use crate::fprintd;
 use log::{error, info, warn};
 
 /// Error types for device management operations.
@@ -33,9 +33,14 @@ pub struct DeviceManager {
 impl DeviceManager {
     /// Acquire a fingerprint device with automatic cleanup.
     pub async fn acquire() -> Result<Self, DeviceError> {
+        Self::acquire_on_bus(&fprintd::Bus::System).await
+    }
+
+    /// Acquire a fingerprint device from fprintd on `bus`.
+    pub async fn acquire_on_bus(bus: &fprintd::Bus) -> Result<Self, DeviceError> {
         info!("Acquiring fingerprint device for operation");
 
-        let client = Self::connect_to_fprintd().await?;
+        let client = Self::connect_to_fprintd(bus).await?;
         let device = Self::get_first_device(&client).await?;
         Self::claim_device(&device).await?;
 
@@ -50,10 +55,10 @@ impl DeviceManager {
         self.device.as_ref()
     }
 
-    /// Connect to fprintd system bus.
-    async fn connect_to_fprintd() -> Result<fprintd::Client, DeviceError> {
-        info!("Connecting to fprintd system bus");
-        match fprintd::Client::system().await {
+    /// Connect to fprintd on `bus`.
+    async fn connect_to_fprintd(bus: &fprintd::Bus) -> Result<fprintd::Client, DeviceError> {
+        info!("Connecting to fprintd");
+        match fprintd::Client::connect_to(bus).await {
             Ok(client) => {
                 info!("Successfully connected to fprintd");
                 Ok(client)
@@ -122,47 +127,62 @@ impl Drop for DeviceManager {
 
 /// Convenience functions for common device operations.
 impl DeviceManager {
-    /// Execute enrollment operation with automatic device management.
-    /// Returns the DeviceManager which must be kept alive until enrollment completes.
-    pub async fn enroll_finger<F>(
-        finger_key: String,
-        setup_listener: F,
-    ) -> Result<Self, DeviceError>
-    where
-        F: FnOnce(&fprintd::Device) -> Result<(), DeviceError>,
-    {
-        let manager = Self::acquire().await?;
-
-        let device = manager
+    /// List the fingers enrolled for the current user on the managed device.
+    pub async fn enrolled_fingers(&self) -> Result<Vec<String>, DeviceError> {
+        let device = self
             .device()
             .ok_or_else(|| DeviceError::OperationFailed("Device not available".to_string()))?;
 
-        setup_listener(device)?;
-
-        info!("Starting enrollment process for finger: '{}'", finger_key);
-        if let Err(e) = device.enroll_start(&finger_key).await {
-            error!("Failed to start enrollment for '{}': {}", finger_key, e);
-            let _ = device.enroll_stop().await;
-            return Err(DeviceError::OperationFailed(format!(
-                "Failed to start enrollment: {}",
-                e
-            )));
+        match device.list_enrolled_fingers("").await {
+            Ok(fingers) => Ok(fingers),
+            Err(e) if fprintd::error_name(&e) == Some(fprintd::ERROR_NO_ENROLLED_PRINTS) => {
+                info!("No enrolled fingerprints on device");
+                Ok(Vec::new())
+            }
+            Err(e) => {
+                error!("Failed to list enrolled fingers: {}", e);
+                Err(DeviceError::OperationFailed(format!(
+                    "Failed to list enrolled fingers: {}",
+                    e
+                )))
+            }
         }
+    }
+
+    /// Release the device now instead of in the destructor.
+    pub async fn release(mut self) -> Result<(), DeviceError> {
+        let Some(device) = self.device.take() else {
+            return Ok(());
+        };
 
-        info!("Enrollment started successfully, waiting for finger scans...");
-        Ok(manager)
+        info!("Releasing fingerprint device");
+        device.release().await.map_err(|e| {
+            warn!("Failed to release device: {}", e);
+            DeviceError::OperationFailed(format!("Failed to release device: {}", e))
+        })
     }
 
     /// Execute removal operation with automatic device management.
     pub async fn delete_finger(finger_key: String) -> Result<(), DeviceError> {
-        let manager = Self::acquire().await?;
+        Self::delete_finger_on_bus(&fprintd::Bus::System, finger_key).await
+    }
+
+    /// Remove an enrolled finger using fprintd on `bus`.
+    pub async fn delete_finger_on_bus(
+        bus: &fprintd::Bus,
+        finger_key: String,
+    ) -> Result<(), DeviceError> {
+        let manager = Self::acquire_on_bus(bus).await?;
 
         let device = manager
             .device()
             .ok_or_else(|| DeviceError::OperationFailed("Device not available".to_string()))?;
 
         info!("Executing deletion of enrolled finger: '{}'", finger_key);
-        if let Err(e) = device.delete_enrolled_finger(&finger_key).await {
+        let result = device.delete_enrolled_finger(&finger_key).await;
+        let _ = manager.release().await;
+
+        if let Err(e) = result {
             error!("Failed to delete enrolled finger '{}': {}", finger_key, e);
             return Err(DeviceError::OperationFailed(format!(
                 "Failed to delete finger: {}",
@@ -174,3 +194,71 @@ impl DeviceManager {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fprintd_mock::{MockDevice, MockFprintd};
+
+    #[tokio::test]
+    async fn acquire_claims_and_releases_device() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let manager = DeviceManager::acquire_on_bus(&mock.bus()).await.unwrap();
+        assert!(manager.device().is_some());
+        assert!(mock.is_claimed(0));
+
+        drop(manager);
+        assert!(mock.wait_released(0).await);
+    }
+
+    #[tokio::test]
+    async fn acquire_fails_while_device_in_use() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let _first = DeviceManager::acquire_on_bus(&mock.bus()).await.unwrap();
+        let second = DeviceManager::acquire_on_bus(&mock.bus()).await;
+        assert!(matches!(second, Err(DeviceError::ClaimFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn acquire_without_devices() {
+        let mock = MockFprintd::start(Vec::new()).await;
+
+        let result = DeviceManager::acquire_on_bus(&mock.bus()).await;
+        assert!(matches!(result, Err(DeviceError::NoDeviceAvailable)));
+    }
+
+    #[tokio::test]
+    async fn acquire_reports_unreachable_bus() {
+        let bus = fprintd::Bus::Address("unix:path=/nonexistent/xfprintd-gui-test-bus".to_string());
+
+        let result = DeviceManager::acquire_on_bus(&bus).await;
+        assert!(matches!(result, Err(DeviceError::ConnectionFailed(_))));
+    }
+
+    #[tokio::test]
+    async fn delete_finger_removes_print() {
+        let device = MockDevice::default().enrolled(&["right-thumb", "left-thumb"]);
+        let mock = MockFprintd::with_device(device).await;
+
+        DeviceManager::delete_finger_on_bus(&mock.bus(), "right-thumb".to_string())
+            .await
+            .unwrap();
+        assert_eq!(mock.enrolled(0), ["left-thumb"]);
+        assert!(mock.wait_released(0).await);
+    }
+
+    #[tokio::test]
+    async fn delete_finger_reports_device_errors() {
+        let device = MockDevice::default()
+            .enrolled(&["right-thumb"])
+            .failing("DeleteEnrolledFinger", "Internal");
+        let mock = MockFprintd::with_device(device).await;
+
+        let result =
+            DeviceManager::delete_finger_on_bus(&mock.bus(), "right-thumb".to_string()).await;
+        assert!(matches!(result, Err(DeviceError::OperationFailed(_))));
+        assert_eq!(mock.enrolled(0), ["right-thumb"]);
+    }
+}
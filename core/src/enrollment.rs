@@ -0,0 +1,509 @@
+//! Enrollment sessions and progress tracking.
+
+use crate::device_manager::{DeviceError, DeviceManager};
+use crate::fprintd::{self, EnrollStatusEvent};
+use futures_util::StreamExt;
+use log::{error, info, warn};
+use std::time::Duration;
+
+/// Status reported by fprintd's `EnrollStatus` signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrollStatus {
+    StagePassed,
+    RetryScan,
+    SwipeTooShort,
+    FingerNotCentered,
+    RemoveAndRetry,
+    Duplicate,
+    DataFull,
+    Failed,
+    Disconnected,
+    Completed,
+    Other(String),
+}
+
+impl EnrollStatus {
+    /// Parse the status string sent by fprintd.
+    pub fn parse(result: &str) -> Self {
+        match result {
+            "enroll-stage-passed" => Self::StagePassed,
+            "enroll-retry-scan" => Self::RetryScan,
+            "enroll-swipe-too-short" => Self::SwipeTooShort,
+            "enroll-finger-not-centered" => Self::FingerNotCentered,
+            "enroll-remove-and-retry" => Self::RemoveAndRetry,
+            "enroll-duplicate" => Self::Duplicate,
+            "enroll-data-full" => Self::DataFull,
+            "enroll-failed" => Self::Failed,
+            "enroll-disconnected" => Self::Disconnected,
+            "enroll-completed" => Self::Completed,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// The fprintd status string.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::StagePassed => "enroll-stage-passed",
+            Self::RetryScan => "enroll-retry-scan",
+            Self::SwipeTooShort => "enroll-swipe-too-short",
+            Self::FingerNotCentered => "enroll-finger-not-centered",
+            Self::RemoveAndRetry => "enroll-remove-and-retry",
+            Self::Duplicate => "enroll-duplicate",
+            Self::DataFull => "enroll-data-full",
+            Self::Failed => "enroll-failed",
+            Self::Disconnected => "enroll-disconnected",
+            Self::Completed => "enroll-completed",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// Progress passed to the caller for every status signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EnrollProgress {
+    pub status: EnrollStatus,
+    /// Scans captured so far.
+    pub stage: usize,
+    /// Scans the device needs, when it reports them.
+    pub total_stages: Option<usize>,
+    /// Whether this is the final status of the session.
+    pub done: bool,
+}
+
+/// How an enrollment session ended.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EnrollOutcome {
+    Completed {
+        stages: usize,
+    },
+    Failed(EnrollStatus),
+    /// No status arrived within the timeout, usually because no finger was placed.
+    TimedOut,
+}
+
+/// Counts captured scans across status updates.
+#[derive(Debug, Default, Clone)]
+pub struct EnrollmentState {
+    stage: usize,
+    total_stages: Option<usize>,
+}
+
+impl EnrollmentState {
+    pub fn new(total_stages: Option<usize>) -> Self {
+        Self {
+            stage: 0,
+            total_stages,
+        }
+    }
+
+    /// Record a status update and return the resulting progress.
+    pub fn update(&mut self, event: &EnrollStatusEvent) -> EnrollProgress {
+        let status = EnrollStatus::parse(&event.result);
+        if status == EnrollStatus::StagePassed {
+            self.stage += 1;
+        }
+
+        EnrollProgress {
+            status,
+            stage: self.stage,
+            total_stages: self.total_stages,
+            done: event.done,
+        }
+    }
+
+    /// Scans captured so far.
+    pub fn stage(&self) -> usize {
+        self.stage
+    }
+}
+
+/// Enroll a finger on an already claimed device, reporting every status update.
+///
+/// With a `timeout`, enrollment stops when no status arrives for that long.
+pub async fn enroll_on<F>(
+    device: &fprintd::Device,
+    finger: &str,
+    timeout: Option<Duration>,
+    mut on_progress: F,
+) -> Result<EnrollOutcome, DeviceError>
+where
+    F: FnMut(&EnrollProgress),
+{
+    let total_stages = device
+        .num_enroll_stages()
+        .await
+        .ok()
+        .and_then(|n| usize::try_from(n).ok())
+        .filter(|&n| n > 0);
+    let mut state = EnrollmentState::new(total_stages);
+
+    // Subscribe before starting so no early status is missed
+    let mut statuses = device.receive_enroll_status().await.map_err(|e| {
+        error!("Failed to subscribe to enrollment status: {}", e);
+        DeviceError::OperationFailed(format!("Failed to listen for enrollment status: {}", e))
+    })?;
+
+    info!("Starting enrollment process for finger: '{}'", finger);
+    if let Err(e) = device.enroll_start(finger).await {
+        error!("Failed to start enrollment for '{}': {}", finger, e);
+        let _ = device.enroll_stop().await;
+        return Err(DeviceError::OperationFailed(format!(
+            "Failed to start enrollment: {}",
+            e
+        )));
+    }
+
+    info!("Enrollment started successfully, waiting for finger scans...");
+    let mut outcome = None;
+    loop {
+        let next = match timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, statuses.next()).await {
+                Ok(next) => next,
+                Err(_) => {
+                    warn!("No enrollment status within {:?}, stopping", timeout);
+                    outcome = Some(EnrollOutcome::TimedOut);
+                    break;
+                }
+            },
+            None => statuses.next().await,
+        };
+        let Some(event) = next else {
+            break;
+        };
+
+        info!(
+            "Enrollment status update: result='{}', done={}",
+            event.result, event.done
+        );
+
+        let progress = state.update(&event);
+        on_progress(&progress);
+
+        if progress.done {
+            outcome = Some(match progress.status {
+                EnrollStatus::Completed => EnrollOutcome::Completed {
+                    stages: progress.stage,
+                },
+                status => EnrollOutcome::Failed(status),
+            });
+            break;
+        }
+    }
+
+    if let Err(e) = device.enroll_stop().await {
+        warn!("Failed to stop enrollment: {}", e);
+    }
+
+    match outcome {
+        Some(outcome) => {
+            info!("Enrollment of '{}' finished: {:?}", finger, outcome);
+            Ok(outcome)
+        }
+        None => Err(DeviceError::OperationFailed(
+            "Device stopped reporting enrollment status".to_string(),
+        )),
+    }
+}
+
+/// Claim the first device, enroll a finger and release the device again.
+pub async fn enroll<F>(
+    finger: &str,
+    timeout: Option<Duration>,
+    on_progress: F,
+) -> Result<EnrollOutcome, DeviceError>
+where
+    F: FnMut(&EnrollProgress),
+{
+    enroll_on_bus(&fprintd::Bus::System, finger, timeout, on_progress).await
+}
+
+/// Enroll a finger using fprintd on `bus`.
+pub async fn enroll_on_bus<F>(
+    bus: &fprintd::Bus,
+    finger: &str,
+    timeout: Option<Duration>,
+    on_progress: F,
+) -> Result<EnrollOutcome, DeviceError>
+where
+    F: FnMut(&EnrollProgress),
+{
+    let manager = DeviceManager::acquire_on_bus(bus).await?;
+
+    let result = match manager.device() {
+        Some(device) => enroll_on(device, finger, timeout, on_progress).await,
+        None => Err(DeviceError::OperationFailed(
+            "Device not available".to_string(),
+        )),
+    };
+
+    let _ = manager.release().await;
+    result
+}
+
+/// Update from a session enrolling several fingers; fingers are referred to by index.
+#[derive(Debug)]
+pub enum SessionUpdate<'a> {
+    Started(usize),
+    Progress(usize, &'a EnrollProgress),
+    Finished(usize, &'a Result<EnrollOutcome, DeviceError>),
+}
+
+/// Claim the first device once and enroll several fingers one after another.
+///
+/// `should_stop` is checked before every finger so a session can end between
+/// fingers. Fingers that were not attempted are missing from the result; a
+/// disconnected reader ends the session too.
+pub async fn enroll_many<F, S>(
+    fingers: &[String],
+    timeout: Option<Duration>,
+    on_update: F,
+    should_stop: S,
+) -> Result<Vec<(String, Result<EnrollOutcome, DeviceError>)>, DeviceError>
+where
+    F: FnMut(SessionUpdate<'_>),
+    S: Fn() -> bool,
+{
+    enroll_many_on_bus(
+        &fprintd::Bus::System,
+        fingers,
+        timeout,
+        on_update,
+        should_stop,
+    )
+    .await
+}
+
+/// Enroll several fingers using fprintd on `bus`.
+pub async fn enroll_many_on_bus<F, S>(
+    bus: &fprintd::Bus,
+    fingers: &[String],
+    timeout: Option<Duration>,
+    mut on_update: F,
+    should_stop: S,
+) -> Result<Vec<(String, Result<EnrollOutcome, DeviceError>)>, DeviceError>
+where
+    F: FnMut(SessionUpdate<'_>),
+    S: Fn() -> bool,
+{
+    let manager = DeviceManager::acquire_on_bus(bus).await?;
+    let Some(device) = manager.device() else {
+        let _ = manager.release().await;
+        return Err(DeviceError::OperationFailed(
+            "Device not available".to_string(),
+        ));
+    };
+
+    let mut results = Vec::new();
+    for (index, finger) in fingers.iter().enumerate() {
+        if should_stop() {
+            info!("Enrollment session stopped before '{}'", finger);
+            break;
+        }
+
+        on_update(SessionUpdate::Started(index));
+        let result = enroll_on(device, finger, timeout, |progress| {
+            on_update(SessionUpdate::Progress(index, progress))
+        })
+        .await;
+        on_update(SessionUpdate::Finished(index, &result));
+
+        let disconnected = matches!(
+            result,
+            Ok(EnrollOutcome::Failed(EnrollStatus::Disconnected))
+        );
+        results.push((finger.clone(), result));
+
+        if disconnected {
+            warn!("Device disconnected, ending enrollment session");
+            break;
+        }
+    }
+
+    let _ = manager.release().await;
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fprintd_mock::{MockDevice, MockFprintd};
+
+    fn event(result: &str, done: bool) -> EnrollStatusEvent {
+        EnrollStatusEvent {
+            result: result.to_string(),
+            done,
+        }
+    }
+
+    #[test]
+    fn state_counts_passed_stages_only() {
+        let mut state = EnrollmentState::new(Some(3));
+        state.update(&event("enroll-stage-passed", false));
+        let retry = state.update(&event("enroll-retry-scan", false));
+        assert_eq!(retry.status, EnrollStatus::RetryScan);
+        assert_eq!(retry.stage, 1);
+
+        let done = state.update(&event("enroll-completed", true));
+        assert_eq!(done.stage, 1);
+        assert_eq!(done.total_stages, Some(3));
+        assert!(done.done);
+    }
+
+    #[test]
+    fn unknown_statuses_round_trip() {
+        let status = EnrollStatus::parse("enroll-something-new");
+        assert_eq!(
+            status,
+            EnrollStatus::Other("enroll-something-new".to_string())
+        );
+        assert_eq!(status.as_str(), "enroll-something-new");
+        assert_eq!(
+            EnrollStatus::parse("enroll-completed").as_str(),
+            "enroll-completed"
+        );
+    }
+
+    #[tokio::test]
+    async fn enroll_follows_scripted_statuses() {
+        let device = MockDevice::default().enroll_script(&[
+            ("enroll-stage-passed", false),
+            ("enroll-retry-scan", false),
+            ("enroll-completed", true),
+        ]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let mut statuses = Vec::new();
+        let outcome = enroll_on_bus(&mock.bus(), "left-index-finger", None, |progress| {
+            statuses.push(progress.status.clone());
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(outcome, EnrollOutcome::Completed { stages: 1 });
+        assert_eq!(
+            statuses,
+            [
+                EnrollStatus::StagePassed,
+                EnrollStatus::RetryScan,
+                EnrollStatus::Completed
+            ]
+        );
+        assert_eq!(mock.enrolled(0), ["left-index-finger"]);
+        assert!(!mock.is_claimed(0));
+    }
+
+    #[tokio::test]
+    async fn enroll_reports_failure_status() {
+        let device = MockDevice::default()
+            .enroll_script(&[("enroll-stage-passed", false), ("enroll-failed", true)]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let outcome = enroll_on_bus(&mock.bus(), "right-thumb", None, |_| {})
+            .await
+            .unwrap();
+        assert_eq!(outcome, EnrollOutcome::Failed(EnrollStatus::Failed));
+        assert!(mock.enrolled(0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn enroll_many_uses_one_claim() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let fingers = ["left-thumb".to_string(), "right-thumb".to_string()];
+        let mut started = Vec::new();
+        let mut finished = Vec::new();
+        let results = enroll_many_on_bus(
+            &mock.bus(),
+            &fingers,
+            None,
+            |update| match update {
+                SessionUpdate::Started(index) => started.push(index),
+                SessionUpdate::Finished(index, _) => finished.push(index),
+                SessionUpdate::Progress(..) => {}
+            },
+            || false,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 2);
+        assert!(results
+            .iter()
+            .all(|(_, result)| matches!(result, Ok(EnrollOutcome::Completed { stages: 4 }))));
+        assert_eq!(started, [0, 1]);
+        assert_eq!(finished, [0, 1]);
+        assert_eq!(mock.enrolled(0), ["left-thumb", "right-thumb"]);
+        assert!(!mock.is_claimed(0));
+    }
+
+    #[tokio::test]
+    async fn enroll_many_stops_when_asked() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let fingers = ["left-thumb".to_string(), "right-thumb".to_string()];
+        let attempted = std::cell::Cell::new(0);
+        let results = enroll_many_on_bus(
+            &mock.bus(),
+            &fingers,
+            None,
+            |update| {
+                if let SessionUpdate::Finished(..) = update {
+                    attempted.set(attempted.get() + 1);
+                }
+            },
+            || attempted.get() >= 1,
+        )
+        .await
+        .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert_eq!(results[0].0, "left-thumb");
+        assert_eq!(mock.enrolled(0), ["left-thumb"]);
+    }
+
+    #[tokio::test]
+    async fn enroll_many_ends_on_disconnect() {
+        let device = MockDevice::default().enroll_script(&[("enroll-disconnected", true)]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let fingers = ["left-thumb".to_string(), "right-thumb".to_string()];
+        let results = enroll_many_on_bus(&mock.bus(), &fingers, None, |_| {}, || false)
+            .await
+            .unwrap();
+
+        assert_eq!(results.len(), 1);
+        assert!(matches!(
+            results[0].1,
+            Ok(EnrollOutcome::Failed(EnrollStatus::Disconnected))
+        ));
+        assert!(mock.enrolled(0).is_empty());
+    }
+
+    #[tokio::test]
+    async fn enroll_times_out_without_scans() {
+        let device = MockDevice::default().enroll_script(&[("enroll-stage-passed", false)]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let outcome = enroll_on_bus(
+            &mock.bus(),
+            "left-thumb",
+            Some(Duration::from_millis(300)),
+            |_| {},
+        )
+        .await
+        .unwrap();
+        assert_eq!(outcome, EnrollOutcome::TimedOut);
+        assert!(mock.enrolled(0).is_empty());
+        assert!(!mock.is_claimed(0));
+    }
+
+    #[tokio::test]
+    async fn enroll_rejects_invalid_finger() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let result = enroll_on_bus(&mock.bus(), "sixth-finger", None, |_| {}).await;
+        assert!(matches!(result, Err(DeviceError::OperationFailed(_))));
+        assert!(mock.enrolled(0).is_empty());
+        assert!(!mock.is_claimed(0));
+    }
+}
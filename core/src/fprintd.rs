@@ -2,8 +2,11 @@
 //! Async helpers for fprintd D-Bus interface.
 
 use std::fmt;
+use std::sync::{PoisonError, RwLock};
 
+use futures_util::stream::BoxStream;
 use futures_util::StreamExt;
+use log::warn;
 use serde::{de::DeserializeOwned, Serialize};
 use zbus::zvariant::{OwnedObjectPath, Type};
 use zbus::{Connection, Proxy};
@@ -71,6 +74,9 @@ pub const IFACE_MANAGER: &str = "net.reactivated.Fprint.Manager";
 /// Device interface name.
 pub const IFACE_DEVICE: &str = "net.reactivated.Fprint.Device";
 
+/// Error returned by fprintd when the user has no enrolled prints.
+pub const ERROR_NO_ENROLLED_PRINTS: &str = "net.reactivated.Fprint.Error.NoEnrolledPrints";
+
 /// Supported finger names.
 pub const FINGERS: &[&str] = &[
     "left-thumb",
@@ -85,6 +91,16 @@ pub const FINGERS: &[&str] = &[
     "right-little-finger",
 ];
 
+/// Bus to reach fprintd on.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub enum Bus {
+    /// The system bus, where fprintd runs.
+    #[default]
+    System,
+    /// A bus at a D-Bus address, such as the private bus of a test.
+    Address(String),
+}
+
 /// Async client with system bus connection.
 #[derive(Clone)]
 pub struct Client {
@@ -104,6 +120,24 @@ impl Client {
         Ok(Self { conn })
     }
 
+    /// Connect to fprintd on the system bus.
+    pub async fn connect() -> zbus::Result<Self> {
+        Self::connect_to(&Bus::System).await
+    }
+
+    /// Connect to fprintd on `bus`.
+    pub async fn connect_to(bus: &Bus) -> zbus::Result<Self> {
+        match bus {
+            Bus::System => Self::system().await,
+            Bus::Address(address) => {
+                let conn = zbus::connection::Builder::address(address.as_str())?
+                    .build()
+                    .await?;
+                Ok(Self { conn })
+            }
+        }
+    }
+
     /// Get underlying connection.
     pub fn connection(&self) -> &Connection {
         &self.conn
@@ -298,6 +332,36 @@ impl Device {
         proxy.get_property::<bool>("finger-needed").await
     }
 
+    /// Subscribe to EnrollStatus signals; the subscription is active once this returns.
+    pub async fn receive_enroll_status(
+        &self,
+    ) -> zbus::Result<BoxStream<'static, EnrollStatusEvent>> {
+        let proxy = self.proxy().await?;
+        let stream = proxy.receive_signal("EnrollStatus").await?;
+
+        Ok(stream
+            .filter_map(|msg| async move {
+                let (result, done): (String, bool) = msg.body().deserialize().ok()?;
+                Some(EnrollStatusEvent { result, done })
+            })
+            .boxed())
+    }
+
+    /// Subscribe to VerifyStatus signals; the subscription is active once this returns.
+    pub async fn receive_verify_status(
+        &self,
+    ) -> zbus::Result<BoxStream<'static, VerifyStatusEvent>> {
+        let proxy = self.proxy().await?;
+        let stream = proxy.receive_signal("VerifyStatus").await?;
+
+        Ok(stream
+            .filter_map(|msg| async move {
+                let (result, done): (String, bool) = msg.body().deserialize().ok()?;
+                Some(VerifyStatusEvent { result, done })
+            })
+            .boxed())
+    }
+
     /// Listen for VerifyFingerSelected signal.
     pub async fn listen_verify_finger_selected<F>(&self, mut handler: F) -> zbus::Result<()>
     where
@@ -364,10 +428,61 @@ pub struct EnrollStatusEvent {
     pub done: bool,
 }
 
-/// Find first available device.
+/// Get the D-Bus error name of a failed fprintd call, if any.
+pub fn error_name(error: &zbus::Error) -> Option<&str> {
+    match error {
+        zbus::Error::MethodError(name, _, _) => Some(name.as_str()),
+        _ => None,
+    }
+}
+
+/// Name of the reader the user prefers, used whenever it is connected.
+static PREFERRED_DEVICE: RwLock<Option<String>> = RwLock::new(None);
+
+/// Prefer the reader with this name; `None` goes back to fprintd's default.
+pub fn set_preferred_device(name: Option<String>) {
+    *PREFERRED_DEVICE
+        .write()
+        .unwrap_or_else(PoisonError::into_inner) = name;
+}
+
+/// Name of the preferred reader, if one was chosen.
+pub fn preferred_device() -> Option<String> {
+    PREFERRED_DEVICE
+        .read()
+        .unwrap_or_else(PoisonError::into_inner)
+        .clone()
+}
+
+/// Names of the connected readers.
+pub async fn device_names(client: &Client) -> zbus::Result<Vec<String>> {
+    let mut names = Vec::new();
+    for path in client.manager().get_devices().await? {
+        names.push(client.device(path).name().await?);
+    }
+    Ok(names)
+}
+
+/// Find the device to use: the preferred reader when connected, otherwise
+/// the first available one.
 pub async fn first_device(client: &Client) -> zbus::Result<Option<Device>> {
+    find_device(client, preferred_device().as_deref()).await
+}
+
+/// Find the reader named `preferred`, falling back to the first available device.
+pub async fn find_device(client: &Client, preferred: Option<&str>) -> zbus::Result<Option<Device>> {
     let mgr = client.manager();
 
+    if let Some(preferred) = preferred {
+        for path in mgr.get_devices().await? {
+            let device = client.device(path);
+            if device.name().await.is_ok_and(|name| name == preferred) {
+                return Ok(Some(device));
+            }
+        }
+        warn!("Preferred reader '{}' is not connected", preferred);
+    }
+
     // Try default device first
     if let Ok(path) = mgr.get_default_device().await {
         return Ok(Some(client.device(path)));
@@ -0,0 +1,670 @@
+//! Scriptable stand-in for fprintd used by the async tests.
+//!
+//! `MockFprintd::start` launches a private `dbus-daemon` and serves the
+//! `net.reactivated.Fprint.Manager` and `.Device` interfaces on it; tests
+//! pass `MockFprintd::bus` to the `*_on_bus` functions under test. Devices
+//! follow fprintd's claim semantics and emit scripted enroll/verify status
+//! sequences. Every mock has its own bus, so tests can run in parallel.
+//! `dbus-daemon` must be installed; starting a mock without it panics.
+
+use std::collections::HashMap;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+use zbus::message::Header;
+use zbus::object_server::SignalEmitter;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::Connection;
+
+use crate::fprintd::{self, Bus, FINGERS};
+
+/// Delay between scripted status signals.
+const STEP_DELAY: Duration = Duration::from_millis(50);
+
+/// Minimal bus configuration allowing everything on a private socket.
+const BUS_CONFIG: &str = r#"<!DOCTYPE busconfig PUBLIC "-//freedesktop//DTD D-Bus Bus Configuration 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/busconfig.dtd">
+<busconfig>
+  <type>session</type>
+  <listen>unix:dir=@DIR@</listen>
+  <auth>EXTERNAL</auth>
+  <policy context="default">
+    <allow send_destination="*" eavesdrop="true"/>
+    <allow eavesdrop="true"/>
+    <allow own="*"/>
+  </policy>
+</busconfig>
+"#;
+
+/// Errors returned by the mock, named like fprintd's.
+#[derive(Debug, zbus::DBusError)]
+#[zbus(prefix = "net.reactivated.Fprint.Error")]
+pub enum FprintError {
+    #[zbus(error)]
+    ZBus(zbus::Error),
+    ClaimDevice(String),
+    AlreadyInUse(String),
+    Internal(String),
+    NoEnrolledPrints(String),
+    NoActionInProgress(String),
+    InvalidFingername(String),
+    NoSuchDevice(String),
+}
+
+impl FprintError {
+    /// Builds an error from its short D-Bus name (e.g. `"AlreadyInUse"`).
+    fn named(name: &str, message: String) -> Self {
+        match name {
+            "ClaimDevice" => Self::ClaimDevice(message),
+            "AlreadyInUse" => Self::AlreadyInUse(message),
+            "NoEnrolledPrints" => Self::NoEnrolledPrints(message),
+            "NoActionInProgress" => Self::NoActionInProgress(message),
+            "InvalidFingername" => Self::InvalidFingername(message),
+            "NoSuchDevice" => Self::NoSuchDevice(message),
+            _ => Self::Internal(message),
+        }
+    }
+}
+
+/// Configuration of a single mock device.
+#[derive(Debug, Clone)]
+pub struct MockDevice {
+    pub name: String,
+    pub scan_type: String,
+    pub num_enroll_stages: i32,
+    pub enrolled: Vec<String>,
+    pub enroll_script: Vec<(String, bool)>,
+    pub verify_script: Vec<(String, bool)>,
+    pub failures: HashMap<String, String>,
+}
+
+impl Default for MockDevice {
+    fn default() -> Self {
+        let mut enroll_script = vec![("enroll-stage-passed".to_string(), false); 4];
+        enroll_script.push(("enroll-completed".to_string(), true));
+
+        Self {
+            name: "Mock Fingerprint Sensor".to_string(),
+            scan_type: "press".to_string(),
+            num_enroll_stages: 5,
+            enrolled: Vec::new(),
+            enroll_script,
+            verify_script: vec![("verify-match".to_string(), true)],
+            failures: HashMap::new(),
+        }
+    }
+}
+
+impl MockDevice {
+    /// Start with these fingers already enrolled.
+    pub fn enrolled(mut self, fingers: &[&str]) -> Self {
+        self.enrolled = fingers.iter().map(|f| f.to_string()).collect();
+        self
+    }
+
+    /// Status sequence emitted after `EnrollStart`.
+    pub fn enroll_script(mut self, script: &[(&str, bool)]) -> Self {
+        self.enroll_script = to_script(script);
+        self
+    }
+
+    /// Status sequence emitted after `VerifyStart`.
+    pub fn verify_script(mut self, script: &[(&str, bool)]) -> Self {
+        self.verify_script = to_script(script);
+        self
+    }
+
+    /// Make `method` fail with the named fprintd error.
+    pub fn failing(mut self, method: &str, error: &str) -> Self {
+        self.failures.insert(method.to_string(), error.to_string());
+        self
+    }
+}
+
+fn to_script(script: &[(&str, bool)]) -> Vec<(String, bool)> {
+    script
+        .iter()
+        .map(|(status, done)| (status.to_string(), *done))
+        .collect()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Action {
+    Enroll,
+    Verify,
+}
+
+/// Mutable device state shared between the D-Bus object and the test.
+#[derive(Debug, Default)]
+struct DeviceState {
+    enrolled: Vec<String>,
+    claimed_by: Option<String>,
+    action: Option<Action>,
+}
+
+type SharedState = Arc<Mutex<DeviceState>>;
+
+struct ManagerObject {
+    devices: Vec<OwnedObjectPath>,
+}
+
+#[zbus::interface(name = "net.reactivated.Fprint.Manager")]
+impl ManagerObject {
+    #[zbus(name = "GetDevices")]
+    fn get_devices(&self) -> Vec<OwnedObjectPath> {
+        self.devices.clone()
+    }
+
+    #[zbus(name = "GetDefaultDevice")]
+    fn get_default_device(&self) -> Result<OwnedObjectPath, FprintError> {
+        self.devices
+            .first()
+            .cloned()
+            .ok_or_else(|| FprintError::NoSuchDevice("No devices available".to_string()))
+    }
+}
+
+struct DeviceObject {
+    config: MockDevice,
+    state: SharedState,
+}
+
+impl DeviceObject {
+    fn injected(&self, method: &str) -> Result<(), FprintError> {
+        match self.config.failures.get(method) {
+            Some(name) => Err(FprintError::named(name, format!("{} failed", method))),
+            None => Ok(()),
+        }
+    }
+
+    fn require_claim(&self, header: &Header<'_>) -> Result<(), FprintError> {
+        let sender = header.sender().map(|s| s.to_string());
+        let state = self.state.lock().unwrap();
+        match &state.claimed_by {
+            Some(owner) if Some(owner) == sender.as_ref() => Ok(()),
+            Some(_) => Err(FprintError::AlreadyInUse(
+                "Device was already claimed".to_string(),
+            )),
+            None => Err(FprintError::ClaimDevice(
+                "Device was not claimed before use".to_string(),
+            )),
+        }
+    }
+
+    fn start_action(&self, action: Action) -> Result<(), FprintError> {
+        let mut state = self.state.lock().unwrap();
+        if state.action.is_some() {
+            return Err(FprintError::AlreadyInUse(
+                "Another operation is already in progress".to_string(),
+            ));
+        }
+        state.action = Some(action);
+        Ok(())
+    }
+
+    fn stop_action(&self, action: Action) -> Result<(), FprintError> {
+        let mut state = self.state.lock().unwrap();
+        if state.action != Some(action) {
+            return Err(FprintError::NoActionInProgress(
+                "No operation in progress".to_string(),
+            ));
+        }
+        state.action = None;
+        Ok(())
+    }
+
+    /// Emits the script while the action is still running.
+    fn play_script(
+        &self,
+        emitter: &SignalEmitter<'_>,
+        action: Action,
+        finger: String,
+        script: Vec<(String, bool)>,
+    ) {
+        let emitter = emitter.to_owned();
+        let state = self.state.clone();
+
+        tokio::spawn(async move {
+            for (status, done) in script {
+                tokio::time::sleep(STEP_DELAY).await;
+                if state.lock().unwrap().action != Some(action) {
+                    return;
+                }
+
+                if status == "enroll-completed" {
+                    let mut state = state.lock().unwrap();
+                    if !state.enrolled.contains(&finger) {
+                        state.enrolled.push(finger.clone());
+                    }
+                }
+
+                let _ = match action {
+                    Action::Enroll => Self::enroll_status(&emitter, &status, done).await,
+                    Action::Verify => Self::verify_status(&emitter, &status, done).await,
+                };
+
+                if done {
+                    break;
+                }
+            }
+        });
+    }
+}
+
+#[zbus::interface(name = "net.reactivated.Fprint.Device")]
+impl DeviceObject {
+    #[zbus(name = "Claim")]
+    fn claim(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        _username: &str,
+    ) -> Result<(), FprintError> {
+        self.injected("Claim")?;
+        let mut state = self.state.lock().unwrap();
+        if state.claimed_by.is_some() {
+            return Err(FprintError::AlreadyInUse(
+                "Device was already claimed".to_string(),
+            ));
+        }
+        state.claimed_by = header.sender().map(|s| s.to_string());
+        Ok(())
+    }
+
+    #[zbus(name = "Release")]
+    fn release(&self, #[zbus(header)] header: Header<'_>) -> Result<(), FprintError> {
+        self.injected("Release")?;
+        self.require_claim(&header)?;
+        let mut state = self.state.lock().unwrap();
+        state.claimed_by = None;
+        state.action = None;
+        Ok(())
+    }
+
+    #[zbus(name = "ListEnrolledFingers")]
+    fn list_enrolled_fingers(&self, _username: &str) -> Result<Vec<String>, FprintError> {
+        self.injected("ListEnrolledFingers")?;
+        let state = self.state.lock().unwrap();
+        if state.enrolled.is_empty() {
+            return Err(FprintError::NoEnrolledPrints(
+                "No fingerprints enrolled".to_string(),
+            ));
+        }
+        Ok(state.enrolled.clone())
+    }
+
+    #[zbus(name = "DeleteEnrolledFingers")]
+    fn delete_enrolled_fingers(&self, _username: &str) -> Result<(), FprintError> {
+        self.injected("DeleteEnrolledFingers")?;
+        self.state.lock().unwrap().enrolled.clear();
+        Ok(())
+    }
+
+    #[zbus(name = "DeleteEnrolledFingers2")]
+    fn delete_enrolled_fingers2(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+    ) -> Result<(), FprintError> {
+        self.injected("DeleteEnrolledFingers2")?;
+        self.require_claim(&header)?;
+        self.state.lock().unwrap().enrolled.clear();
+        Ok(())
+    }
+
+    #[zbus(name = "DeleteEnrolledFinger")]
+    fn delete_enrolled_finger(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        finger: &str,
+    ) -> Result<(), FprintError> {
+        self.injected("DeleteEnrolledFinger")?;
+        self.require_claim(&header)?;
+        let mut state = self.state.lock().unwrap();
+        let before = state.enrolled.len();
+        state.enrolled.retain(|f| f != finger);
+        if state.enrolled.len() == before {
+            return Err(FprintError::NoEnrolledPrints(format!(
+                "Finger {} is not enrolled",
+                finger
+            )));
+        }
+        Ok(())
+    }
+
+    #[zbus(name = "EnrollStart")]
+    fn enroll_start(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        finger: &str,
+    ) -> Result<(), FprintError> {
+        self.injected("EnrollStart")?;
+        self.require_claim(&header)?;
+        if !FINGERS.contains(&finger) {
+            return Err(FprintError::InvalidFingername(format!(
+                "Invalid finger name {}",
+                finger
+            )));
+        }
+        self.start_action(Action::Enroll)?;
+        self.play_script(
+            &emitter,
+            Action::Enroll,
+            finger.to_string(),
+            self.config.enroll_script.clone(),
+        );
+        Ok(())
+    }
+
+    #[zbus(name = "EnrollStop")]
+    fn enroll_stop(&self, #[zbus(header)] header: Header<'_>) -> Result<(), FprintError> {
+        self.injected("EnrollStop")?;
+        self.require_claim(&header)?;
+        self.stop_action(Action::Enroll)
+    }
+
+    #[zbus(name = "VerifyStart")]
+    async fn verify_start(
+        &self,
+        #[zbus(header)] header: Header<'_>,
+        #[zbus(signal_emitter)] emitter: SignalEmitter<'_>,
+        finger: &str,
+    ) -> Result<(), FprintError> {
+        self.injected("VerifyStart")?;
+        self.require_claim(&header)?;
+        if self.state.lock().unwrap().enrolled.is_empty() {
+            return Err(FprintError::NoEnrolledPrints(
+                "No fingerprints enrolled".to_string(),
+            ));
+        }
+        self.start_action(Action::Verify)?;
+        Self::verify_finger_selected(&emitter, finger).await?;
+        self.play_script(
+            &emitter,
+            Action::Verify,
+            finger.to_string(),
+            self.config.verify_script.clone(),
+        );
+        Ok(())
+    }
+
+    #[zbus(name = "VerifyStop")]
+    fn verify_stop(&self, #[zbus(header)] header: Header<'_>) -> Result<(), FprintError> {
+        self.injected("VerifyStop")?;
+        self.require_claim(&header)?;
+        self.stop_action(Action::Verify)
+    }
+
+    #[zbus(property, name = "name")]
+    fn name(&self) -> String {
+        self.config.name.clone()
+    }
+
+    #[zbus(property, name = "num-enroll-stages")]
+    fn num_enroll_stages(&self) -> i32 {
+        self.config.num_enroll_stages
+    }
+
+    #[zbus(property, name = "scan-type")]
+    fn scan_type(&self) -> String {
+        self.config.scan_type.clone()
+    }
+
+    #[zbus(property, name = "finger-present")]
+    fn finger_present(&self) -> bool {
+        false
+    }
+
+    #[zbus(property, name = "finger-needed")]
+    fn finger_needed(&self) -> bool {
+        self.state.lock().unwrap().action.is_some()
+    }
+
+    #[zbus(signal, name = "EnrollStatus")]
+    async fn enroll_status(
+        emitter: &SignalEmitter<'_>,
+        result: &str,
+        done: bool,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "VerifyStatus")]
+    async fn verify_status(
+        emitter: &SignalEmitter<'_>,
+        result: &str,
+        done: bool,
+    ) -> zbus::Result<()>;
+
+    #[zbus(signal, name = "VerifyFingerSelected")]
+    async fn verify_finger_selected(emitter: &SignalEmitter<'_>, finger: &str) -> zbus::Result<()>;
+}
+
+/// A running mock fprintd on its own private bus.
+pub struct MockFprintd {
+    // Field order matters: the connection goes before the daemon.
+    _service: Connection,
+    address: String,
+    states: Vec<SharedState>,
+    daemon: Child,
+    _dir: tempfile::TempDir,
+}
+
+impl MockFprintd {
+    /// Starts the service with the given devices on a private bus.
+    pub async fn start(devices: Vec<MockDevice>) -> Self {
+        let dir = tempfile::tempdir().expect("bus directory");
+        let config = dir.path().join("bus.conf");
+        std::fs::write(
+            &config,
+            BUS_CONFIG.replace("@DIR@", &dir.path().display().to_string()),
+        )
+        .expect("write bus configuration");
+
+        let mut daemon = Command::new("dbus-daemon")
+            .arg("--nofork")
+            .arg("--print-address")
+            .arg(format!("--config-file={}", config.display()))
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .unwrap_or_else(|e| panic!("the fprintd mock tests need dbus-daemon: {}", e));
+
+        let mut address = String::new();
+        let stdout = daemon.stdout.take().expect("dbus-daemon stdout");
+        BufReader::new(stdout)
+            .read_line(&mut address)
+            .expect("read dbus-daemon address");
+        let address = address.trim().to_string();
+
+        let paths: Vec<OwnedObjectPath> = (0..devices.len())
+            .map(|i| {
+                OwnedObjectPath::try_from(format!("/net/reactivated/Fprint/Device/{}", i))
+                    .expect("valid object path")
+            })
+            .collect();
+
+        let mut builder = zbus::connection::Builder::address(address.as_str())
+            .expect("mock bus address")
+            .name(fprintd::SERVICE)
+            .expect("service name")
+            .serve_at(
+                fprintd::MANAGER_PATH,
+                ManagerObject {
+                    devices: paths.clone(),
+                },
+            )
+            .expect("serve manager");
+
+        let mut states = Vec::new();
+        for (path, config) in paths.iter().zip(devices) {
+            let state = Arc::new(Mutex::new(DeviceState {
+                enrolled: config.enrolled.clone(),
+                ..Default::default()
+            }));
+            states.push(state.clone());
+            builder = builder
+                .serve_at(path.as_str(), DeviceObject { config, state })
+                .expect("serve device");
+        }
+
+        let service = builder.build().await.expect("connect mock fprintd");
+
+        Self {
+            _service: service,
+            address,
+            states,
+            daemon,
+            _dir: dir,
+        }
+    }
+
+    /// Starts the service with a single default device.
+    pub async fn with_device(device: MockDevice) -> Self {
+        Self::start(vec![device]).await
+    }
+
+    /// The private bus the mock is served on.
+    pub fn bus(&self) -> Bus {
+        Bus::Address(self.address.clone())
+    }
+
+    /// Fingers currently enrolled on a device.
+    pub fn enrolled(&self, device: usize) -> Vec<String> {
+        self.states[device].lock().unwrap().enrolled.clone()
+    }
+
+    /// Whether a device is currently claimed by any client.
+    pub fn is_claimed(&self, device: usize) -> bool {
+        self.states[device].lock().unwrap().claimed_by.is_some()
+    }
+
+    /// Polls until the device is released (the client releases asynchronously on drop).
+    pub async fn wait_released(&self, device: usize) -> bool {
+        for _ in 0..40 {
+            if !self.is_claimed(device) {
+                return true;
+            }
+            tokio::time::sleep(Duration::from_millis(25)).await;
+        }
+        false
+    }
+}
+
+impl Drop for MockFprintd {
+    fn drop(&mut self) {
+        let _ = self.daemon.kill();
+        let _ = self.daemon.wait();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fprintd::Client;
+
+    async fn claimed_device(client: &Client) -> fprintd::Device {
+        let device = fprintd::first_device(client)
+            .await
+            .unwrap()
+            .expect("mock device");
+        device.claim("").await.unwrap();
+        device
+    }
+
+    #[tokio::test]
+    async fn exposes_device_properties() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+        let client = Client::connect_to(&mock.bus()).await.unwrap();
+        let device = claimed_device(&client).await;
+
+        assert_eq!(device.name().await.unwrap(), "Mock Fingerprint Sensor");
+        assert_eq!(device.num_enroll_stages().await.unwrap(), 5);
+        assert_eq!(device.scan_type().await.unwrap(), "press");
+    }
+
+    #[tokio::test]
+    async fn finds_preferred_reader_by_name() {
+        let second = MockDevice {
+            name: "Second Sensor".to_string(),
+            ..MockDevice::default()
+        };
+        let mock = MockFprintd::start(vec![MockDevice::default(), second]).await;
+        let client = Client::connect_to(&mock.bus()).await.unwrap();
+
+        assert_eq!(
+            fprintd::device_names(&client).await.unwrap(),
+            ["Mock Fingerprint Sensor", "Second Sensor"]
+        );
+
+        let preferred = fprintd::find_device(&client, Some("Second Sensor"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(preferred.name().await.unwrap(), "Second Sensor");
+
+        let fallback = fprintd::find_device(&client, Some("Unplugged Sensor"))
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(fallback.name().await.unwrap(), "Mock Fingerprint Sensor");
+    }
+
+    #[tokio::test]
+    async fn verify_emits_scripted_statuses() {
+        let device = MockDevice::default()
+            .enrolled(&["right-index-finger"])
+            .verify_script(&[("verify-retry-scan", false), ("verify-no-match", true)]);
+        let mock = MockFprintd::with_device(device).await;
+        let client = Client::connect_to(&mock.bus()).await.unwrap();
+        let device = claimed_device(&client).await;
+
+        let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel();
+        let listener = device.clone();
+        tokio::spawn(async move {
+            let _ = listener
+                .listen_verify_status(move |evt| {
+                    let _ = tx.send(evt);
+                })
+                .await;
+        });
+        tokio::time::sleep(STEP_DELAY).await;
+
+        device.verify_start("any").await.unwrap();
+        let first = rx.recv().await.unwrap();
+        let second = rx.recv().await.unwrap();
+        assert_eq!(
+            (first.result.as_str(), first.done),
+            ("verify-retry-scan", false)
+        );
+        assert_eq!(
+            (second.result.as_str(), second.done),
+            ("verify-no-match", true)
+        );
+        device.verify_stop().await.unwrap();
+    }
+
+    #[tokio::test]
+    async fn verify_without_prints_fails() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+        let client = Client::connect_to(&mock.bus()).await.unwrap();
+        let device = claimed_device(&client).await;
+
+        let err = device.verify_start("any").await.unwrap_err();
+        assert!(err.to_string().contains("NoEnrolledPrints"), "{}", err);
+    }
+
+    #[tokio::test]
+    async fn unclaimed_device_rejects_operations() {
+        let device = MockDevice::default().enrolled(&["left-thumb"]);
+        let mock = MockFprintd::with_device(device).await;
+        let client = Client::connect_to(&mock.bus()).await.unwrap();
+        let device = fprintd::first_device(&client).await.unwrap().unwrap();
+
+        let err = device
+            .delete_enrolled_finger("left-thumb")
+            .await
+            .unwrap_err();
+        assert!(err.to_string().contains("ClaimDevice"), "{}", err);
+        assert!(device.release().await.is_err());
+    }
+}
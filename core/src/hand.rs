@@ -0,0 +1,173 @@
+//! Layout of the hand diagram used to pick a finger.
+//!
+//! Each hand is drawn palm-down, so the thumbs point towards each other.
+//! Fingers are capsules with a flat base and a round tip, and the palm covers
+//! their bases. Clicks are matched against these shapes rather than against
+//! bounding boxes, which for the slanted thumb would reach over the palm and
+//! the index finger.
+
+/// Size of one hand drawing.
+pub const WIDTH: f64 = 200.0;
+pub const HEIGHT: f64 = 250.0;
+
+/// Palm rectangle (left, top, right, bottom) and its corner radii, for the
+/// right hand.
+pub const PALM: (f64, f64, f64, f64) = (48.0, 120.0, 160.0, 235.0);
+pub const PALM_TOP_RADIUS: f64 = 10.0;
+pub const PALM_BOTTOM_RADIUS: f64 = 35.0;
+
+/// A finger drawn as a capsule from its base to its tip.
+#[derive(Debug)]
+pub struct FingerShape {
+    /// Finger name without the hand, e.g. "index-finger".
+    pub name: &'static str,
+    pub base: (f64, f64),
+    pub tip: (f64, f64),
+    pub width: f64,
+}
+
+/// Fingers of the right hand, from the thumb outwards.
+pub const RIGHT_HAND: [FingerShape; 5] = [
+    FingerShape {
+        name: "thumb",
+        base: (56.0, 196.0),
+        tip: (16.0, 130.0),
+        width: 26.0,
+    },
+    FingerShape {
+        name: "index-finger",
+        base: (64.0, 126.0),
+        tip: (64.0, 40.0),
+        width: 24.0,
+    },
+    FingerShape {
+        name: "middle-finger",
+        base: (92.0, 126.0),
+        tip: (92.0, 20.0),
+        width: 24.0,
+    },
+    FingerShape {
+        name: "ring-finger",
+        base: (120.0, 126.0),
+        tip: (120.0, 32.0),
+        width: 24.0,
+    },
+    FingerShape {
+        name: "little-finger",
+        base: (147.0, 126.0),
+        tip: (147.0, 62.0),
+        width: 22.0,
+    },
+];
+
+/// Which hand a diagram shows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Hand {
+    Left,
+    Right,
+}
+
+impl Hand {
+    /// Prefix of this hand's finger names in fprintd, e.g. "left".
+    pub fn prefix(self) -> &'static str {
+        match self {
+            Hand::Left => "left",
+            Hand::Right => "right",
+        }
+    }
+
+    /// The left hand is the right hand mirrored.
+    pub fn x(self, x: f64) -> f64 {
+        match self {
+            Hand::Left => WIDTH - x,
+            Hand::Right => x,
+        }
+    }
+}
+
+impl FingerShape {
+    /// Whether the point lies in this finger's capsule, under the palm included.
+    fn covers(&self, hand: Hand, x: f64, y: f64) -> bool {
+        let (bx, by) = (hand.x(self.base.0), self.base.1);
+        let (tx, ty) = (hand.x(self.tip.0), self.tip.1);
+        let length = (tx - bx).hypot(ty - by);
+        let radius = self.width / 2.0;
+
+        // Position along the finger and distance from its centre line
+        let (dx, dy) = ((tx - bx) / length, (ty - by) / length);
+        let along = (x - bx) * dx + (y - by) * dy;
+        let across = ((x - bx) * dy - (y - by) * dx).abs();
+
+        ((0.0..=length).contains(&along) && across <= radius) || (x - tx).hypot(y - ty) <= radius
+    }
+}
+
+/// Whether the point lies on the palm.
+pub fn palm_contains(hand: Hand, x: f64, y: f64) -> bool {
+    let (left, top, right, bottom) = PALM;
+    let (left, right) = (
+        hand.x(left).min(hand.x(right)),
+        hand.x(left).max(hand.x(right)),
+    );
+    if !(left..=right).contains(&x) || !(top..=bottom).contains(&y) {
+        return false;
+    }
+
+    // Outside the rounded corners, the point has to be within their arcs
+    let (radius, cy) = if y < top + PALM_TOP_RADIUS {
+        (PALM_TOP_RADIUS, top + PALM_TOP_RADIUS)
+    } else if y > bottom - PALM_BOTTOM_RADIUS {
+        (PALM_BOTTOM_RADIUS, bottom - PALM_BOTTOM_RADIUS)
+    } else {
+        return true;
+    };
+    let cx = x.clamp(left + radius, right - radius);
+    (x - cx).hypot(y - cy) <= radius
+}
+
+/// The finger drawn at a point, if any; the palm hides the fingers' bases.
+pub fn finger_at(hand: Hand, x: f64, y: f64) -> Option<&'static FingerShape> {
+    if palm_contains(hand, x, y) {
+        return None;
+    }
+    RIGHT_HAND.iter().find(|finger| finger.covers(hand, x, y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name_at(hand: Hand, x: f64, y: f64) -> Option<&'static str> {
+        finger_at(hand, x, y).map(|finger| finger.name)
+    }
+
+    #[test]
+    fn palm_is_not_a_finger() {
+        assert_eq!(name_at(Hand::Right, 100.0, 180.0), None);
+        // Inside the thumb's bounding box, where it meets the palm
+        assert_eq!(name_at(Hand::Right, 60.0, 190.0), None);
+    }
+
+    #[test]
+    fn thumb_and_index_overlap_goes_to_the_drawn_finger() {
+        // Both bounding boxes hold this point, but only the index finger does
+        assert_eq!(name_at(Hand::Right, 53.0, 121.0), Some("index-finger"));
+        assert_eq!(name_at(Hand::Right, 36.0, 163.0), Some("thumb"));
+        // The thumb's bounding box reaches past it towards the wrist
+        assert_eq!(name_at(Hand::Right, 30.0, 200.0), None);
+    }
+
+    #[test]
+    fn left_hand_is_mirrored() {
+        assert_eq!(name_at(Hand::Left, 147.0, 121.0), Some("index-finger"));
+        assert_eq!(name_at(Hand::Left, 164.0, 163.0), Some("thumb"));
+        assert_eq!(name_at(Hand::Left, 100.0, 180.0), None);
+    }
+
+    #[test]
+    fn finger_tips_are_round() {
+        // Just past the middle finger's tip on its centre line, then beside it
+        assert_eq!(name_at(Hand::Right, 92.0, 9.0), Some("middle-finger"));
+        assert_eq!(name_at(Hand::Right, 103.0, 9.0), None);
+    }
+}
@@ -0,0 +1,51 @@
+//! Translation markers and placeholder filling that need no gettext runtime.
+//!
+//! The core crate only marks messages; the GUI looks them up in its catalog.
+//! Messages with values use named `{placeholders}` so translators can
+//! reorder them.
+
+use std::fmt::Display;
+
+/// Mark a string for extraction without translating it yet, for tables
+/// that are translated where they are used.
+pub const fn gettext_noop(msgid: &'static str) -> &'static str {
+    msgid
+}
+
+/// Replace every `{name}` in `template` with its value.
+pub fn fill(template: &str, args: &[(&str, &dyn Display)]) -> String {
+    let mut text = template.to_string();
+    for (name, value) in args {
+        text = text.replace(&format!("{{{}}}", name), &value.to_string());
+    }
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_named_placeholders_in_any_order() {
+        assert_eq!(
+            fill(
+                "{count} fingers on {device}",
+                &[("device", &"Reader"), ("count", &3)]
+            ),
+            "3 fingers on Reader"
+        );
+    }
+
+    #[test]
+    fn fills_repeated_placeholders() {
+        assert_eq!(fill("{a} and {a}", &[("a", &"x")]), "x and x");
+    }
+
+    #[test]
+    fn leaves_unknown_placeholders_and_braces() {
+        assert_eq!(
+            fill("{missing} {name} {}", &[("name", &"value")]),
+            "{missing} value {}"
+        );
+    }
+}
@@ -0,0 +1,32 @@
+//! GTK-free fingerprint and PAM logic shared by the XFPrintD GUI and its
+//! command-line interface.
+//!
+//! - `fprintd`: async D-Bus client for fprintd
+//! - `device_manager`: device claiming with automatic release
+//! - `enrollment` / `verification`: complete async sessions with progress callbacks
+//! - `fingerprints`: enrolled fingerprint queries
+//! - `hand`: layout and hit-testing of the hand diagram's fingers
+//! - `i18n`: translation markers and `{placeholder}` filling
+//! - `logging`: log filtering, rotating log files and journald output
+//! - `pam`: PAM integration through the privileged helper tool
+//! - `preferences`: user preferences of the GUI and their TOML file
+//! - `report`: redacted diagnostic reports for troubleshooting
+//! - `system`: readiness diagnostics for fprintd, the helper and polkit
+//! - `utc`: ISO 8601 timestamps, shared with the helper tool
+
+pub mod device_manager;
+pub mod enrollment;
+pub mod fingerprints;
+pub mod fprintd;
+pub mod hand;
+pub mod i18n;
+pub mod logging;
+pub mod pam;
+pub mod preferences;
+pub mod report;
+pub mod system;
+pub mod utc;
+pub mod verification;
+
+#[cfg(test)]
+mod fprintd_mock;
@@ -0,0 +1,359 @@
+//! Building blocks for persistent logging.
+//!
+//! The `log` facade only routes records; these types decide which records to
+//! keep and where they end up: a `RUST_LOG` style [`Filter`], a size-limited
+//! [`RotatingFile`] and the systemd [`Journal`].
+
+use crate::utc::format_utc;
+use log::{Level, LevelFilter, Record};
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::os::unix::net::UnixDatagram;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::SystemTime;
+
+/// Socket of the journald native protocol.
+pub const JOURNAL_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// One human readable log line, without trailing newline.
+pub fn format_record(record: &Record) -> String {
+    format!(
+        "{} {:<5} [{}] {}",
+        format_utc(SystemTime::now()),
+        record.level(),
+        record.target(),
+        record.args()
+    )
+}
+
+/// Per-target log levels, as understood from `RUST_LOG`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Filter {
+    default: LevelFilter,
+    /// Module prefixes with their levels, longest first.
+    targets: Vec<(String, LevelFilter)>,
+}
+
+impl Filter {
+    /// Filter applying one level to every target.
+    pub fn new(level: LevelFilter) -> Self {
+        Filter {
+            default: level,
+            targets: Vec::new(),
+        }
+    }
+
+    /// Parse a spec such as `warn,xfprintd_gui::ui=debug`.
+    ///
+    /// Unknown levels are skipped; `None` when nothing in the spec is usable.
+    pub fn parse(spec: &str) -> Option<Self> {
+        let mut default = None;
+        let mut targets = Vec::new();
+
+        for directive in spec.split(',').map(str::trim).filter(|d| !d.is_empty()) {
+            match directive.split_once('=') {
+                Some((target, level)) => {
+                    if let Ok(level) = LevelFilter::from_str(level.trim()) {
+                        targets.push((target.trim().to_string(), level));
+                    }
+                }
+                None => {
+                    if let Ok(level) = LevelFilter::from_str(directive) {
+                        default = Some(level);
+                    } else {
+                        // A bare module name enables everything for it
+                        targets.push((directive.to_string(), LevelFilter::Trace));
+                    }
+                }
+            }
+        }
+
+        if default.is_none() && targets.is_empty() {
+            return None;
+        }
+
+        targets.sort_by_key(|(target, _)| std::cmp::Reverse(target.len()));
+        Some(Filter {
+            default: default.unwrap_or(LevelFilter::Error),
+            targets,
+        })
+    }
+
+    /// Level applying to `target`.
+    pub fn level_for(&self, target: &str) -> LevelFilter {
+        self.targets
+            .iter()
+            .find(|(prefix, _)| {
+                target == prefix
+                    || target
+                        .strip_prefix(prefix.as_str())
+                        .is_some_and(|rest| rest.starts_with("::"))
+            })
+            .map(|(_, level)| *level)
+            .unwrap_or(self.default)
+    }
+
+    pub fn enabled(&self, target: &str, level: Level) -> bool {
+        level <= self.level_for(target)
+    }
+
+    /// Most verbose level any target can reach.
+    pub fn max_level(&self) -> LevelFilter {
+        self.targets
+            .iter()
+            .map(|(_, level)| *level)
+            .fold(self.default, Ord::max)
+    }
+}
+
+/// Append-only log file that is rotated once it grows past a size limit.
+///
+/// Rotation renames `name` to `name.1`, `name.1` to `name.2` and so on,
+/// dropping the oldest file beyond `keep`.
+#[derive(Debug)]
+pub struct RotatingFile {
+    path: PathBuf,
+    max_bytes: u64,
+    keep: usize,
+    file: File,
+    len: u64,
+}
+
+impl RotatingFile {
+    /// Open or create `path`, creating its directory if needed.
+    pub fn open(path: impl Into<PathBuf>, max_bytes: u64, keep: usize) -> io::Result<Self> {
+        let path = path.into();
+        if let Some(dir) = path.parent() {
+            fs::DirBuilder::new()
+                .recursive(true)
+                .mode(0o700)
+                .create(dir)?;
+        }
+
+        let file = Self::open_file(&path)?;
+        let len = file.metadata()?.len();
+        let mut rotating = RotatingFile {
+            path,
+            max_bytes,
+            keep,
+            file,
+            len,
+        };
+        if rotating.len >= max_bytes {
+            rotating.rotate()?;
+        }
+        Ok(rotating)
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append one line, rotating first if it would not fit.
+    pub fn write_line(&mut self, line: &str) -> io::Result<()> {
+        let size = line.len() as u64 + 1;
+        if self.len > 0 && self.len + size > self.max_bytes {
+            self.rotate()?;
+        }
+
+        self.file.write_all(line.as_bytes())?;
+        self.file.write_all(b"\n")?;
+        self.len += size;
+        Ok(())
+    }
+
+    /// Path of the `n`th rotated file.
+    pub fn rotated_path(&self, n: usize) -> PathBuf {
+        let mut name = self.path.clone().into_os_string();
+        name.push(format!(".{}", n));
+        PathBuf::from(name)
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        self.file.flush()?;
+
+        if self.keep == 0 {
+            fs::remove_file(&self.path)?;
+        } else {
+            for n in (1..self.keep).rev() {
+                let from = self.rotated_path(n);
+                if from.exists() {
+                    fs::rename(&from, self.rotated_path(n + 1))?;
+                }
+            }
+            fs::rename(&self.path, self.rotated_path(1))?;
+        }
+
+        self.file = Self::open_file(&self.path)?;
+        self.len = 0;
+        Ok(())
+    }
+
+    fn open_file(path: &Path) -> io::Result<File> {
+        OpenOptions::new()
+            .create(true)
+            .append(true)
+            .mode(0o600)
+            .open(path)
+    }
+}
+
+/// Client for the journald native protocol.
+#[derive(Debug)]
+pub struct Journal {
+    socket: UnixDatagram,
+    identifier: String,
+}
+
+impl Journal {
+    /// Connect to the system journal; fails where journald is not running.
+    pub fn connect(identifier: &str) -> io::Result<Self> {
+        Self::connect_to(Path::new(JOURNAL_SOCKET), identifier)
+    }
+
+    pub fn connect_to(socket_path: &Path, identifier: &str) -> io::Result<Self> {
+        let socket = UnixDatagram::unbound()?;
+        socket.connect(socket_path)?;
+        Ok(Journal {
+            socket,
+            identifier: identifier.to_string(),
+        })
+    }
+
+    /// Send a log record.
+    pub fn log(&self, record: &Record) -> io::Result<()> {
+        let message = record.args().to_string();
+        let mut fields = vec![
+            ("PRIORITY", priority(record.level()).to_string()),
+            ("SYSLOG_IDENTIFIER", self.identifier.clone()),
+            ("TARGET", record.target().to_string()),
+            ("MESSAGE", message),
+        ];
+        if let Some(file) = record.file() {
+            fields.push(("CODE_FILE", file.to_string()));
+        }
+        if let Some(line) = record.line() {
+            fields.push(("CODE_LINE", line.to_string()));
+        }
+
+        self.send(&fields)
+    }
+
+    /// Send an entry made of arbitrary upper-case fields.
+    pub fn send(&self, fields: &[(&str, String)]) -> io::Result<()> {
+        self.socket.send(&encode_fields(fields)).map(|_| ())
+    }
+}
+
+/// Syslog priority for a log level.
+pub fn priority(level: Level) -> u8 {
+    match level {
+        Level::Error => 3,
+        Level::Warn => 4,
+        Level::Info => 6,
+        Level::Debug | Level::Trace => 7,
+    }
+}
+
+/// Serialize fields in the journal export format.
+///
+/// Values containing newlines use the length-prefixed binary form.
+fn encode_fields(fields: &[(&str, String)]) -> Vec<u8> {
+    let mut out = Vec::new();
+    for (key, value) in fields {
+        out.extend_from_slice(key.as_bytes());
+        if value.contains('\n') {
+            out.push(b'\n');
+            out.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            out.push(b'=');
+        }
+        out.extend_from_slice(value.as_bytes());
+        out.push(b'\n');
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn filter_parses_rust_log_directives() {
+        let filter = Filter::parse("warn, xfprintd_gui::ui=debug,zbus=off,bogus=loud").unwrap();
+
+        assert_eq!(filter.level_for("xfprintd_gui"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("xfprintd_gui::ui"), LevelFilter::Debug);
+        assert_eq!(
+            filter.level_for("xfprintd_gui::ui::app"),
+            LevelFilter::Debug
+        );
+        assert_eq!(filter.level_for("xfprintd_gui::uix"), LevelFilter::Warn);
+        assert_eq!(filter.level_for("zbus::connection"), LevelFilter::Off);
+        assert_eq!(filter.max_level(), LevelFilter::Debug);
+        assert!(filter.enabled("xfprintd_gui", Level::Error));
+        assert!(!filter.enabled("xfprintd_gui", Level::Info));
+
+        let module_only = Filter::parse("xfprintd_gui_core").unwrap();
+        assert_eq!(
+            module_only.level_for("xfprintd_gui_core::pam"),
+            LevelFilter::Trace
+        );
+        assert_eq!(module_only.level_for("zbus"), LevelFilter::Error);
+
+        assert_eq!(Filter::parse(""), None);
+        assert_eq!(Filter::parse("x=loud"), None);
+    }
+
+    #[test]
+    fn rotating_file_keeps_limited_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("logs/app.log");
+
+        let mut file = RotatingFile::open(&path, 20, 2).unwrap();
+        for line in ["first line", "second line", "third line", "fourth line"] {
+            file.write_line(line).unwrap();
+        }
+
+        let read = |p: PathBuf| fs::read_to_string(p).unwrap();
+        assert_eq!(read(path.clone()), "fourth line\n");
+        assert_eq!(read(file.rotated_path(1)), "third line\n");
+        assert_eq!(read(file.rotated_path(2)), "second line\n");
+        assert!(!file.rotated_path(3).exists());
+
+        // Reopening an oversized file starts a fresh one
+        fs::write(&path, "x".repeat(30)).unwrap();
+        let reopened = RotatingFile::open(&path, 20, 2).unwrap();
+        assert_eq!(read(path.clone()), "");
+        assert_eq!(read(reopened.rotated_path(1)).len(), 30);
+    }
+
+    #[test]
+    fn journal_sends_native_protocol_entries() {
+        let dir = tempfile::tempdir().unwrap();
+        let socket_path = dir.path().join("journal.socket");
+        let server = UnixDatagram::bind(&socket_path).unwrap();
+
+        let journal = Journal::connect_to(&socket_path, "xfprintd-gui").unwrap();
+        journal
+            .log(
+                &Record::builder()
+                    .level(Level::Warn)
+                    .target("xfprintd_gui::pam")
+                    .args(format_args!("two\nlines"))
+                    .build(),
+            )
+            .unwrap();
+
+        let mut buf = [0u8; 512];
+        let len = server.recv(&mut buf).unwrap();
+        let mut expected =
+            b"PRIORITY=4\nSYSLOG_IDENTIFIER=xfprintd-gui\nTARGET=xfprintd_gui::pam\nMESSAGE\n"
+                .to_vec();
+        expected.extend_from_slice(&9u64.to_le_bytes());
+        expected.extend_from_slice(b"two\nlines\n");
+        assert_eq!(&buf[..len], &expected[..]);
+    }
+}
@@ -0,0 +1,638 @@
+//! PAM integration through the privileged helper tool.
+//!
+//! Status queries run the helper directly; changes go through `pkexec`.
+//! These calls block until the helper exits.
+
+use log::{debug, error, info, warn};
+use serde::{Deserialize, Serialize};
+use std::io;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+
+/// Installed location of the privileged helper tool.
+pub const HELPER_PATH: &str = "/opt/xfprintd-gui/xfprintd-gui-helper";
+
+/// Directory holding the PAM patches the helper applies.
+pub const PATCHES_DIR: &str = "/opt/xfprintd-gui/patches/etc/pam.d";
+
+/// Exit code returned when another helper run holds the modification lock.
+pub const EXIT_LOCK_TIMEOUT: i32 = 75;
+
+/// Utility for managing PAM fingerprint configurations.
+pub struct PamHelper;
+
+/// Markers around the block the helper manages in a PAM file.
+pub const BLOCK_BEGIN: &str = "# BEGIN xfprintd-gui";
+pub const BLOCK_END: &str = "# END xfprintd-gui";
+
+/// Suffixes package managers give a new configuration file instead of
+/// overwriting a locally modified one.
+pub const NEW_CONFIG_SUFFIXES: [&str; 2] = [".pacnew", ".rpmnew"];
+
+/// PAM file paths (using configuration).
+pub const SUDO_PATH: &str = "/etc/pam.d/sudo";
+pub const POLKIT_PATH: &str = "/etc/pam.d/polkit-1";
+
+/// Vendor default used when the polkit PAM file has not been customised.
+const POLKIT_DEFAULT_PATH: &str = "/usr/lib/pam.d/polkit-1";
+
+/// pam_fprintd parameters written into the fenced block.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PamParams {
+    /// Seconds to wait for a finger before falling back to password.
+    pub timeout: u32,
+    /// Fingerprint attempts before falling back to password.
+    pub max_tries: u32,
+}
+
+impl Default for PamParams {
+    /// pam_fprintd's own defaults.
+    fn default() -> Self {
+        Self {
+            timeout: 30,
+            max_tries: 3,
+        }
+    }
+}
+
+/// Result of the helper's pre-flight validation for a PAM file.
+#[derive(Debug, Default, Clone)]
+pub struct PreflightReport {
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
+
+impl PreflightReport {
+    /// Check if applying the configuration is considered safe.
+    pub fn is_safe(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// A packaged version of a managed PAM file waiting to be merged.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PendingConfig {
+    /// PAM file in use.
+    pub target: &'static str,
+    /// `.pacnew` or `.rpmnew` file next to it.
+    pub new_file: PathBuf,
+}
+
+/// The `.pacnew`/`.rpmnew` file next to `path`, if a package manager left one.
+pub fn new_config_for(path: &str) -> Option<PathBuf> {
+    NEW_CONFIG_SUFFIXES
+        .iter()
+        .map(|suffix| PathBuf::from(format!("{}{}", path, suffix)))
+        .find(|candidate| candidate.is_file())
+}
+
+/// Managed PAM files with a packaged version waiting to be merged.
+pub fn pending_configs() -> Vec<PendingConfig> {
+    [SUDO_PATH, POLKIT_PATH]
+        .into_iter()
+        .filter_map(|target| {
+            new_config_for(target).map(|new_file| PendingConfig { target, new_file })
+        })
+        .collect()
+}
+
+/// A PAM change recorded by the helper's audit log.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct AuditEntry {
+    /// UTC time of the change.
+    pub time: String,
+    /// Helper command, e.g. "apply" or "remove".
+    pub action: String,
+    /// User who requested the change.
+    pub uid: u32,
+    #[serde(default)]
+    pub user: Option<String>,
+    /// PAM file that was changed.
+    pub target: String,
+    /// SHA-256 of the file before and after the change.
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// "success" or "error".
+    pub result: String,
+    #[serde(default)]
+    pub error: Option<String>,
+}
+
+impl AuditEntry {
+    pub fn succeeded(&self) -> bool {
+        self.result == "success"
+    }
+
+    /// Whether the file content differs after the change.
+    pub fn changed(&self) -> bool {
+        self.before != self.after
+    }
+}
+
+/// Parse the helper's `log` output, newest entry first.
+///
+/// Lines that cannot be parsed are skipped.
+pub fn parse_audit_log(output: &str) -> Vec<AuditEntry> {
+    let mut entries: Vec<AuditEntry> = output
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .filter_map(|line| match serde_json::from_str(line) {
+            Ok(entry) => Some(entry),
+            Err(e) => {
+                warn!("Skipping unreadable audit log line: {}", e);
+                None
+            }
+        })
+        .collect();
+    entries.reverse();
+    entries
+}
+
+impl PamHelper {
+    /// Check configuration status for sudo and polkit services.
+    /// Returns (sudo_configured, polkit_configured).
+    pub fn check_sudo_and_polkit_configurations() -> (bool, bool) {
+        info!("Checking fingerprint authentication status for sudo and polkit PAM services");
+        info!("Performing batch check of PAM configurations");
+
+        match Command::new(HELPER_PATH)
+            .arg("check")
+            .arg(SUDO_PATH)
+            .arg(POLKIT_PATH)
+            .output()
+        {
+            Ok(output) => {
+                let stdout = String::from_utf8_lossy(&output.stdout);
+                let mut sudo = false;
+                let mut polkit = false;
+
+                debug!("PAM helper output:\n{}", stdout);
+
+                for line in stdout.lines() {
+                    if let Some(path) = line.strip_prefix("applied: ") {
+                        match path {
+                            SUDO_PATH => {
+                                sudo = true;
+                                info!("Sudo PAM configuration: ENABLED");
+                            }
+                            POLKIT_PATH => {
+                                polkit = true;
+                                info!("Polkit PAM configuration: ENABLED");
+                            }
+                            _ => {
+                                debug!("Unknown PAM configuration found: {}", path);
+                            }
+                        }
+                    } else if let Some(path) = line.strip_prefix("not-applied: ") {
+                        match path {
+                            SUDO_PATH => info!("Sudo PAM configuration: DISABLED"),
+                            POLKIT_PATH => info!("Polkit PAM configuration: DISABLED"),
+                            _ => debug!("Unknown PAM path not configured: {}", path),
+                        }
+                    }
+                }
+
+                let exit_code = output.status.code().unwrap_or(-1);
+                info!("PAM batch check completed (exit code: {})", exit_code);
+                info!("Final PAM status: sudo={}, polkit={}", sudo, polkit);
+                (sudo, polkit)
+            }
+            Err(e) => {
+                warn!("Batch PAM check failed: {}", e);
+                warn!("Fallback to individual service checks");
+                warn!("Using fallback method: checking PAM configurations individually");
+
+                let sudo = Self::is_configured(SUDO_PATH);
+                let polkit = Self::is_configured(POLKIT_PATH);
+
+                info!(
+                    "Individual PAM check results: sudo={}, polkit={}",
+                    sudo, polkit
+                );
+                info!("Final PAM status: sudo={}, polkit={}", sudo, polkit);
+                (sudo, polkit)
+            }
+        }
+    }
+
+    /// Read the pam_fprintd parameters currently applied for sudo and polkit.
+    /// Returns (sudo_params, polkit_params); None when not applied or not parameterised.
+    pub fn read_sudo_and_polkit_parameters() -> (Option<PamParams>, Option<PamParams>) {
+        let output = match Command::new(HELPER_PATH)
+            .arg("check")
+            .arg(SUDO_PATH)
+            .arg(POLKIT_PATH)
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                warn!("Reading PAM parameters failed: {}", e);
+                return (None, None);
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut sudo = None;
+        let mut polkit = None;
+
+        for line in stdout.lines() {
+            let Some(rest) = line.strip_prefix("params: ") else {
+                continue;
+            };
+            let mut parts = rest.split_whitespace();
+            let Some(path) = parts.next() else {
+                continue;
+            };
+
+            let mut params = PamParams::default();
+            for part in parts {
+                if let Some(value) = part.strip_prefix("max-tries=") {
+                    params.max_tries = value.parse().unwrap_or(params.max_tries);
+                } else if let Some(value) = part.strip_prefix("timeout=") {
+                    params.timeout = value.parse().unwrap_or(params.timeout);
+                }
+            }
+
+            debug!("Applied PAM parameters for '{}': {:?}", path, params);
+            match path {
+                SUDO_PATH => sudo = Some(params),
+                POLKIT_PATH => polkit = Some(params),
+                _ => debug!("Unknown PAM path in parameters output: {}", path),
+            }
+        }
+
+        (sudo, polkit)
+    }
+
+    /// Find system PAM files that already enable fingerprint auth for sudo and polkit.
+    /// Returns (sudo_sources, polkit_sources); empty lists mean not enabled by the system.
+    pub fn inspect_sudo_and_polkit_stacks() -> (Vec<String>, Vec<String>) {
+        info!("Inspecting resolved PAM stacks for sudo and polkit");
+
+        let output = match Command::new(HELPER_PATH)
+            .arg("inspect")
+            .arg(Self::target_argument(SUDO_PATH, None))
+            .arg(Self::target_argument(POLKIT_PATH, None))
+            .output()
+        {
+            Ok(output) => output,
+            Err(e) => {
+                warn!("PAM stack inspection failed: {}", e);
+                return (Vec::new(), Vec::new());
+            }
+        };
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        debug!("PAM helper inspect output:\n{}", stdout);
+
+        let mut sudo = Vec::new();
+        let mut polkit = Vec::new();
+
+        for line in stdout.lines() {
+            let Some(rest) = line.strip_prefix("system-enabled: ") else {
+                continue;
+            };
+            let Some((path, source)) = rest.split_once(" via ") else {
+                continue;
+            };
+            match path {
+                SUDO_PATH => sudo.push(source.to_string()),
+                POLKIT_PATH => polkit.push(source.to_string()),
+                _ => debug!("Unknown PAM path in inspection output: {}", path),
+            }
+        }
+
+        if !sudo.is_empty() {
+            info!(
+                "Sudo fingerprint auth already enabled via: {}",
+                sudo.join(", ")
+            );
+        }
+        if !polkit.is_empty() {
+            info!(
+                "Polkit fingerprint auth already enabled via: {}",
+                polkit.join(", ")
+            );
+        }
+        (sudo, polkit)
+    }
+
+    /// Check if fingerprint configuration is applied for path.
+    fn is_configured(path: &str) -> bool {
+        info!("Checking PAM configuration for path: '{}'", path);
+
+        match Command::new(HELPER_PATH).arg("check").arg(path).status() {
+            Ok(status) => {
+                let configured = status.success();
+                if configured {
+                    info!("PAM path '{}' has fingerprint authentication enabled", path);
+                } else {
+                    info!(
+                        "PAM path '{}' does not have fingerprint authentication",
+                        path
+                    );
+                }
+                configured
+            }
+            Err(e) => {
+                error!("Failed to check PAM configuration for '{}': {}", path, e);
+                error!(
+                    "Helper tool might not be installed or accessible at: {}",
+                    HELPER_PATH
+                );
+                false
+            }
+        }
+    }
+
+    /// Build the helper target argument (JSON object with optional default file and parameters).
+    fn target_argument(path: &str, params: Option<&PamParams>) -> String {
+        let mut json = format!(r#"{{"file":"{}""#, path);
+        if path == POLKIT_PATH {
+            json.push_str(&format!(r#","default":"{}""#, POLKIT_DEFAULT_PATH));
+        }
+        if let Some(params) = params {
+            json.push_str(&format!(
+                r#","max_tries":{},"timeout":{}"#,
+                params.max_tries, params.timeout
+            ));
+        }
+        json.push('}');
+        json
+    }
+
+    /// Run the helper's pre-flight checks for a PAM file path (no privileges needed).
+    pub fn validate_configuration(path: &str, params: &PamParams) -> io::Result<PreflightReport> {
+        info!("Running PAM pre-flight validation for path: '{}'", path);
+
+        let output = Command::new(HELPER_PATH)
+            .arg("validate")
+            .arg(Self::target_argument(path, Some(params)))
+            .output()
+            .map_err(|e| {
+                error!("Failed to execute helper for PAM validation: {}", e);
+                io::Error::other(format!("Failed to execute helper: {}", e))
+            })?;
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        debug!("PAM helper validation output:\n{}", stdout);
+
+        let mut report = PreflightReport::default();
+        let path_prefix = format!("{}: ", path);
+
+        for line in stdout.lines() {
+            if let Some(rest) = line.strip_prefix("warning: ") {
+                let message = rest.strip_prefix(&path_prefix).unwrap_or(rest);
+                warn!("PAM pre-flight warning for '{}': {}", path, message);
+                report.warnings.push(message.to_string());
+            } else if let Some(rest) = line.strip_prefix("error: ") {
+                let message = rest.strip_prefix(&path_prefix).unwrap_or(rest);
+                error!("PAM pre-flight error for '{}': {}", path, message);
+                report.errors.push(message.to_string());
+            }
+        }
+
+        // Any failure without a parsed reason still has to block the change
+        if !output.status.success() && report.errors.is_empty() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            report
+                .errors
+                .push(format!("Helper validation failed: {}", err.trim()));
+        }
+
+        info!(
+            "PAM pre-flight for '{}': {} warning(s), {} error(s)",
+            path,
+            report.warnings.len(),
+            report.errors.len()
+        );
+        Ok(report)
+    }
+
+    /// Error reported when another helper run is still modifying PAM files.
+    fn lock_busy_error(path: &str) -> io::Error {
+        warn!(
+            "Helper lock busy while changing '{}' - another PAM change is in progress",
+            path
+        );
+        io::Error::new(
+            io::ErrorKind::WouldBlock,
+            "Another PAM configuration change is in progress. Please try again.",
+        )
+    }
+
+    /// Apply fingerprint configuration for PAM file path using pkexec.
+    pub fn apply_configuration(path: &str, params: &PamParams) -> io::Result<()> {
+        info!(
+            "Applying fingerprint PAM configuration for path: '{}' ({:?})",
+            path, params
+        );
+        info!("Requesting root privileges via pkexec");
+
+        let json_arg = Self::target_argument(path, Some(params));
+
+        let output = Command::new("pkexec")
+            .arg(HELPER_PATH)
+            .arg("apply")
+            .arg(&json_arg)
+            .output()
+            .map_err(|e| {
+                error!("Failed to execute pkexec for PAM configuration: {}", e);
+                error!("Make sure polkit is installed and configured properly");
+                io::Error::other(format!("Failed to execute pkexec: {}", e))
+            })?;
+
+        if output.status.code() == Some(EXIT_LOCK_TIMEOUT) {
+            return Err(Self::lock_busy_error(path));
+        }
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            error!("PAM configuration failed for path '{}': {}", path, err);
+            if !stdout.is_empty() {
+                debug!("Helper stdout: {}", stdout);
+            }
+            return Err(io::Error::other(format!("Helper failed: {}", err)));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        info!(
+            "Successfully applied fingerprint PAM configuration for '{}'",
+            path
+        );
+        if !stdout.is_empty() {
+            info!("Helper response: {}", stdout.trim());
+        }
+        Ok(())
+    }
+
+    /// Remove fingerprint configuration for PAM file path using pkexec.
+    pub fn remove_configuration(path: &str) -> io::Result<()> {
+        info!(
+            "Removing fingerprint PAM configuration for path: '{}'",
+            path
+        );
+        info!("Requesting root privileges via pkexec");
+
+        let output = Command::new("pkexec")
+            .arg(HELPER_PATH)
+            .arg("remove")
+            .arg(path)
+            .output()
+            .map_err(|e| {
+                error!("Failed to execute pkexec for PAM removal: {}", e);
+                error!("Make sure polkit is installed and configured properly");
+                io::Error::other(format!("Failed to execute pkexec: {}", e))
+            })?;
+
+        if output.status.code() == Some(EXIT_LOCK_TIMEOUT) {
+            return Err(Self::lock_busy_error(path));
+        }
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            let stdout = String::from_utf8_lossy(&output.stdout);
+            error!(
+                "PAM configuration removal failed for path '{}': {}",
+                path, err
+            );
+            if !stdout.is_empty() {
+                debug!("Helper stdout: {}", stdout);
+            }
+            return Err(io::Error::other(format!("Helper failed: {}", err)));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        info!(
+            "Successfully removed fingerprint PAM configuration for '{}'",
+            path
+        );
+        if !stdout.is_empty() {
+            info!("Helper response: {}", stdout.trim());
+        }
+        Ok(())
+    }
+
+    /// Replace a PAM file with its `.pacnew`/`.rpmnew` version using pkexec.
+    ///
+    /// The helper keeps the fingerprint block if it was enabled.
+    pub fn merge_new_config(path: &str) -> io::Result<()> {
+        info!("Merging packaged PAM configuration for path: '{}'", path);
+        info!("Requesting root privileges via pkexec");
+
+        let output = Command::new("pkexec")
+            .arg(HELPER_PATH)
+            .arg("merge")
+            .arg(path)
+            .output()
+            .map_err(|e| {
+                error!("Failed to execute pkexec for PAM merge: {}", e);
+                io::Error::other(format!("Failed to execute pkexec: {}", e))
+            })?;
+
+        if output.status.code() == Some(EXIT_LOCK_TIMEOUT) {
+            return Err(Self::lock_busy_error(path));
+        }
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            error!("Merging PAM configuration failed for '{}': {}", path, err);
+            return Err(io::Error::other(format!("Helper failed: {}", err.trim())));
+        }
+
+        info!(
+            "Helper response: {}",
+            String::from_utf8_lossy(&output.stdout).trim()
+        );
+        Ok(())
+    }
+
+    /// Recorded PAM changes, newest first, at most `limit` of them.
+    pub fn history(limit: usize) -> io::Result<Vec<AuditEntry>> {
+        debug!("Reading PAM change history (limit {})", limit);
+
+        let output = Command::new(HELPER_PATH)
+            .arg("log")
+            .arg("--limit")
+            .arg(limit.to_string())
+            .output()
+            .map_err(|e| {
+                error!("Failed to execute helper tool '{}': {}", HELPER_PATH, e);
+                io::Error::other(format!("Failed to execute helper tool: {}", e))
+            })?;
+
+        if !output.status.success() {
+            let err = String::from_utf8_lossy(&output.stderr);
+            error!("Reading PAM change history failed: {}", err.trim());
+            return Err(io::Error::other(format!("Helper failed: {}", err.trim())));
+        }
+
+        Ok(parse_audit_log(&String::from_utf8_lossy(&output.stdout)))
+    }
+
+    /// Raw output of the helper's `check` command, for diagnostics.
+    pub fn check_output(paths: &[&str]) -> io::Result<Output> {
+        Command::new(HELPER_PATH).arg("check").args(paths).output()
+    }
+}
+
+/// Lines between the helper's markers in a PAM file, if a block is present.
+pub fn fenced_block(content: &str) -> Option<String> {
+    let mut lines = content.lines().skip_while(|l| l.trim() != BLOCK_BEGIN);
+    lines.next()?;
+
+    let block: Vec<&str> = lines.take_while(|l| l.trim() != BLOCK_END).collect();
+    Some(block.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn new_config_prefers_pacnew() {
+        let dir = tempfile::tempdir().unwrap();
+        let target = dir.path().join("sudo");
+        let target = target.to_str().unwrap();
+        std::fs::write(target, "#%PAM-1.0\n").unwrap();
+
+        assert_eq!(new_config_for(target), None);
+
+        std::fs::write(format!("{}.rpmnew", target), "").unwrap();
+        assert_eq!(
+            new_config_for(target),
+            Some(PathBuf::from(format!("{}.rpmnew", target)))
+        );
+
+        std::fs::write(format!("{}.pacnew", target), "").unwrap();
+        assert_eq!(
+            new_config_for(target),
+            Some(PathBuf::from(format!("{}.pacnew", target)))
+        );
+    }
+
+    #[test]
+    fn audit_log_is_parsed_newest_first() {
+        let output = concat!(
+            r#"{"time":"2026-01-02T03:04:05Z","action":"apply","uid":1000,"user":"alice","target":"/etc/pam.d/sudo","before":"aa","after":"bb","result":"success"}"#,
+            "\n",
+            "not json\n",
+            r#"{"time":"2026-01-02T03:05:00Z","action":"remove","uid":1000,"target":"/etc/pam.d/sudo","before":null,"after":null,"result":"error","error":"denied"}"#,
+            "\n",
+        );
+
+        let entries = parse_audit_log(output);
+        assert_eq!(entries.len(), 2);
+
+        assert_eq!(entries[0].action, "remove");
+        assert!(!entries[0].succeeded());
+        assert!(!entries[0].changed());
+        assert_eq!(entries[0].user, None);
+        assert_eq!(entries[0].error.as_deref(), Some("denied"));
+
+        assert_eq!(entries[1].user.as_deref(), Some("alice"));
+        assert!(entries[1].succeeded());
+        assert!(entries[1].changed());
+    }
+}
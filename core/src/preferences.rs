@@ -0,0 +1,222 @@
+//! User preferences of the GUI, stored as TOML.
+//!
+//! A missing or unreadable file gives the defaults, and unknown keys are
+//! ignored so older versions can read files written by newer ones. Where the
+//! file lives is up to the application.
+
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::Duration;
+
+/// Settings remembered between runs.
+///
+/// Tables come last: TOML cannot write plain keys after a table.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Preferences {
+    /// Whether the first-run setup was finished or skipped.
+    pub onboarding_completed: bool,
+    /// Name of the reader to use; unset uses fprintd's default reader.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub device: Option<String>,
+    /// Seconds to wait for each enrollment scan; 0 waits indefinitely.
+    pub enroll_timeout: u32,
+    pub appearance: Appearance,
+    /// Main window size from the last run.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub window: Option<WindowSize>,
+    pub colors: ColorOverrides,
+}
+
+/// Light or dark style of the application.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Appearance {
+    /// Follow the desktop's preference.
+    #[default]
+    System,
+    Light,
+    Dark,
+}
+
+/// Size and state of the main window.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WindowSize {
+    pub width: i32,
+    pub height: i32,
+    #[serde(default)]
+    pub maximized: bool,
+}
+
+/// Status colours replacing those of the theme, as CSS colours.
+#[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ColorOverrides {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub accent: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub success: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub warning: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl ColorOverrides {
+    /// The set colours that `parse` accepts, by status name ("accent",
+    /// "success", "warning" or "error"); the others are skipped with a
+    /// warning so a bad value cannot reach a stylesheet.
+    pub fn parsed<T>(&self, parse: impl Fn(&str) -> Option<T>) -> Vec<(&'static str, T)> {
+        [
+            ("accent", &self.accent),
+            ("success", &self.success),
+            ("warning", &self.warning),
+            ("error", &self.error),
+        ]
+        .into_iter()
+        .filter_map(|(name, color)| {
+            let color = color.as_deref()?;
+            let parsed = parse(color);
+            if parsed.is_none() {
+                warn!("Ignoring invalid {} color '{}'", name, color);
+            }
+            Some((name, parsed?))
+        })
+        .collect()
+    }
+}
+
+impl Preferences {
+    /// How long enrollment waits for a scan, `None` for no limit.
+    pub fn enroll_timeout(&self) -> Option<Duration> {
+        (self.enroll_timeout > 0).then(|| Duration::from_secs(self.enroll_timeout.into()))
+    }
+
+    /// Load the preferences from `path`, falling back to the defaults.
+    pub fn load_from(path: &Path) -> Self {
+        match fs::read_to_string(path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+                warn!("Ignoring invalid preferences in {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                warn!("Cannot read preferences from {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the preferences to `path` atomically, creating its directory.
+    pub fn save_to(&self, path: &Path) -> io::Result<()> {
+        let dir = path.parent().unwrap_or(path);
+        fs::create_dir_all(dir)?;
+
+        let content = toml::to_string_pretty(self).map_err(io::Error::other)?;
+
+        let file_name = path.file_name().unwrap_or_default().to_string_lossy();
+        let temp_path = dir.join(format!(".{}.{}.tmp", file_name, std::process::id()));
+        let result = (|| {
+            let mut file = fs::File::create(&temp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp_path, path)
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn saved_preferences_load_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("xfprintd-gui/preferences.toml");
+
+        let preferences = Preferences {
+            onboarding_completed: true,
+            device: Some("Synaptics Sensors".into()),
+            enroll_timeout: 30,
+            appearance: Appearance::Dark,
+            window: Some(WindowSize {
+                width: 800,
+                height: 600,
+                maximized: true,
+            }),
+            colors: ColorOverrides {
+                accent: Some("#3584e4".into()),
+                error: Some("rgb(224,27,36)".into()),
+                ..Default::default()
+            },
+        };
+        preferences.save_to(&path).unwrap();
+
+        assert_eq!(Preferences::load_from(&path), preferences);
+        // No temporary file is left next to the preferences
+        assert_eq!(fs::read_dir(path.parent().unwrap()).unwrap().count(), 1);
+    }
+
+    #[test]
+    fn missing_or_invalid_files_give_the_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("preferences.toml");
+        assert_eq!(Preferences::load_from(&path), Preferences::default());
+
+        fs::write(&path, "enroll_timeout = \"soon\"\n").unwrap();
+        assert_eq!(Preferences::load_from(&path), Preferences::default());
+
+        fs::write(&path, "not toml at all [").unwrap();
+        assert_eq!(Preferences::load_from(&path), Preferences::default());
+
+        // A directory in place of the file cannot be read
+        let unreadable = dir.path().join("unreadable");
+        fs::create_dir(&unreadable).unwrap();
+        assert_eq!(Preferences::load_from(&unreadable), Preferences::default());
+    }
+
+    #[test]
+    fn unknown_and_missing_keys_are_tolerated() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("preferences.toml");
+        fs::write(
+            &path,
+            "enroll_timeout = 10\nfuture_setting = true\n\n[colors]\nwarning = \"orange\"\n",
+        )
+        .unwrap();
+
+        let preferences = Preferences::load_from(&path);
+        assert_eq!(preferences.enroll_timeout(), Some(Duration::from_secs(10)));
+        assert_eq!(preferences.appearance, Appearance::System);
+        assert_eq!(preferences.colors.warning.as_deref(), Some("orange"));
+        assert_eq!(Preferences::default().enroll_timeout(), None);
+    }
+
+    #[test]
+    fn invalid_colors_are_skipped() {
+        let overrides = ColorOverrides {
+            accent: Some("#3584e4".into()),
+            success: Some("not a color; } body { color: red".into()),
+            warning: None,
+            error: Some("#e01b24".into()),
+        };
+        let hex = |color: &str| color.strip_prefix('#').map(str::to_uppercase);
+
+        assert_eq!(
+            overrides.parsed(hex),
+            [
+                ("accent", "3584E4".to_string()),
+                ("error", "E01B24".to_string())
+            ]
+        );
+        assert!(ColorOverrides::default().parsed(hex).is_empty());
+    }
+}
@@ -0,0 +1,386 @@
+//! Diagnostic report for troubleshooting.
+//!
+//! Collects everything usually asked for when fingerprint authentication does
+//! not work into one document. User name, home directory and host name are
+//! redacted from the rendered output.
+
+use crate::fingerprints::scan_enrolled_fingerprints_on_bus;
+use crate::fprintd;
+use crate::pam::{self, PamHelper};
+use crate::system::{self, SystemReport};
+use crate::utc::format_utc;
+use log::{info, warn};
+use serde::Serialize;
+use std::fmt::Write as _;
+use std::io::{self, Write as _};
+use std::os::unix::fs::OpenOptionsExt;
+use std::path::Path;
+use std::time::SystemTime;
+
+/// Output format of a saved report.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Text,
+    Json,
+}
+
+impl ReportFormat {
+    /// JSON for `.json` files, text otherwise.
+    pub fn for_path(path: &Path) -> Self {
+        match path.extension() {
+            Some(ext) if ext.eq_ignore_ascii_case("json") => ReportFormat::Json,
+            _ => ReportFormat::Text,
+        }
+    }
+}
+
+/// A fingerprint reader as reported by fprintd.
+#[derive(Debug, Clone, Serialize)]
+pub struct DeviceInfo {
+    pub name: Option<String>,
+    pub scan_type: Option<String>,
+    pub num_enroll_stages: Option<i32>,
+}
+
+/// Helper and file state for one PAM target.
+#[derive(Debug, Clone, Serialize)]
+pub struct PamTarget {
+    pub path: String,
+    /// Exit code and output of the helper's `check` command.
+    pub check_exit_code: Option<i32>,
+    pub check_output: String,
+    /// Content of the managed block, when present.
+    pub fenced_block: Option<String>,
+}
+
+/// Everything collected for a report.
+#[derive(Debug, Clone, Serialize)]
+pub struct DiagnosticReport {
+    pub app_version: String,
+    pub generated_at: String,
+    pub os: Option<String>,
+    pub devices: Vec<DeviceInfo>,
+    pub device_error: Option<String>,
+    pub enrolled: Vec<String>,
+    pub system: SystemReport,
+    pub pam: Vec<PamTarget>,
+    pub logs: Vec<String>,
+}
+
+impl DiagnosticReport {
+    /// Collect a report; `logs` are the application's recent log lines.
+    pub async fn collect(app_version: &str, logs: Vec<String>) -> Self {
+        Self::collect_on_bus(&fprintd::Bus::System, app_version, logs).await
+    }
+
+    /// Collect a report, looking for fprintd on `bus`.
+    pub async fn collect_on_bus(bus: &fprintd::Bus, app_version: &str, logs: Vec<String>) -> Self {
+        info!("Collecting diagnostic report");
+
+        let (devices, device_error) = match collect_devices(bus).await {
+            Ok(devices) => (devices, None),
+            Err(e) => {
+                warn!("Could not list fingerprint devices for report: {}", e);
+                (Vec::new(), Some(e.to_string()))
+            }
+        };
+
+        let mut enrolled: Vec<String> = scan_enrolled_fingerprints_on_bus(bus)
+            .await
+            .into_iter()
+            .collect();
+        enrolled.sort_by_key(|f| fprintd::FINGERS.iter().position(|known| known == f));
+
+        Self {
+            app_version: app_version.to_string(),
+            generated_at: format_utc(SystemTime::now()),
+            os: os_name(),
+            devices,
+            device_error,
+            enrolled,
+            system: system::diagnose_on_bus(bus).await,
+            pam: [pam::SUDO_PATH, pam::POLKIT_PATH]
+                .into_iter()
+                .map(collect_pam_target)
+                .collect(),
+            logs,
+        }
+    }
+
+    /// Render in the given format.
+    pub fn render(&self, format: ReportFormat) -> String {
+        match format {
+            ReportFormat::Text => self.to_text(),
+            ReportFormat::Json => self.to_json(),
+        }
+    }
+
+    /// Write the rendered report to a file readable only by the user.
+    pub fn save(&self, path: &Path, format: ReportFormat) -> io::Result<()> {
+        let mut file = std::fs::OpenOptions::new()
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .mode(0o600)
+            .open(path)?;
+        file.write_all(self.render(format).as_bytes())?;
+        info!("Diagnostic report saved to {}", path.display());
+        Ok(())
+    }
+
+    /// Render as redacted plain text.
+    pub fn to_text(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "XFPrintD GUI diagnostic report");
+        let _ = writeln!(out, "Generated: {}", self.generated_at);
+        let _ = writeln!(out, "Version: {}", self.app_version);
+        let _ = writeln!(out, "OS: {}", self.os.as_deref().unwrap_or("unknown"));
+
+        let _ = writeln!(out, "\n== Devices ==");
+        if let Some(error) = &self.device_error {
+            let _ = writeln!(out, "error: {}", error);
+        } else if self.devices.is_empty() {
+            let _ = writeln!(out, "none");
+        }
+        for device in &self.devices {
+            let _ = writeln!(
+                out,
+                "{} (scan type: {}, enroll stages: {})",
+                device.name.as_deref().unwrap_or("unknown"),
+                device.scan_type.as_deref().unwrap_or("unknown"),
+                device
+                    .num_enroll_stages
+                    .map_or("unknown".to_string(), |n| n.to_string())
+            );
+        }
+
+        let _ = writeln!(out, "\n== Enrolled fingers ==");
+        if self.enrolled.is_empty() {
+            let _ = writeln!(out, "none");
+        }
+        for finger in &self.enrolled {
+            let _ = writeln!(out, "{}", finger);
+        }
+
+        let _ = writeln!(out, "\n== System ==");
+        for check in &self.system.checks {
+            let _ = writeln!(
+                out,
+                "[{}] {}: {}",
+                check.severity.as_str(),
+                check.kind.title(),
+                check.summary
+            );
+        }
+
+        for target in &self.pam {
+            let _ = writeln!(out, "\n== PAM: {} ==", target.path);
+            let _ = writeln!(
+                out,
+                "helper check (exit {}):",
+                target
+                    .check_exit_code
+                    .map_or("none".to_string(), |c| c.to_string())
+            );
+            let _ = writeln!(out, "{}", target.check_output.trim_end());
+            match &target.fenced_block {
+                Some(block) => {
+                    let _ = writeln!(out, "managed block:\n{}", block);
+                }
+                None => {
+                    let _ = writeln!(out, "managed block: none");
+                }
+            }
+        }
+
+        let _ = writeln!(out, "\n== Recent logs ==");
+        for line in &self.logs {
+            let _ = writeln!(out, "{}", line);
+        }
+
+        Redactor::current().apply(&out)
+    }
+
+    /// Render as redacted, pretty-printed JSON.
+    pub fn to_json(&self) -> String {
+        let json = serde_json::to_string_pretty(self).unwrap_or_else(|e| {
+            serde_json::json!({ "error": format!("Failed to serialise report: {}", e) }).to_string()
+        });
+        Redactor::current().apply(&json)
+    }
+}
+
+async fn collect_devices(bus: &fprintd::Bus) -> zbus::Result<Vec<DeviceInfo>> {
+    let client = fprintd::Client::connect_to(bus).await?;
+    let paths = client.manager().get_devices().await?;
+
+    let mut devices = Vec::new();
+    for path in paths {
+        let device = client.device(path);
+        devices.push(DeviceInfo {
+            name: device.name().await.ok(),
+            scan_type: device.scan_type().await.ok(),
+            num_enroll_stages: device.num_enroll_stages().await.ok(),
+        });
+    }
+    Ok(devices)
+}
+
+fn collect_pam_target(path: &str) -> PamTarget {
+    let (check_exit_code, check_output) = match PamHelper::check_output(&[path]) {
+        Ok(output) => {
+            let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+            text.push_str(&String::from_utf8_lossy(&output.stderr));
+            (output.status.code(), text)
+        }
+        Err(e) => (None, format!("failed to run helper: {}", e)),
+    };
+
+    let fenced_block = std::fs::read_to_string(path)
+        .ok()
+        .and_then(|content| pam::fenced_block(&content));
+
+    PamTarget {
+        path: path.to_string(),
+        check_exit_code,
+        check_output,
+        fenced_block,
+    }
+}
+
+/// `PRETTY_NAME` from os-release.
+fn os_name() -> Option<String> {
+    let content = std::fs::read_to_string("/etc/os-release")
+        .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+        .ok()?;
+    content.lines().find_map(|line| {
+        line.strip_prefix("PRETTY_NAME=")
+            .map(|v| v.trim_matches('"').to_string())
+    })
+}
+
+/// Replaces identifying strings with placeholders.
+struct Redactor {
+    replacements: Vec<(String, &'static str)>,
+}
+
+impl Redactor {
+    /// Redact the current user name, home directory and host name.
+    fn current() -> Self {
+        let mut replacements = Vec::new();
+        if let Some(home) = std::env::var_os("HOME") {
+            replacements.push((home.to_string_lossy().into_owned(), "<home>"));
+        }
+        if let Ok(user) = std::env::var("USER") {
+            replacements.push((user, "<user>"));
+        }
+        if let Ok(host) = std::fs::read_to_string("/proc/sys/kernel/hostname") {
+            replacements.push((host.trim().to_string(), "<host>"));
+        }
+        Self::new(replacements)
+    }
+
+    fn new(mut replacements: Vec<(String, &'static str)>) -> Self {
+        // Very short values would redact unrelated words
+        replacements.retain(|(value, _)| value.len() >= 2 && value != "/");
+        // Longest first, so a home directory is replaced before the user name in it
+        replacements.sort_by_key(|(value, _)| std::cmp::Reverse(value.len()));
+        Self { replacements }
+    }
+
+    fn apply(&self, text: &str) -> String {
+        let mut text = text.to_string();
+        for (value, placeholder) in &self.replacements {
+            text = replace_word(&text, value, placeholder);
+        }
+        text
+    }
+}
+
+/// Replace occurrences of `word` that are not part of a longer identifier.
+fn replace_word(text: &str, word: &str, replacement: &str) -> String {
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_' || c == '-';
+    let mut out = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(pos) = rest.find(word) {
+        let before = rest[..pos].chars().next_back();
+        let after = rest[pos + word.len()..].chars().next();
+        let bounded = !before.is_some_and(is_ident) && !after.is_some_and(is_ident);
+
+        out.push_str(&rest[..pos]);
+        out.push_str(if bounded { replacement } else { word });
+        rest = &rest[pos + word.len()..];
+    }
+    out.push_str(rest);
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fprintd_mock::{MockDevice, MockFprintd};
+
+    #[test]
+    fn redacts_whole_words_only() {
+        let redactor = Redactor::new(vec![
+            ("ann".to_string(), "<user>"),
+            ("/home/ann".to_string(), "<home>"),
+            ("anvil".to_string(), "<host>"),
+        ]);
+
+        assert_eq!(
+            redactor.apply("ann@anvil: /home/ann/.config, annotation by ann"),
+            "<user>@<host>: <home>/.config, annotation by <user>"
+        );
+    }
+
+    #[test]
+    fn ignores_trivial_values() {
+        let redactor = Redactor::new(vec![("/".to_string(), "<home>")]);
+        assert_eq!(redactor.apply("/etc/pam.d/sudo"), "/etc/pam.d/sudo");
+    }
+
+    #[test]
+    fn extracts_fenced_block() {
+        let content = "#%PAM-1.0\n# BEGIN xfprintd-gui\nauth sufficient pam_fprintd.so\n# END xfprintd-gui\nauth include system-auth\n";
+        assert_eq!(
+            pam::fenced_block(content).as_deref(),
+            Some("auth sufficient pam_fprintd.so")
+        );
+        assert_eq!(pam::fenced_block("auth include system-auth\n"), None);
+    }
+
+    #[tokio::test]
+    async fn collects_devices_and_enrolled_fingers() {
+        let device = MockDevice::default().enrolled(&["right-thumb", "left-index-finger"]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let report = DiagnosticReport::collect_on_bus(
+            &mock.bus(),
+            "1.2.3",
+            vec!["INFO started".to_string()],
+        )
+        .await;
+        assert_eq!(report.devices.len(), 1);
+        assert_eq!(
+            report.devices[0].name.as_deref(),
+            Some("Mock Fingerprint Sensor")
+        );
+        assert_eq!(report.enrolled, ["left-index-finger", "right-thumb"]);
+        assert_eq!(report.pam.len(), 2);
+
+        let text = report.to_text();
+        assert!(text.contains("Version: 1.2.3"));
+        assert!(text.contains("INFO started"));
+
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("report.json");
+        report.save(&path, ReportFormat::for_path(&path)).unwrap();
+        let saved = std::fs::read_to_string(&path).unwrap();
+
+        let json: serde_json::Value = serde_json::from_str(&saved).unwrap();
+        assert_eq!(json["enrolled"][1], "right-thumb");
+        assert_eq!(json["system"]["checks"][0]["kind"], "fprintd-service");
+    }
+}
@@ -0,0 +1,740 @@
+//! System readiness diagnostics.
+//!
+//! systemd and the bus daemon are queried over D-Bus and everything else is
+//! read from the filesystem, so the results can be shown to the user instead
+//! of only being logged.
+
+use crate::fprintd;
+use crate::i18n::{self, gettext_noop};
+use crate::pam;
+use log::{info, warn};
+use serde::Serialize;
+use std::fmt::{self, Display};
+use std::os::unix::fs::PermissionsExt;
+use std::path::{Path, PathBuf};
+use zbus::fdo::DBusProxy;
+use zbus::names::{BusName, WellKnownName};
+use zbus::proxy::MethodFlags;
+use zbus::zvariant::OwnedObjectPath;
+use zbus::{Connection, Proxy};
+
+/// systemd unit running fprintd.
+pub const FPRINTD_UNIT: &str = "fprintd.service";
+
+/// Bus name of the polkit authority.
+pub const POLKIT_SERVICE: &str = "org.freedesktop.PolicyKit1";
+
+const SYSTEMD_SERVICE: &str = "org.freedesktop.systemd1";
+const SYSTEMD_PATH: &str = "/org/freedesktop/systemd1";
+const SYSTEMD_IFACE_MANAGER: &str = "org.freedesktop.systemd1.Manager";
+const SYSTEMD_IFACE_UNIT: &str = "org.freedesktop.systemd1.Unit";
+
+/// PAM patch files the helper applies.
+const PATCH_FILES: &[&str] = &["sudo.patch", "polkit-1.patch"];
+
+/// Executables of known polkit authentication agents, including desktop
+/// shells that provide their own.
+const POLKIT_AGENTS: &[&str] = &[
+    "polkit-gnome-authentication-agent-1",
+    "polkit-kde-authentication-agent-1",
+    "polkit-mate-authentication-agent-1",
+    "polkit-efl-authentication-agent-1",
+    "lxpolkit",
+    "lxqt-policykit-agent",
+    "xfce-polkit",
+    "hyprpolkitagent",
+    "soteria",
+    "budgie-polkit-dialog",
+    "io.elementary.desktop.agent-polkit",
+    "ukui-polkit",
+    "gnome-shell",
+    "cinnamon",
+    "cosmic-osd",
+];
+
+/// How serious a diagnostic result is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Ok,
+    Warning,
+    Error,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Ok => "ok",
+            Severity::Warning => "warning",
+            Severity::Error => "error",
+        }
+    }
+}
+
+/// Untranslated message with named `{placeholders}`.
+///
+/// Displays in English for logs and reports; the GUI looks `msgid` up in
+/// its catalog and fills in `args` itself.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Message {
+    pub msgid: &'static str,
+    pub args: Vec<(&'static str, String)>,
+}
+
+impl Message {
+    fn new(msgid: &'static str) -> Self {
+        Self {
+            msgid,
+            args: Vec::new(),
+        }
+    }
+
+    fn arg(mut self, name: &'static str, value: impl Display) -> Self {
+        self.args.push((name, value.to_string()));
+        self
+    }
+
+    /// Fill this message's values into `template`, a translation of `msgid`.
+    pub fn fill(&self, template: &str) -> String {
+        let args: Vec<(&str, &dyn Display)> = self
+            .args
+            .iter()
+            .map(|(name, value)| (*name, value as &dyn Display))
+            .collect();
+        i18n::fill(template, &args)
+    }
+}
+
+impl From<&'static str> for Message {
+    fn from(msgid: &'static str) -> Self {
+        Self::new(msgid)
+    }
+}
+
+impl Display for Message {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.fill(self.msgid))
+    }
+}
+
+impl Serialize for Message {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Component covered by a check.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CheckKind {
+    FprintdService,
+    HelperTool,
+    PamPatches,
+    Pkexec,
+    PolkitAgent,
+}
+
+impl CheckKind {
+    /// Stable identifier for logs and machine-readable output.
+    pub fn id(&self) -> &'static str {
+        match self {
+            CheckKind::FprintdService => "fprintd-service",
+            CheckKind::HelperTool => "helper-tool",
+            CheckKind::PamPatches => "pam-patches",
+            CheckKind::Pkexec => "pkexec",
+            CheckKind::PolkitAgent => "polkit-agent",
+        }
+    }
+
+    /// Human-readable title, untranslated.
+    pub fn title(&self) -> &'static str {
+        match self {
+            CheckKind::FprintdService => gettext_noop("Fingerprint service"),
+            CheckKind::HelperTool => gettext_noop("Helper tool"),
+            CheckKind::PamPatches => gettext_noop("PAM patches"),
+            CheckKind::Pkexec => "pkexec",
+            CheckKind::PolkitAgent => gettext_noop("Authentication agent"),
+        }
+    }
+}
+
+/// Action that can resolve a problem automatically.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Fix {
+    /// Start fprintd through systemd, authorised by polkit.
+    StartFprintd,
+}
+
+impl Fix {
+    /// Button label for the action, untranslated.
+    pub fn label(&self) -> &'static str {
+        match self {
+            Fix::StartFprintd => gettext_noop("Start fprintd"),
+        }
+    }
+
+    /// Sentence telling the user the action failed, untranslated.
+    pub fn failure(&self) -> &'static str {
+        match self {
+            Fix::StartFprintd => gettext_noop("Could not start fprintd."),
+        }
+    }
+}
+
+/// Result of a single check.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Check {
+    pub kind: CheckKind,
+    pub severity: Severity,
+    pub summary: Message,
+    /// What the user can do about a problem.
+    pub hint: Option<Message>,
+    pub fix: Option<Fix>,
+}
+
+impl Check {
+    fn ok(kind: CheckKind, summary: impl Into<Message>) -> Self {
+        Self {
+            kind,
+            severity: Severity::Ok,
+            summary: summary.into(),
+            hint: None,
+            fix: None,
+        }
+    }
+
+    fn problem(
+        kind: CheckKind,
+        severity: Severity,
+        summary: impl Into<Message>,
+        hint: impl Into<Message>,
+    ) -> Self {
+        Self {
+            kind,
+            severity,
+            summary: summary.into(),
+            hint: Some(hint.into()),
+            fix: None,
+        }
+    }
+
+    fn with_fix(mut self, fix: Fix) -> Self {
+        self.fix = Some(fix);
+        self
+    }
+}
+
+/// Results of every check.
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct SystemReport {
+    pub checks: Vec<Check>,
+}
+
+impl SystemReport {
+    /// Checks that did not pass.
+    pub fn problems(&self) -> impl Iterator<Item = &Check> {
+        self.checks.iter().filter(|c| c.severity != Severity::Ok)
+    }
+
+    /// Most serious severity in the report.
+    pub fn severity(&self) -> Severity {
+        self.checks
+            .iter()
+            .map(|c| c.severity)
+            .max()
+            .unwrap_or(Severity::Ok)
+    }
+
+    /// Log every check, warning about problems.
+    pub fn log(&self) {
+        for check in &self.checks {
+            match check.severity {
+                Severity::Ok => info!("{}: {}", check.kind.title(), check.summary),
+                _ => {
+                    warn!("{}: {}", check.kind.title(), check.summary);
+                    if let Some(hint) = &check.hint {
+                        warn!("{}", hint);
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Run every check.
+pub async fn diagnose() -> SystemReport {
+    diagnose_on_bus(&fprintd::Bus::System).await
+}
+
+/// Run every check, looking for fprintd on `bus`.
+pub async fn diagnose_on_bus(bus: &fprintd::Bus) -> SystemReport {
+    SystemReport {
+        checks: vec![
+            check_fprintd_on_bus(bus).await,
+            check_helper_tool(),
+            check_pam_patches(),
+            check_pkexec(),
+            check_polkit_agent().await,
+        ],
+    }
+}
+
+/// fprintd as seen by systemd and the bus daemon.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct FprintdState {
+    /// The bus name currently has an owner.
+    running: bool,
+    /// The bus daemon can start fprintd on demand.
+    activatable: bool,
+    /// systemd `LoadState`, when systemd is reachable.
+    load_state: Option<String>,
+    /// systemd `ActiveState`, when systemd is reachable.
+    active_state: Option<String>,
+}
+
+impl FprintdState {
+    async fn query(conn: &Connection) -> zbus::Result<Self> {
+        let dbus = DBusProxy::new(conn).await?;
+        let name = BusName::try_from(fprintd::SERVICE)?;
+        let running = dbus.name_has_owner(name).await?;
+        let activatable = dbus
+            .list_activatable_names()
+            .await?
+            .iter()
+            .any(|n| n.as_str() == fprintd::SERVICE);
+
+        let (load_state, active_state) = match unit_states(conn, FPRINTD_UNIT).await {
+            Ok((load, active)) => (Some(load), Some(active)),
+            Err(e) => {
+                info!("systemd unit state unavailable: {}", e);
+                (None, None)
+            }
+        };
+
+        Ok(Self {
+            running,
+            activatable,
+            load_state,
+            active_state,
+        })
+    }
+
+    fn check(&self) -> Check {
+        let kind = CheckKind::FprintdService;
+
+        if self.running {
+            return Check::ok(kind, gettext_noop("fprintd is running"));
+        }
+
+        if self.active_state.as_deref() == Some("failed") {
+            return Check::problem(
+                kind,
+                Severity::Error,
+                gettext_noop("fprintd failed to start"),
+                gettext_noop("Check `journalctl -u fprintd` for the cause, then start it again."),
+            )
+            .with_fix(Fix::StartFprintd);
+        }
+
+        if self.activatable {
+            return Check::ok(kind, gettext_noop("fprintd starts on demand"));
+        }
+
+        if self.load_state.as_deref() == Some("not-found") || self.load_state.is_none() {
+            return Check::problem(
+                kind,
+                Severity::Error,
+                gettext_noop("fprintd is not installed"),
+                gettext_noop("Install the fprintd package."),
+            );
+        }
+
+        Check::problem(
+            kind,
+            Severity::Warning,
+            gettext_noop("fprintd is not running"),
+            gettext_noop("Fingerprint operations will fail until fprintd is started."),
+        )
+        .with_fix(Fix::StartFprintd)
+    }
+}
+
+/// Load and active state of a systemd unit.
+async fn unit_states(conn: &Connection, unit: &str) -> zbus::Result<(String, String)> {
+    let manager = Proxy::new(conn, SYSTEMD_SERVICE, SYSTEMD_PATH, SYSTEMD_IFACE_MANAGER).await?;
+    let path: OwnedObjectPath = manager.call("LoadUnit", &(unit,)).await?;
+
+    let unit = Proxy::new(conn, SYSTEMD_SERVICE, path, SYSTEMD_IFACE_UNIT).await?;
+    let load_state: String = unit.get_property("LoadState").await?;
+    let active_state: String = unit.get_property("ActiveState").await?;
+    Ok((load_state, active_state))
+}
+
+/// Check that fprintd is running or can be started on demand.
+pub async fn check_fprintd() -> Check {
+    check_fprintd_on_bus(&fprintd::Bus::System).await
+}
+
+/// Check that fprintd is running on `bus` or can be started on demand.
+pub async fn check_fprintd_on_bus(bus: &fprintd::Bus) -> Check {
+    let state = match fprintd::Client::connect_to(bus).await {
+        Ok(client) => FprintdState::query(client.connection()).await,
+        Err(e) => Err(e),
+    };
+
+    match state {
+        Ok(state) => state.check(),
+        Err(e) => Check::problem(
+            CheckKind::FprintdService,
+            Severity::Error,
+            Message::new(gettext_noop("Cannot reach the system bus: {error}")).arg("error", e),
+            gettext_noop("Fingerprint operations need a running D-Bus system bus."),
+        ),
+    }
+}
+
+/// Check that the helper tool is installed and executable.
+pub fn check_helper_tool() -> Check {
+    helper_tool_check(Path::new(pam::HELPER_PATH))
+}
+
+fn helper_tool_check(path: &Path) -> Check {
+    let kind = CheckKind::HelperTool;
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.is_file() && meta.permissions().mode() & 0o111 != 0 => Check::ok(
+            kind,
+            Message::new(gettext_noop("Installed at {path}")).arg("path", path.display()),
+        ),
+        Ok(_) => Check::problem(
+            kind,
+            Severity::Error,
+            Message::new(gettext_noop("{path} is not executable")).arg("path", path.display()),
+            gettext_noop("Reinstall xfprintd-gui to restore the helper tool."),
+        ),
+        Err(_) => Check::problem(
+            kind,
+            Severity::Error,
+            Message::new(gettext_noop("Not found at {path}")).arg("path", path.display()),
+            gettext_noop("PAM configuration needs the helper tool; reinstall xfprintd-gui."),
+        ),
+    }
+}
+
+/// Check that the PAM patches the helper applies are present.
+pub fn check_pam_patches() -> Check {
+    pam_patches_check(Path::new(pam::PATCHES_DIR))
+}
+
+fn pam_patches_check(dir: &Path) -> Check {
+    let missing: Vec<&str> = PATCH_FILES
+        .iter()
+        .copied()
+        .filter(|name| !dir.join(name).is_file())
+        .collect();
+
+    if missing.is_empty() {
+        Check::ok(
+            CheckKind::PamPatches,
+            Message::new(gettext_noop("Found in {dir}")).arg("dir", dir.display()),
+        )
+    } else {
+        Check::problem(
+            CheckKind::PamPatches,
+            Severity::Error,
+            Message::new(gettext_noop("Missing from {dir}: {files}"))
+                .arg("dir", dir.display())
+                .arg("files", missing.join(", ")),
+            gettext_noop("Reinstall xfprintd-gui to restore the PAM patches."),
+        )
+    }
+}
+
+/// Check that pkexec is installed and setuid root.
+pub fn check_pkexec() -> Check {
+    let kind = CheckKind::Pkexec;
+    let Some(path) = find_executable("pkexec") else {
+        return Check::problem(
+            kind,
+            Severity::Error,
+            gettext_noop("pkexec not found"),
+            gettext_noop("Install polkit; PAM changes need pkexec to run the helper as root."),
+        );
+    };
+
+    match std::fs::metadata(&path) {
+        Ok(meta) if meta.permissions().mode() & 0o4000 == 0 => Check::problem(
+            kind,
+            Severity::Error,
+            Message::new(gettext_noop("{path} is not setuid root")).arg("path", path.display()),
+            gettext_noop("Reinstall polkit to restore pkexec's permissions."),
+        ),
+        _ => Check::ok(
+            kind,
+            Message::new(gettext_noop("Available at {path}")).arg("path", path.display()),
+        ),
+    }
+}
+
+/// Find an executable on `PATH`.
+fn find_executable(name: &str) -> Option<PathBuf> {
+    let path = std::env::var_os("PATH")?;
+    std::env::split_paths(&path)
+        .map(|dir| dir.join(name))
+        .find(|candidate| {
+            std::fs::metadata(candidate)
+                .map(|m| m.is_file() && m.permissions().mode() & 0o111 != 0)
+                .unwrap_or(false)
+        })
+}
+
+/// Check that polkit is reachable and an authentication agent is running.
+pub async fn check_polkit_agent() -> Check {
+    let kind = CheckKind::PolkitAgent;
+
+    if let Ok(conn) = Connection::system().await {
+        if !polkit_available(&conn).await.unwrap_or(true) {
+            return Check::problem(
+                kind,
+                Severity::Error,
+                gettext_noop("polkit is not available on the system bus"),
+                gettext_noop("Install and start polkit."),
+            );
+        }
+    }
+
+    let Some(agent) = running_polkit_agent(Path::new("/proc"), current_uid()) else {
+        let hint = gettext_noop(
+            "pkexec needs an agent to ask for your password; start your desktop's polkit agent.",
+        );
+        return Check::problem(
+            kind,
+            Severity::Warning,
+            gettext_noop("No polkit authentication agent detected"),
+            hint,
+        );
+    };
+    Check::ok(
+        kind,
+        Message::new(gettext_noop("{agent} is running")).arg("agent", agent),
+    )
+}
+
+async fn polkit_available(conn: &Connection) -> zbus::Result<bool> {
+    let dbus = DBusProxy::new(conn).await?;
+    if dbus
+        .name_has_owner(BusName::try_from(POLKIT_SERVICE)?)
+        .await?
+    {
+        return Ok(true);
+    }
+    Ok(dbus
+        .list_activatable_names()
+        .await?
+        .iter()
+        .any(|n| n.as_str() == POLKIT_SERVICE))
+}
+
+fn current_uid() -> u32 {
+    std::fs::metadata("/proc/self")
+        .map(|m| std::os::unix::fs::MetadataExt::uid(&m))
+        .unwrap_or(u32::MAX)
+}
+
+/// Name of a known polkit agent owned by `uid`, found by scanning `proc`.
+fn running_polkit_agent(proc: &Path, uid: u32) -> Option<String> {
+    let entries = std::fs::read_dir(proc).ok()?;
+    for entry in entries.flatten() {
+        let is_pid = entry
+            .file_name()
+            .to_str()
+            .is_some_and(|n| n.bytes().all(|b| b.is_ascii_digit()));
+        if !is_pid {
+            continue;
+        }
+
+        let owner = entry
+            .metadata()
+            .map(|m| std::os::unix::fs::MetadataExt::uid(&m));
+        if owner.ok() != Some(uid) {
+            continue;
+        }
+
+        let Ok(cmdline) = std::fs::read(entry.path().join("cmdline")) else {
+            continue;
+        };
+        let program = cmdline.split(|&b| b == 0).next().unwrap_or_default();
+        let program = String::from_utf8_lossy(program);
+        let name = program.rsplit('/').next().unwrap_or_default();
+        if POLKIT_AGENTS.contains(&name) {
+            return Some(name.to_string());
+        }
+    }
+    None
+}
+
+/// Apply a fix, prompting for authorisation through polkit where needed.
+pub async fn apply_fix(fix: Fix) -> zbus::Result<()> {
+    match fix {
+        Fix::StartFprintd => start_fprintd().await,
+    }
+}
+
+async fn start_fprintd() -> zbus::Result<()> {
+    info!("Starting {} through systemd", FPRINTD_UNIT);
+    let conn = Connection::system().await?;
+    let manager = Proxy::new(&conn, SYSTEMD_SERVICE, SYSTEMD_PATH, SYSTEMD_IFACE_MANAGER).await?;
+
+    let started: zbus::Result<Option<OwnedObjectPath>> = manager
+        .call_with_flags(
+            "StartUnit",
+            MethodFlags::AllowInteractiveAuth.into(),
+            &(FPRINTD_UNIT, "replace"),
+        )
+        .await;
+
+    match started {
+        Ok(_) => Ok(()),
+        Err(e) => {
+            // Without systemd the bus daemon can still activate fprintd.
+            warn!("systemd could not start {}: {}", FPRINTD_UNIT, e);
+            let dbus = DBusProxy::new(&conn).await?;
+            dbus.start_service_by_name(WellKnownName::try_from(fprintd::SERVICE)?, 0)
+                .await?;
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fprintd_mock::{MockDevice, MockFprintd};
+    use std::fs;
+
+    fn state(running: bool, activatable: bool, load: Option<&str>, active: Option<&str>) -> Check {
+        FprintdState {
+            running,
+            activatable,
+            load_state: load.map(str::to_string),
+            active_state: active.map(str::to_string),
+        }
+        .check()
+    }
+
+    #[test]
+    fn fprintd_state_classification() {
+        let running = state(true, true, Some("loaded"), Some("active"));
+        assert_eq!(running.severity, Severity::Ok);
+
+        let on_demand = state(false, true, Some("loaded"), Some("inactive"));
+        assert_eq!(on_demand.severity, Severity::Ok);
+
+        let failed = state(false, true, Some("loaded"), Some("failed"));
+        assert_eq!(failed.severity, Severity::Error);
+        assert_eq!(failed.fix, Some(Fix::StartFprintd));
+
+        let stopped = state(false, false, Some("loaded"), Some("inactive"));
+        assert_eq!(stopped.severity, Severity::Warning);
+        assert_eq!(stopped.fix, Some(Fix::StartFprintd));
+
+        let missing = state(false, false, Some("not-found"), Some("inactive"));
+        assert_eq!(missing.severity, Severity::Error);
+        assert_eq!(missing.fix, None);
+    }
+
+    #[test]
+    fn helper_tool_must_be_executable() {
+        let dir = tempfile::tempdir().unwrap();
+        let helper = dir.path().join("helper");
+        assert_eq!(helper_tool_check(&helper).severity, Severity::Error);
+
+        fs::write(&helper, "#!/bin/sh\n").unwrap();
+        fs::set_permissions(&helper, fs::Permissions::from_mode(0o644)).unwrap();
+        assert_eq!(helper_tool_check(&helper).severity, Severity::Error);
+
+        fs::set_permissions(&helper, fs::Permissions::from_mode(0o755)).unwrap();
+        assert_eq!(helper_tool_check(&helper).severity, Severity::Ok);
+    }
+
+    #[test]
+    fn pam_patches_report_missing_files() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("sudo.patch"), "").unwrap();
+
+        let check = pam_patches_check(dir.path());
+        assert_eq!(check.severity, Severity::Error);
+        let summary = check.summary.to_string();
+        assert!(summary.contains("polkit-1.patch"));
+        assert!(!summary.contains("sudo.patch"));
+
+        fs::write(dir.path().join("polkit-1.patch"), "").unwrap();
+        assert_eq!(pam_patches_check(dir.path()).severity, Severity::Ok);
+    }
+
+    #[test]
+    fn polkit_agent_found_in_proc() {
+        let proc = tempfile::tempdir().unwrap();
+        let uid = current_uid();
+
+        fs::create_dir(proc.path().join("self")).unwrap();
+        fs::create_dir(proc.path().join("100")).unwrap();
+        fs::write(proc.path().join("100/cmdline"), b"/usr/bin/bash\0-l\0").unwrap();
+        assert_eq!(running_polkit_agent(proc.path(), uid), None);
+
+        fs::create_dir(proc.path().join("200")).unwrap();
+        fs::write(
+            proc.path().join("200/cmdline"),
+            b"/usr/lib/polkit-kde-authentication-agent-1\0",
+        )
+        .unwrap();
+        assert_eq!(
+            running_polkit_agent(proc.path(), uid).as_deref(),
+            Some("polkit-kde-authentication-agent-1")
+        );
+        assert_eq!(running_polkit_agent(proc.path(), uid.wrapping_add(1)), None);
+    }
+
+    #[test]
+    fn messages_fill_in_values() {
+        let message = Message::new("Missing from {dir}: {files}")
+            .arg("dir", "/opt")
+            .arg("files", "sudo.patch");
+        assert_eq!(message.to_string(), "Missing from /opt: sudo.patch");
+        assert_eq!(
+            message.fill("{files} fehlt in {dir}"),
+            "sudo.patch fehlt in /opt"
+        );
+        assert_eq!(
+            serde_json::to_string(&message).unwrap(),
+            "\"Missing from /opt: sudo.patch\""
+        );
+    }
+
+    #[test]
+    fn report_severity_is_worst_check() {
+        let mut report = SystemReport {
+            checks: vec![Check::ok(CheckKind::HelperTool, "ok")],
+        };
+        assert_eq!(report.severity(), Severity::Ok);
+        assert_eq!(report.problems().count(), 0);
+
+        report.checks.push(Check::problem(
+            CheckKind::PolkitAgent,
+            Severity::Warning,
+            "missing",
+            "start one",
+        ));
+        assert_eq!(report.severity(), Severity::Warning);
+        assert_eq!(report.problems().count(), 1);
+    }
+
+    #[tokio::test]
+    async fn fprintd_running_on_bus() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let check = check_fprintd_on_bus(&mock.bus()).await;
+        assert_eq!(check.severity, Severity::Ok);
+        assert_eq!(check.summary.to_string(), "fprintd is running");
+    }
+}
@@ -0,0 +1,184 @@
+//! Verification sessions.
+
+use crate::device_manager::{DeviceError, DeviceManager};
+use crate::fprintd;
+use futures_util::StreamExt;
+use log::{error, info, warn};
+
+/// Status reported by fprintd's `VerifyStatus` signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum VerifyStatus {
+    Match,
+    NoMatch,
+    RetryScan,
+    SwipeTooShort,
+    FingerNotCentered,
+    RemoveAndRetry,
+    Disconnected,
+    Other(String),
+}
+
+impl VerifyStatus {
+    /// Parse the status string sent by fprintd.
+    pub fn parse(result: &str) -> Self {
+        match result {
+            "verify-match" => Self::Match,
+            "verify-no-match" => Self::NoMatch,
+            "verify-retry-scan" => Self::RetryScan,
+            "verify-swipe-too-short" => Self::SwipeTooShort,
+            "verify-finger-not-centered" => Self::FingerNotCentered,
+            "verify-remove-and-retry" => Self::RemoveAndRetry,
+            "verify-disconnected" => Self::Disconnected,
+            other => Self::Other(other.to_string()),
+        }
+    }
+
+    /// The fprintd status string.
+    pub fn as_str(&self) -> &str {
+        match self {
+            Self::Match => "verify-match",
+            Self::NoMatch => "verify-no-match",
+            Self::RetryScan => "verify-retry-scan",
+            Self::SwipeTooShort => "verify-swipe-too-short",
+            Self::FingerNotCentered => "verify-finger-not-centered",
+            Self::RemoveAndRetry => "verify-remove-and-retry",
+            Self::Disconnected => "verify-disconnected",
+            Self::Other(other) => other,
+        }
+    }
+}
+
+/// Progress passed to the caller for every status signal.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct VerifyProgress {
+    pub status: VerifyStatus,
+    /// Whether this is the final status of the session.
+    pub done: bool,
+}
+
+/// Verify a finger ("any" for every enrolled finger) on an already claimed device.
+/// Returns the final status; `VerifyStatus::Match` means the finger was recognised.
+pub async fn verify_on<F>(
+    device: &fprintd::Device,
+    finger: &str,
+    mut on_progress: F,
+) -> Result<VerifyStatus, DeviceError>
+where
+    F: FnMut(&VerifyProgress),
+{
+    // Subscribe before starting so no early status is missed
+    let mut statuses = device.receive_verify_status().await.map_err(|e| {
+        error!("Failed to subscribe to verification status: {}", e);
+        DeviceError::OperationFailed(format!("Failed to listen for verification status: {}", e))
+    })?;
+
+    info!("Starting verification for finger: '{}'", finger);
+    if let Err(e) = device.verify_start(finger).await {
+        error!("Failed to start verification for '{}': {}", finger, e);
+        return Err(DeviceError::OperationFailed(format!(
+            "Failed to start verification: {}",
+            e
+        )));
+    }
+
+    let mut outcome = None;
+    while let Some(event) = statuses.next().await {
+        info!(
+            "Verification status update: result='{}', done={}",
+            event.result, event.done
+        );
+
+        let progress = VerifyProgress {
+            status: VerifyStatus::parse(&event.result),
+            done: event.done,
+        };
+        on_progress(&progress);
+
+        if progress.done {
+            outcome = Some(progress.status);
+            break;
+        }
+    }
+
+    if let Err(e) = device.verify_stop().await {
+        warn!("Failed to stop verification: {}", e);
+    }
+
+    outcome.ok_or_else(|| {
+        DeviceError::OperationFailed("Device stopped reporting verification status".to_string())
+    })
+}
+
+/// Claim the first device, verify a finger and release the device again.
+pub async fn verify<F>(finger: &str, on_progress: F) -> Result<VerifyStatus, DeviceError>
+where
+    F: FnMut(&VerifyProgress),
+{
+    verify_on_bus(&fprintd::Bus::System, finger, on_progress).await
+}
+
+/// Verify a finger using fprintd on `bus`.
+pub async fn verify_on_bus<F>(
+    bus: &fprintd::Bus,
+    finger: &str,
+    on_progress: F,
+) -> Result<VerifyStatus, DeviceError>
+where
+    F: FnMut(&VerifyProgress),
+{
+    let manager = DeviceManager::acquire_on_bus(bus).await?;
+
+    let result = match manager.device() {
+        Some(device) => verify_on(device, finger, on_progress).await,
+        None => Err(DeviceError::OperationFailed(
+            "Device not available".to_string(),
+        )),
+    };
+
+    let _ = manager.release().await;
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fprintd_mock::{MockDevice, MockFprintd};
+
+    #[tokio::test]
+    async fn verify_returns_final_status() {
+        let device = MockDevice::default()
+            .enrolled(&["right-index-finger"])
+            .verify_script(&[("verify-retry-scan", false), ("verify-match", true)]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let mut statuses = Vec::new();
+        let result = verify_on_bus(&mock.bus(), "any", |progress| {
+            statuses.push(progress.status.clone())
+        })
+        .await
+        .unwrap();
+
+        assert_eq!(result, VerifyStatus::Match);
+        assert_eq!(statuses, [VerifyStatus::RetryScan, VerifyStatus::Match]);
+        assert!(!mock.is_claimed(0));
+    }
+
+    #[tokio::test]
+    async fn verify_reports_no_match() {
+        let device = MockDevice::default()
+            .enrolled(&["right-index-finger"])
+            .verify_script(&[("verify-no-match", true)]);
+        let mock = MockFprintd::with_device(device).await;
+
+        let result = verify_on_bus(&mock.bus(), "any", |_| {}).await.unwrap();
+        assert_eq!(result, VerifyStatus::NoMatch);
+    }
+
+    #[tokio::test]
+    async fn verify_without_prints_fails_to_start() {
+        let mock = MockFprintd::with_device(MockDevice::default()).await;
+
+        let result = verify_on_bus(&mock.bus(), "any", |_| {}).await;
+        assert!(matches!(result, Err(DeviceError::OperationFailed(_))));
+    }
+}
@@ -0,0 +1,159 @@
+//! Fingerprint subcommands: list, enroll, delete and verify.
+
+use super::Output;
+use crate::core::util::display_finger_name;
+use serde_json::json;
+use xfprintd_gui_core::device_manager::DeviceManager;
+use xfprintd_gui_core::enrollment::{self, EnrollOutcome, EnrollStatus};
+use xfprintd_gui_core::fingerprints::scan_enrolled_fingerprints;
+use xfprintd_gui_core::fprintd;
+use xfprintd_gui_core::pam::PamHelper;
+use xfprintd_gui_core::verification::{self, VerifyStatus};
+
+/// List enrolled fingers.
+pub async fn list(out: Output) -> Result<(), String> {
+    let manager = DeviceManager::acquire().await.map_err(|e| e.to_string())?;
+    let result = manager.enrolled_fingers().await;
+    let _ = manager.release().await;
+
+    let mut fingers = result.map_err(|e| e.to_string())?;
+    fingers.sort_by_key(|f| fprintd::FINGERS.iter().position(|known| known == f));
+
+    out.emit(json!({ "enrolled": fingers }), || {
+        if fingers.is_empty() {
+            "No fingerprints enrolled.".to_string()
+        } else {
+            fingers
+                .iter()
+                .map(|f| format!("{} ({})", display_finger_name(f), f))
+                .collect::<Vec<_>>()
+                .join("\n")
+        }
+    });
+    Ok(())
+}
+
+/// Enroll a finger, printing every status until the device reports completion.
+pub async fn enroll(out: Output, finger: String) -> Result<(), String> {
+    out.note(|| {
+        format!(
+            "Enrolling {}. Place your finger on the scanner…",
+            display_finger_name(&finger).to_lowercase()
+        )
+    });
+
+    let outcome = enrollment::enroll(&finger, None, |progress| {
+        out.emit(
+            json!({
+                "event": "enroll-status",
+                "finger": finger,
+                "result": progress.status.as_str(),
+                "done": progress.done,
+                "stage": progress.stage,
+            }),
+            || enroll_message(&progress.status, progress.stage),
+        );
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match outcome {
+        EnrollOutcome::Completed { .. } => {
+            out.emit(json!({ "event": "enrolled", "finger": finger }), || {
+                format!("{} enrolled.", display_finger_name(&finger))
+            });
+            Ok(())
+        }
+        EnrollOutcome::Failed(status) => Err(format!("Enrollment failed ({})", status.as_str())),
+        EnrollOutcome::TimedOut => Err("Enrollment timed out waiting for a scan".to_string()),
+    }
+}
+
+/// Terminal message for an enrollment status.
+fn enroll_message(status: &EnrollStatus, stage: usize) -> String {
+    match status {
+        EnrollStatus::StagePassed => format!(
+            "Scan {} captured. Lift your finger, then place it again…",
+            stage
+        ),
+        EnrollStatus::RemoveAndRetry => format!(
+            "Retry scan {}. Lift your finger completely and place it again…",
+            stage + 1
+        ),
+        EnrollStatus::RetryScan => format!("Retry scan {}. Place your finger again…", stage + 1),
+        EnrollStatus::SwipeTooShort => format!("Swipe too short (scan {}).", stage + 1),
+        EnrollStatus::FingerNotCentered => format!(
+            "Not centered. Place your finger centered and flat (scan {}).",
+            stage + 1
+        ),
+        EnrollStatus::Duplicate => "Already enrolled. Choose a different finger.".to_string(),
+        EnrollStatus::DataFull => "Fingerprint storage on the device is full.".to_string(),
+        EnrollStatus::Failed => "Enrollment failed.".to_string(),
+        EnrollStatus::Completed => format!("Enrollment complete after {} scans.", stage),
+        other => format!("Status: {} (scan {})", other.as_str(), stage.max(1)),
+    }
+}
+
+/// Delete an enrolled finger, refusing to remove the last one while PAM uses it.
+pub async fn delete(out: Output, finger: String, force: bool) -> Result<(), String> {
+    if !force {
+        let (sudo, polkit) = PamHelper::check_sudo_and_polkit_configurations();
+        if sudo || polkit {
+            let enrolled = scan_enrolled_fingerprints().await;
+            if enrolled.len() == 1 && enrolled.contains(&finger) {
+                return Err(format!(
+                    "{} is the last enrolled fingerprint and fingerprint authentication is enabled; \
+                     disable it first or pass --force",
+                    finger
+                ));
+            }
+        }
+    }
+
+    DeviceManager::delete_finger(finger.clone())
+        .await
+        .map_err(|e| e.to_string())?;
+
+    out.emit(json!({ "event": "deleted", "finger": finger }), || {
+        format!("{} deleted.", display_finger_name(&finger))
+    });
+    Ok(())
+}
+
+/// Verify a finger once, succeeding only on a match.
+pub async fn verify(out: Output, finger: String) -> Result<(), String> {
+    out.note(|| "Place your finger on the scanner…".to_string());
+
+    let result = verification::verify(&finger, |progress| {
+        out.emit(
+            json!({
+                "event": "verify-status",
+                "result": progress.status.as_str(),
+                "done": progress.done,
+            }),
+            || verify_message(&progress.status),
+        );
+    })
+    .await
+    .map_err(|e| e.to_string())?;
+
+    match result {
+        VerifyStatus::Match => Ok(()),
+        VerifyStatus::NoMatch => Err("Fingerprint did not match".to_string()),
+        status => Err(format!("Verification failed ({})", status.as_str())),
+    }
+}
+
+/// Terminal message for a verification status.
+fn verify_message(status: &VerifyStatus) -> String {
+    match status {
+        VerifyStatus::Match => "Fingerprint matched.".to_string(),
+        VerifyStatus::NoMatch => "Fingerprint did not match.".to_string(),
+        VerifyStatus::RetryScan => "Scan again…".to_string(),
+        VerifyStatus::SwipeTooShort => "Swipe too short, try again…".to_string(),
+        VerifyStatus::FingerNotCentered => "Not centered, try again…".to_string(),
+        VerifyStatus::RemoveAndRetry => "Lift your finger and try again…".to_string(),
+        VerifyStatus::Disconnected => "Device disconnected.".to_string(),
+        other => format!("Status: {}", other.as_str()),
+    }
+}
@@ -0,0 +1,229 @@
+//! Headless command-line interface.
+//!
+//! Running `xfprintd-gui` with a subcommand skips the GTK window and drives
+//! the same fprintd and PAM logic from the terminal. With `--json` every
+//! result, progress event and error is printed as one JSON object per line
+//! on stdout.
+
+mod fingerprints;
+mod pam;
+mod report;
+
+use crate::logging::Verbosity;
+use clap::{Parser, Subcommand, ValueEnum};
+use serde_json::{json, Value};
+use std::path::PathBuf;
+use std::process::ExitCode;
+use xfprintd_gui_core::fprintd;
+
+/// Command line arguments; without a subcommand the GUI is started.
+#[derive(Parser, Debug)]
+#[command(
+    name = "xfprintd-gui",
+    version,
+    about = "Manage fingerprints and PAM integration",
+    args_conflicts_with_subcommands = true
+)]
+pub struct Cli {
+    /// Print JSON lines instead of human readable text
+    #[arg(long, global = true)]
+    pub json: bool,
+
+    /// Log debug messages too (overrides RUST_LOG)
+    #[arg(short, long, global = true, conflicts_with = "quiet")]
+    pub verbose: bool,
+
+    /// Only log warnings and errors (overrides RUST_LOG)
+    #[arg(short, long, global = true)]
+    pub quiet: bool,
+
+    /// Save a redacted diagnostic report to FILE ("-" for stdout; JSON with --json or a .json name)
+    #[arg(long, value_name = "FILE")]
+    pub diagnostic_report: Option<PathBuf>,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand, Debug)]
+pub enum Command {
+    /// List enrolled fingerprints for the current user
+    List,
+    /// Enroll a finger, printing scan progress
+    Enroll {
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(fprintd::FINGERS))]
+        finger: String,
+    },
+    /// Delete an enrolled finger
+    Delete {
+        #[arg(value_parser = clap::builder::PossibleValuesParser::new(fprintd::FINGERS))]
+        finger: String,
+
+        /// Delete the last fingerprint even while PAM integration is enabled
+        #[arg(long)]
+        force: bool,
+    },
+    /// Verify a fingerprint against the enrolled ones
+    Verify {
+        /// Finger to verify (default: any enrolled finger)
+        #[arg(
+            default_value = "any",
+            value_parser = clap::builder::PossibleValuesParser::new(
+                std::iter::once("any").chain(fprintd::FINGERS.iter().copied())
+            )
+        )]
+        finger: String,
+    },
+    /// Show or change fingerprint authentication for PAM services
+    Pam {
+        #[command(subcommand)]
+        action: PamAction,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum PamAction {
+    /// Show whether fingerprint authentication is enabled
+    Status {
+        /// Services to show (default: all)
+        services: Vec<PamService>,
+    },
+    /// Enable fingerprint authentication (asks for authorization via pkexec)
+    Enable {
+        service: PamService,
+
+        /// Seconds to wait for a finger before falling back to password
+        #[arg(long, value_parser = clap::value_parser!(u32).range(10..=300))]
+        timeout: Option<u32>,
+
+        /// Fingerprint attempts before falling back to password
+        #[arg(long, value_parser = clap::value_parser!(u32).range(1..=10))]
+        max_tries: Option<u32>,
+    },
+    /// Disable fingerprint authentication (asks for authorization via pkexec)
+    Disable { service: PamService },
+}
+
+/// PAM services managed by the application.
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PamService {
+    /// Terminal authentication (sudo)
+    Sudo,
+    /// Graphical authentication prompts (polkit)
+    Polkit,
+}
+
+impl PamService {
+    pub const ALL: [PamService; 2] = [PamService::Sudo, PamService::Polkit];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PamService::Sudo => "sudo",
+            PamService::Polkit => "polkit",
+        }
+    }
+
+    pub fn path(self) -> &'static str {
+        match self {
+            PamService::Sudo => xfprintd_gui_core::pam::SUDO_PATH,
+            PamService::Polkit => xfprintd_gui_core::pam::POLKIT_PATH,
+        }
+    }
+}
+
+/// Prints results either as text or as JSON lines.
+#[derive(Debug, Clone, Copy)]
+pub struct Output {
+    json: bool,
+}
+
+impl Output {
+    /// Print a result or progress event.
+    pub fn emit(&self, value: Value, text: impl FnOnce() -> String) {
+        if self.json {
+            println!("{}", value);
+        } else {
+            println!("{}", text());
+        }
+    }
+
+    /// Print a message that only makes sense to a human reader.
+    pub fn note(&self, text: impl FnOnce() -> String) {
+        if !self.json {
+            println!("{}", text());
+        }
+    }
+
+    fn error(&self, message: &str) {
+        if self.json {
+            println!("{}", json!({ "error": message }));
+        } else {
+            eprintln!("Error: {}", message);
+        }
+    }
+}
+
+impl Cli {
+    /// Whether the arguments ask for headless operation instead of the GUI.
+    pub fn is_headless(&self) -> bool {
+        self.command.is_some() || self.diagnostic_report.is_some()
+    }
+
+    /// Logging verbosity requested on the command line.
+    pub fn verbosity(&self) -> Verbosity {
+        match (self.verbose, self.quiet) {
+            (true, _) => Verbosity::Verbose,
+            (_, true) => Verbosity::Quiet,
+            _ => Verbosity::Normal,
+        }
+    }
+}
+
+/// Run a headless request to completion and return the process exit code.
+pub fn run(cli: Cli) -> ExitCode {
+    let out = Output { json: cli.json };
+
+    let rt = match tokio::runtime::Builder::new_multi_thread()
+        .enable_all()
+        .build()
+    {
+        Ok(rt) => rt,
+        Err(e) => {
+            out.error(&format!("Failed to start async runtime: {}", e));
+            return ExitCode::FAILURE;
+        }
+    };
+
+    let result = match (cli.diagnostic_report, cli.command) {
+        (Some(path), _) => rt.block_on(report::export(out, path)),
+        (None, Some(command)) => execute(&rt, out, command),
+        (None, None) => Ok(()),
+    };
+
+    match result {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(message) => {
+            out.error(&message);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+/// Run a single subcommand.
+fn execute(rt: &tokio::runtime::Runtime, out: Output, command: Command) -> Result<(), String> {
+    match command {
+        Command::List => rt.block_on(fingerprints::list(out)),
+        Command::Enroll { finger } => rt.block_on(fingerprints::enroll(out, finger)),
+        Command::Delete { finger, force } => rt.block_on(fingerprints::delete(out, finger, force)),
+        Command::Verify { finger } => rt.block_on(fingerprints::verify(out, finger)),
+        Command::Pam { action } => match action {
+            PamAction::Status { services } => pam::status(out, &services),
+            PamAction::Enable {
+                service,
+                timeout,
+                max_tries,
+            } => pam::enable(out, service, timeout, max_tries),
+            PamAction::Disable { service } => pam::disable(out, service),
+        },
+    }
+}
@@ -0,0 +1,117 @@
+//! PAM subcommands: status, enable and disable.
+
+use super::{Output, PamService};
+use serde_json::json;
+use xfprintd_gui_core::pam::{PamHelper, PamParams};
+
+/// Show the PAM integration state of the given services (all when empty).
+pub fn status(out: Output, services: &[PamService]) -> Result<(), String> {
+    let services = if services.is_empty() {
+        &PamService::ALL[..]
+    } else {
+        services
+    };
+
+    let (sudo, polkit) = PamHelper::check_sudo_and_polkit_configurations();
+    let (sudo_params, polkit_params) = PamHelper::read_sudo_and_polkit_parameters();
+    let (sudo_sources, polkit_sources) = PamHelper::inspect_sudo_and_polkit_stacks();
+
+    for &service in services {
+        let (enabled, params, sources) = match service {
+            PamService::Sudo => (sudo, sudo_params, &sudo_sources),
+            PamService::Polkit => (polkit, polkit_params, &polkit_sources),
+        };
+
+        out.emit(
+            json!({
+                "service": service.name(),
+                "path": service.path(),
+                "enabled": enabled,
+                "timeout": params.map(|p| p.timeout),
+                "max_tries": params.map(|p| p.max_tries),
+                "system_sources": sources,
+            }),
+            || {
+                let mut line = format!(
+                    "{}: {}",
+                    service.name(),
+                    if enabled { "enabled" } else { "disabled" }
+                );
+                if let Some(params) = params {
+                    line.push_str(&format!(
+                        " (timeout {}s, {} attempts)",
+                        params.timeout, params.max_tries
+                    ));
+                }
+                if !sources.is_empty() {
+                    line.push_str(&format!(
+                        "; already enabled by system configuration ({})",
+                        sources.join(", ")
+                    ));
+                }
+                line
+            },
+        );
+    }
+
+    Ok(())
+}
+
+/// Enable fingerprint authentication after the helper's pre-flight checks pass.
+pub fn enable(
+    out: Output,
+    service: PamService,
+    timeout: Option<u32>,
+    max_tries: Option<u32>,
+) -> Result<(), String> {
+    let defaults = PamParams::default();
+    let params = PamParams {
+        timeout: timeout.unwrap_or(defaults.timeout),
+        max_tries: max_tries.unwrap_or(defaults.max_tries),
+    };
+
+    let report =
+        PamHelper::validate_configuration(service.path(), &params).map_err(|e| e.to_string())?;
+    for warning in &report.warnings {
+        out.emit(
+            json!({ "service": service.name(), "warning": warning }),
+            || format!("Warning: {}", warning),
+        );
+    }
+    if !report.is_safe() {
+        return Err(format!(
+            "Refusing to enable {}: {}",
+            service.name(),
+            report.errors.join("; ")
+        ));
+    }
+
+    PamHelper::apply_configuration(service.path(), &params).map_err(|e| e.to_string())?;
+
+    out.emit(
+        json!({
+            "service": service.name(),
+            "enabled": true,
+            "timeout": params.timeout,
+            "max_tries": params.max_tries,
+        }),
+        || format!("Fingerprint authentication enabled for {}.", service.name()),
+    );
+    Ok(())
+}
+
+/// Disable fingerprint authentication.
+pub fn disable(out: Output, service: PamService) -> Result<(), String> {
+    PamHelper::remove_configuration(service.path()).map_err(|e| e.to_string())?;
+
+    out.emit(
+        json!({ "service": service.name(), "enabled": false }),
+        || {
+            format!(
+                "Fingerprint authentication disabled for {}.",
+                service.name()
+            )
+        },
+    );
+    Ok(())
+}
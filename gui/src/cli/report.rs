@@ -0,0 +1,38 @@
+//! Diagnostic report export.
+
+use super::Output;
+use crate::{config, logging};
+use serde_json::json;
+use std::io::Write;
+use std::path::PathBuf;
+use xfprintd_gui_core::report::{DiagnosticReport, ReportFormat};
+
+/// Collect a diagnostic report and save it to `path`, or print it for "-".
+pub async fn export(out: Output, path: PathBuf) -> Result<(), String> {
+    let report = DiagnosticReport::collect(config::app_info::VERSION, logging::recent()).await;
+
+    if path.as_os_str() == "-" {
+        let format = if out.json {
+            ReportFormat::Json
+        } else {
+            ReportFormat::Text
+        };
+        let mut stdout = std::io::stdout().lock();
+        return writeln!(stdout, "{}", report.render(format).trim_end())
+            .map_err(|e| format!("Failed to print report: {}", e));
+    }
+
+    let format = if out.json {
+        ReportFormat::Json
+    } else {
+        ReportFormat::for_path(&path)
+    };
+    report
+        .save(&path, format)
+        .map_err(|e| format!("Failed to save report to {}: {}", path.display(), e))?;
+
+    out.emit(json!({ "event": "report-saved", "path": path }), || {
+        format!("Diagnostic report saved to {}.", path.display())
+    });
+    Ok(())
+}
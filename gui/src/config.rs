@@ -1,25 +1,5 @@
 //! Centralized configuration and constants for the application.
 
-/// Color scheme for UI feedback messages.
-pub struct ColorScheme {
-    pub progress: &'static str,
-    pub warning: &'static str,
-    pub error: &'static str,
-    pub success: &'static str,
-    pub neutral: &'static str,
-    pub process: &'static str,
-}
-
-/// Default color scheme for enrollment feedback.
-pub const COLORS: ColorScheme = ColorScheme {
-    progress: "#a277ff", // Purple - successful scan/progress
-    warning: "#ff6ac1",  // Pink - retry/adjustment needed
-    error: "#ff4d6d",    // Red - failure/error
-    success: "#a277ff",  // Purple - completion
-    neutral: "#8a8f98",  // Gray - neutral/fallback
-    process: "#5ea2ff",  // Blue - processing/neutral status
-};
-
 /// Application information constants.
 pub mod app_info {
     pub const NAME: &str = "XFPrintD GUI";
@@ -27,11 +7,6 @@ pub mod app_info {
     pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 }
 
-/// Helper tool configuration.
-pub mod helper {
-    pub const BINARY_PATH: &str = "/opt/xfprintd-gui/xfprintd-gui-helper";
-}
-
 /// UI resource paths for GResource files.
 pub mod resources {
     /// Main application window UI.
@@ -46,13 +21,14 @@ pub mod resources {
     /// Dialog UI resources.
     pub mod dialogs {
         pub const ERROR: &str = "/xyz/xerolinux/xfprintd_gui/ui/error_dialog.ui";
+        pub const HISTORY: &str = "/xyz/xerolinux/xfprintd_gui/ui/history_dialog.ui";
         pub const INFO: &str = "/xyz/xerolinux/xfprintd_gui/ui/info_dialog.ui";
+        pub const LOG_VIEWER: &str = "/xyz/xerolinux/xfprintd_gui/ui/log_viewer_dialog.ui";
+        pub const MULTI_ENROLL: &str = "/xyz/xerolinux/xfprintd_gui/ui/multi_enroll_dialog.ui";
+        pub const ONBOARDING: &str = "/xyz/xerolinux/xfprintd_gui/ui/onboarding_dialog.ui";
+        pub const PREFERENCES: &str = "/xyz/xerolinux/xfprintd_gui/ui/preferences_dialog.ui";
         pub const LOCKOUT_WARNING: &str =
             "/xyz/xerolinux/xfprintd_gui/ui/lockout_warning_dialog.ui";
+        pub const SYSTEM_STATUS: &str = "/xyz/xerolinux/xfprintd_gui/ui/system_status_dialog.ui";
     }
 }
-
-/// Get color scheme for UI feedback.
-pub fn colors() -> &'static ColorScheme {
-    &COLORS
-}
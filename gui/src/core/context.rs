@@ -2,6 +2,7 @@
 
 use crate::ui::context::UiComponents;
 use gtk4::prelude::*;
+use gtk4::Button;
 use std::cell::RefCell;
 use std::collections::HashSet;
 use std::rc::Rc;
@@ -15,6 +16,8 @@ pub struct FingerprintContext {
     pub ui: UiComponents,
     pub selected_finger: Rc<RefCell<Option<String>>>,
     pub enrolled: Rc<RefCell<HashSet<String>>>,
+    /// Finger buttons on the manage page with their finger keys, in display order.
+    pub finger_buttons: Rc<RefCell<Vec<(String, Button)>>>,
 }
 
 impl FingerprintContext {
@@ -29,23 +32,25 @@ impl FingerprintContext {
             ui,
             selected_finger,
             enrolled: Rc::new(RefCell::new(HashSet::new())),
+            finger_buttons: Rc::new(RefCell::new(Vec::new())),
         }
     }
 
     /// Check if any PAM switches are active.
     pub fn has_active_pam_switches(&self) -> bool {
-        self.ui.switches.term.is_active() || self.ui.switches.prompt.is_active()
+        self.ui.switches.term.switch.is_active() || self.ui.switches.prompt.switch.is_active()
     }
 
     /// Enable or disable all PAM switches based on fingerprint availability.
     pub fn set_pam_switches_sensitive(&self, sensitive: bool) {
-        self.ui.switches.term.set_sensitive(sensitive);
-        self.ui.switches.prompt.set_sensitive(sensitive);
+        self.ui.switches.term.set_available(sensitive);
+        self.ui.switches.prompt.set_available(sensitive);
     }
 
     /// Update button states based on selected finger and enrollment status.
     pub fn update_button_states(&self, is_enrolled: bool) {
         self.ui.buttons.add.set_sensitive(!is_enrolled);
+        self.ui.buttons.verify.set_sensitive(is_enrolled);
         self.ui.buttons.delete.set_sensitive(is_enrolled);
     }
 
@@ -1,28 +1,23 @@
-/// Format finger name for display (replace dashes, capitalize).
-pub fn display_finger_name(name: &str) -> String {
-    if name.is_empty() {
-        return String::new();
-    }
-    let mut s = name.replace('-', " ");
-    let mut chars = s.chars();
-    if let Some(first) = chars.next() {
-        let upper = first.to_ascii_uppercase().to_string();
-        s.replace_range(0..first.len_utf8(), &upper);
-    }
-    s
-}
+use crate::i18n::{gettext, gettext_noop};
 
-/// Create short finger name by removing common words.
-pub fn create_short_finger_name(display_name: &str) -> String {
-    let mut short_name = display_name
-        .replace(" finger", "")
-        .replace("Left ", "")
-        .replace("Right ", "");
+/// Display names of the fprintd finger keys.
+const FINGER_NAMES: &[(&str, &str)] = &[
+    ("left-thumb", gettext_noop("Left thumb")),
+    ("left-index-finger", gettext_noop("Left index finger")),
+    ("left-middle-finger", gettext_noop("Left middle finger")),
+    ("left-ring-finger", gettext_noop("Left ring finger")),
+    ("left-little-finger", gettext_noop("Left little finger")),
+    ("right-thumb", gettext_noop("Right thumb")),
+    ("right-index-finger", gettext_noop("Right index finger")),
+    ("right-middle-finger", gettext_noop("Right middle finger")),
+    ("right-ring-finger", gettext_noop("Right ring finger")),
+    ("right-little-finger", gettext_noop("Right little finger")),
+];
 
-    if let Some(first_char) = short_name.chars().next() {
-        short_name =
-            first_char.to_uppercase().collect::<String>() + &short_name[first_char.len_utf8()..];
+/// Translated name of a finger; unknown keys are shown as they are.
+pub fn display_finger_name(key: &str) -> String {
+    match FINGER_NAMES.iter().find(|(k, _)| *k == key) {
+        Some((_, name)) => gettext(name),
+        None => key.to_string(),
     }
-
-    short_name
 }
@@ -1,53 +1,79 @@
 //! Fingerprint enrollment functionality.
 
-use crate::config;
 use crate::core::context::FingerprintContext;
-use crate::core::device_manager::{DeviceError, DeviceManager};
-use crate::core::fprintd;
+use crate::core::util;
+use crate::i18n::{gettext, gettext_f};
+use crate::preferences;
+use crate::ui::notifications::{self, Topic};
+use crate::ui::theme::Tone;
+use crate::ui::utils::{status_line, StatusLine};
 use gtk4::glib;
+use xfprintd_gui_core::device_manager::DeviceError;
+use xfprintd_gui_core::enrollment::{self, EnrollOutcome, EnrollProgress, EnrollStatus};
 
 use log::{info, warn};
 use std::sync::mpsc::{self, TryRecvError};
-use std::sync::Arc;
-use tokio::sync::Mutex;
 
 /// Events sent during enrollment process.
 #[derive(Clone)]
 pub enum EnrollmentEvent {
-    SetText(String),
+    SetStatus(StatusLine),
     EnrollCompleted,
+    /// Enrollment ended without a new fingerprint, for the given reason.
+    EnrollFailed(String),
+    ReaderDisconnected,
 }
 
-/// Holds the DeviceManager during enrollment to keep device claimed.
-type SharedDeviceManager = Arc<Mutex<Option<DeviceManager>>>;
-
 /// Start fingerprint enrollment process for specified finger.
 pub fn start_enrollment(finger_key: String, ctx: FingerprintContext) {
     let (tx, rx) = mpsc::channel::<EnrollmentEvent>();
 
-    setup_ui_listener(rx, ctx.clone());
+    setup_ui_listener(rx, &finger_key, ctx.clone());
     // We don't yet know required stages (varies by device), so we show a generic Step 1 message.
-    let _ = tx.send(EnrollmentEvent::SetText(format!(
-        "<b><span foreground='{}'>🔍 Scan 1</span> - Place your finger firmly on the scanner…</b>",
-        config::colors().progress
+    let _ = tx.send(EnrollmentEvent::SetStatus(status_line(
+        Tone::Accent,
+        "🔍",
+        &gettext_f("Scan {stage}", &[("stage", &1)]),
+        &gettext("Place your finger firmly on the scanner…"),
     )));
     spawn_enrollment_task(finger_key, tx, ctx);
 }
 
 /// Set up UI listener for enrollment status updates.
-fn setup_ui_listener(rx: mpsc::Receiver<EnrollmentEvent>, ctx: FingerprintContext) {
-    let lbl = ctx.ui.labels.action.clone();
+fn setup_ui_listener(
+    rx: mpsc::Receiver<EnrollmentEvent>,
+    finger_key: &str,
+    ctx: FingerprintContext,
+) {
+    let labels = ctx.ui.labels.clone();
     let ctx_for_refresh = ctx.clone();
+    let finger_name = util::display_finger_name(finger_key);
 
     glib::idle_add_local(move || {
         loop {
             match rx.try_recv() {
-                Ok(EnrollmentEvent::SetText(text)) => {
-                    lbl.set_use_markup(true);
-                    lbl.set_markup(&text);
-                }
+                Ok(EnrollmentEvent::SetStatus(status)) => labels.show_action_status(&status),
                 Ok(EnrollmentEvent::EnrollCompleted) => {
                     crate::ui::fingerprint_ui::refresh_fingerprint_display(ctx_for_refresh.clone());
+                    notifications::notify(
+                        Topic::Enrollment,
+                        &gettext("Fingerprint Enrolled"),
+                        &gettext_f(
+                            "{finger} can now be used to sign in.",
+                            &[("finger", &finger_name)],
+                        ),
+                    );
+                }
+                Ok(EnrollmentEvent::EnrollFailed(reason)) => notifications::notify(
+                    Topic::Enrollment,
+                    &gettext("Enrollment Failed"),
+                    &gettext_f(
+                        "{finger}: {reason}",
+                        &[("finger", &finger_name), ("reason", &reason)],
+                    ),
+                ),
+                Ok(EnrollmentEvent::ReaderDisconnected) => {
+                    notifications::notify_reader_disconnected()
                 }
                 Err(TryRecvError::Empty) => break,
                 Err(TryRecvError::Disconnected) => return glib::ControlFlow::Break,
@@ -63,183 +89,160 @@ fn spawn_enrollment_task(
     tx: mpsc::Sender<EnrollmentEvent>,
     ctx: FingerprintContext,
 ) {
+    let timeout = preferences::load().enroll_timeout();
     ctx.rt.spawn(async move {
         info!(
             "Starting fingerprint enrollment process for finger: {}",
             finger_key
         );
 
-        // Create shared storage for DeviceManager to keep device claimed during enrollment
-        let device_manager: SharedDeviceManager = Arc::new(Mutex::new(None));
-        let device_manager_for_cleanup = device_manager.clone();
-
-        let result = DeviceManager::enroll_finger(finger_key.clone(), |device| {
-            setup_enrollment_listener_sync(device, &tx, device_manager_for_cleanup)
+        let result = enrollment::enroll(&finger_key, timeout, |progress| {
+            let _ = tx.send(EnrollmentEvent::SetStatus(progress_status(progress)));
         })
         .await;
 
-        match result {
-            Ok(manager) => {
-                // Store the DeviceManager to keep the device claimed
-                *device_manager.lock().await = Some(manager);
-            }
-            Err(e) => {
-                let error_msg = match e {
-                    DeviceError::NoDeviceAvailable => {
-                        format!(
-                            "<span foreground='{}'>No fingerprint devices available.</span>",
-                            config::colors().warning
-                        )
-                    }
-                    _ => format!("Failed to start enrollment: {}", e),
-                };
-                let _ = tx.send(EnrollmentEvent::SetText(error_msg));
+        match &result {
+            Ok(EnrollOutcome::Failed(EnrollStatus::Disconnected)) => {
+                let _ = tx.send(EnrollmentEvent::ReaderDisconnected);
             }
-        }
-    });
-}
-
-/// Set up enrollment status listener (synchronous wrapper for DeviceManager).
-fn setup_enrollment_listener_sync(
-    device: &fprintd::Device,
-    tx: &mpsc::Sender<EnrollmentEvent>,
-    device_manager: SharedDeviceManager,
-) -> Result<(), DeviceError> {
-    let device_clone = device.clone();
-    let tx_clone = tx.clone();
-
-    tokio::spawn(async move {
-        setup_enrollment_listener(&device_clone, &tx_clone, device_manager).await;
-    });
-
-    Ok(())
-}
-
-/// Set up enrollment status listener.
-async fn setup_enrollment_listener(
-    device: &fprintd::Device,
-    tx: &mpsc::Sender<EnrollmentEvent>,
-    device_manager: SharedDeviceManager,
-) {
-    let device_for_listener = device.clone();
-    let device_for_cleanup = device.clone();
-    let tx_status = tx.clone();
-
-    info!("Setting up enrollment status listener for real-time feedback");
-    // Track progressive successful stages (we only show how many good scans were captured so far).
-    let mut stage_count: usize = 0usize;
-
-    let _ = device_for_listener
-        .listen_enroll_status(move |evt| {
-            info!(
-                "Enrollment status update: result='{}', done={}",
-                evt.result, evt.done
-            );
-
-            let mut _message: Option<String> = None;
-
-
-
-            match evt.result.as_str() {
-                "enroll-stage-passed" => {
-                    stage_count += 1;
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>✅ Scan {} captured.</b> Lift your finger, then place it again…</span>",
-                        config::colors().progress,
-                        stage_count
-                    ));
+            Ok(EnrollOutcome::Completed { .. }) => {}
+            _ => {
+                if let Err(reason) = failure_reason(&result) {
+                    let _ = tx.send(EnrollmentEvent::EnrollFailed(reason));
                 }
-                "enroll-remove-and-retry" => {
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>⚠️  Retry scan {}.</b> Lift your finger completely, reposition (centered & flat), then place again…</span>",
-                        config::colors().warning,
-                        stage_count + 1
-                    ));
-                }
-                "enroll-swipe-too-short" => {
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>👆 Swipe too short.</b> Try a longer, smoother swipe (still on scan {}).</span>",
-                        config::colors().warning,
-                        stage_count + 1
-                    ));
-                }
-                "enroll-finger-not-centered" => {
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>🎯 Not centered.</b> Re‑place finger centered & flat (scan {}).</span>",
-                        config::colors().warning,
-                        stage_count + 1
-                    ));
-                }
-                "enroll-duplicate" => {
-                    _message = Some(
-                        format!(
-                            "<span foreground='{}'><b>🔄 Already enrolled!</b> Choose a different finger.</span>",
-                            config::colors().warning
-                        )
-                    );
-                }
-                "enroll-data-full" => {
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>📊 Processing captured data…</b> ({} scans so far)</span>",
-                        config::colors().process,
-                        stage_count
-                    ));
-                }
-                "enroll-failed" => {
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>❌ Enrollment failed.</b> Please try again.</span>",
-                        config::colors().error
-                    ));
-                }
-                "enroll-completed" => {
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>🎉 Enrollment complete!</b> Captured {} quality scans.</span>",
-                        config::colors().success,
-                        stage_count
-                    ));
-                }
-                other => {
-                    // Fallback / unknown statuses
-                    _message = Some(format!(
-                        "<span foreground='{}'><b>📊 Status:</b> {} (scan {})</span>",
-                        config::colors().neutral,
-                        other,
-                        stage_count.max(1)
-                    ));
-                }
-            }
-
-            if let Some(text) = _message {
-                let _ = tx_status.send(EnrollmentEvent::SetText(text));
             }
+        }
 
-            if evt.result == "enroll-completed" {
+        match result {
+            Ok(EnrollOutcome::Completed { stages }) => {
                 info!(
                     "Fingerprint enrollment completed successfully after {} stages",
-                    stage_count
+                    stages
                 );
-                let _ = tx_status.send(EnrollmentEvent::EnrollCompleted);
+                let _ = tx.send(EnrollmentEvent::EnrollCompleted);
             }
-
-            if evt.done {
-                info!("Enrollment process finished, cleaning up device");
-                let device_clone = device_for_cleanup.clone();
-                let manager_clone = device_manager.clone();
-                tokio::spawn(async move {
-                    cleanup_enrollment_device(device_clone, manager_clone).await;
-                });
+            Ok(EnrollOutcome::Failed(status)) => {
+                warn!("Enrollment finished without success: {}", status.as_str());
             }
-        })
-        .await;
+            Ok(EnrollOutcome::TimedOut) => {
+                warn!("Enrollment timed out waiting for a scan");
+                let _ = tx.send(EnrollmentEvent::SetStatus(status_line(
+                    Tone::Warning,
+                    "⏱️ ",
+                    &gettext("No finger was scanned in time."),
+                    &gettext("Select the finger again to retry."),
+                )));
+            }
+            Err(e) => {
+                let status = match e {
+                    DeviceError::NoDeviceAvailable => StatusLine::text(
+                        Tone::Warning,
+                        &gettext("No fingerprint devices available."),
+                    ),
+                    _ => StatusLine::text(
+                        Tone::Error,
+                        &gettext_f("Failed to start enrollment: {error}", &[("error", &e)]),
+                    ),
+                };
+                let _ = tx.send(EnrollmentEvent::SetStatus(status));
+            }
+        }
+    });
 }
 
-/// Clean up enrollment device.
-async fn cleanup_enrollment_device(device: fprintd::Device, device_manager: SharedDeviceManager) {
-    if let Err(e) = device.enroll_stop().await {
-        warn!("Failed to stop enrollment: {}", e);
+/// Message shown in the action label for an enrollment status update.
+/// Only successful scans are counted, so retries refer to the next scan.
+pub fn progress_status(progress: &EnrollProgress) -> StatusLine {
+    let stage = progress.stage;
+    match &progress.status {
+        EnrollStatus::StagePassed => status_line(
+            Tone::Accent,
+            "✅",
+            &gettext_f("Scan {stage} captured.", &[("stage", &stage)]),
+            &gettext("Lift your finger, then place it again…"),
+        ),
+        EnrollStatus::RemoveAndRetry => status_line(
+            Tone::Warning,
+            "⚠️ ",
+            &gettext_f("Retry scan {stage}.", &[("stage", &(stage + 1))]),
+            &gettext(
+                "Lift your finger completely, reposition (centered & flat), then place again…",
+            ),
+        ),
+        EnrollStatus::SwipeTooShort => status_line(
+            Tone::Warning,
+            "👆",
+            &gettext("Swipe too short."),
+            &gettext_f(
+                "Try a longer, smoother swipe (still on scan {stage}).",
+                &[("stage", &(stage + 1))],
+            ),
+        ),
+        EnrollStatus::FingerNotCentered => status_line(
+            Tone::Warning,
+            "🎯",
+            &gettext("Not centered."),
+            &gettext_f(
+                "Re‑place finger centered & flat (scan {stage}).",
+                &[("stage", &(stage + 1))],
+            ),
+        ),
+        EnrollStatus::Duplicate => status_line(
+            Tone::Warning,
+            "🔄",
+            &gettext("Already enrolled!"),
+            &gettext("Choose a different finger."),
+        ),
+        EnrollStatus::DataFull => status_line(
+            Tone::Accent,
+            "📊",
+            &gettext("Processing captured data…"),
+            &gettext_f("({count} scans so far)", &[("count", &stage)]),
+        ),
+        EnrollStatus::Failed => status_line(
+            Tone::Error,
+            "❌",
+            &gettext("Enrollment failed."),
+            &gettext("Please try again."),
+        ),
+        EnrollStatus::Completed => status_line(
+            Tone::Success,
+            "🎉",
+            &gettext("Enrollment complete!"),
+            &gettext_f("Captured {count} quality scans.", &[("count", &stage)]),
+        ),
+        // Fallback / unknown statuses
+        other => status_line(
+            Tone::Neutral,
+            "📊",
+            &gettext("Status:"),
+            &gettext_f(
+                "{status} (scan {stage})",
+                &[("status", &other.as_str()), ("stage", &stage.max(1))],
+            ),
+        ),
     }
+}
 
-    // Release the DeviceManager to properly clean up the device
-    *device_manager.lock().await = None;
-    info!("Enrollment cleanup completed");
+/// Why a finger was not enrolled, in words for the user.
+pub fn failure_reason(result: &Result<EnrollOutcome, DeviceError>) -> Result<(), String> {
+    match result {
+        Ok(EnrollOutcome::Completed { .. }) => Ok(()),
+        Ok(EnrollOutcome::Failed(EnrollStatus::Duplicate)) => {
+            Err(gettext("Already enrolled as another finger"))
+        }
+        Ok(EnrollOutcome::Failed(EnrollStatus::Disconnected)) => {
+            Err(gettext("The reader was disconnected"))
+        }
+        Ok(EnrollOutcome::TimedOut) => Err(gettext("Timed out waiting for a scan")),
+        Ok(EnrollOutcome::Failed(status)) => Err(gettext_f(
+            "Enrollment failed ({status})",
+            &[("status", &status.as_str())],
+        )),
+        Err(DeviceError::NoDeviceAvailable) => Err(gettext("No fingerprint devices available.")),
+        Err(e) => {
+            warn!("Enrollment could not run: {}", e);
+            Err(gettext_f("Enrollment failed: {error}", &[("error", e)]))
+        }
+    }
 }
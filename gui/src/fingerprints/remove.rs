@@ -1,8 +1,11 @@
 //! Fingerprint removal functionality.
 
 use crate::core::context::FingerprintContext;
-use crate::core::device_manager::{DeviceError, DeviceManager};
-use crate::ui::utils::extract_widget;
+use crate::i18n::gettext;
+use crate::ui::theme::Tone;
+use crate::ui::utils::{extract_widget, StatusLine};
+use xfprintd_gui_core::device_manager::{DeviceError, DeviceManager};
+use xfprintd_gui_core::fingerprints::scan_enrolled_fingerprints;
 
 use gtk4::glib;
 
@@ -14,7 +17,7 @@ use std::sync::mpsc::{self, TryRecvError};
 #[derive(Clone)]
 pub enum RemovalEvent {
     Success,
-    Error(String),
+    Error(StatusLine),
 }
 
 /// Start fingerprint removal process for specified finger.
@@ -52,7 +55,7 @@ pub fn start_removal(finger_key: String, ctx: FingerprintContext) {
     });
 
     rt_clone.spawn(async move {
-        let enrolled = crate::fingerprints::scan_enrolled_fingerprints().await;
+        let enrolled = scan_enrolled_fingerprints().await;
         let is_last_fingerprint = enrolled.len() == 1 && enrolled.contains(&finger_key_clone);
 
         let _ = tx.send(is_last_fingerprint);
@@ -105,8 +108,7 @@ fn proceed_with_removal(finger_key: String, ctx: FingerprintContext) {
 
     ctx.ui
         .labels
-        .action
-        .set_label("Deleting enrolled fingerprint...");
+        .show_action_text(&gettext("Deleting enrolled fingerprint..."));
     let (tx, rx) = mpsc::channel::<RemovalEvent>();
 
     setup_removal_ui_listener(rx, ctx.clone());
@@ -115,19 +117,20 @@ fn proceed_with_removal(finger_key: String, ctx: FingerprintContext) {
 
 /// Set up UI listener for removal status updates.
 fn setup_removal_ui_listener(rx: mpsc::Receiver<RemovalEvent>, ctx: FingerprintContext) {
-    let action_label = ctx.ui.labels.action.clone();
+    let labels = ctx.ui.labels.clone();
     let _rt = ctx.rt.clone();
 
     glib::idle_add_local(move || match rx.try_recv() {
         Ok(RemovalEvent::Success) => {
-            action_label.set_use_markup(true);
-            action_label.set_markup("<span color='orange'>Fingerprint deleted.</span>");
+            labels.show_action_status(&StatusLine::text(
+                Tone::Warning,
+                &gettext("Fingerprint deleted."),
+            ));
             crate::ui::fingerprint_ui::refresh_fingerprint_display(ctx.clone());
             glib::ControlFlow::Break
         }
-        Ok(RemovalEvent::Error(msg)) => {
-            action_label.set_use_markup(true);
-            action_label.set_markup(&msg);
+        Ok(RemovalEvent::Error(status)) => {
+            labels.show_action_status(&status);
             crate::ui::fingerprint_ui::refresh_fingerprint_display(ctx.clone());
             glib::ControlFlow::Break
         }
@@ -149,13 +152,21 @@ fn spawn_removal_task(finger_key: String, tx: mpsc::Sender<RemovalEvent>, ctx: F
                 let _ = tx.send(RemovalEvent::Success);
             }
             Err(e) => {
-                let error_msg = match e {
-                    DeviceError::NoDeviceAvailable => {
-                        "<span color='orange'>No fingerprint devices available.</span>".to_string()
-                    }
-                    _ => format!("<span color='red'><b>Delete failed</b>: {}</span>", e),
+                let status = match e {
+                    DeviceError::NoDeviceAvailable => StatusLine::text(
+                        Tone::Warning,
+                        &gettext("No fingerprint devices available."),
+                    ),
+                    _ => StatusLine {
+                        tone: Tone::Error,
+                        markup: format!(
+                            "<b>{}</b>: {}",
+                            glib::markup_escape_text(&gettext("Delete failed")),
+                            glib::markup_escape_text(&e.to_string())
+                        ),
+                    },
                 };
-                let _ = tx.send(RemovalEvent::Error(error_msg));
+                let _ = tx.send(RemovalEvent::Error(status));
             }
         }
     });
@@ -0,0 +1,132 @@
+//! Fingerprint verification functionality.
+
+use crate::core::context::FingerprintContext;
+use crate::core::util;
+use crate::i18n::{gettext, gettext_f};
+use crate::ui::notifications;
+use crate::ui::theme::Tone;
+use crate::ui::utils::{status_line, StatusLine};
+use gtk4::glib;
+use xfprintd_gui_core::device_manager::DeviceError;
+use xfprintd_gui_core::verification::{self, VerifyStatus};
+
+use log::{info, warn};
+use std::sync::mpsc::{self, TryRecvError};
+
+/// Events sent during verification.
+enum VerificationEvent {
+    SetStatus(StatusLine),
+    ReaderDisconnected,
+}
+
+/// Verify that an enrolled finger is recognised, reporting progress in the action label.
+pub fn start_verification(finger_key: String, ctx: FingerprintContext) {
+    let (tx, rx) = mpsc::channel::<VerificationEvent>();
+
+    let labels = ctx.ui.labels.clone();
+    glib::idle_add_local(move || loop {
+        match rx.try_recv() {
+            Ok(VerificationEvent::SetStatus(status)) => labels.show_action_status(&status),
+            Ok(VerificationEvent::ReaderDisconnected) => {
+                notifications::notify_reader_disconnected()
+            }
+            Err(TryRecvError::Empty) => return glib::ControlFlow::Continue,
+            Err(TryRecvError::Disconnected) => return glib::ControlFlow::Break,
+        }
+    });
+
+    let _ = tx.send(VerificationEvent::SetStatus(status_line(
+        Tone::Accent,
+        "🔍",
+        &gettext("Verifying."),
+        &gettext("Place your finger on the scanner…"),
+    )));
+
+    ctx.rt.spawn(async move {
+        info!(
+            "Starting fingerprint verification for finger: {}",
+            finger_key
+        );
+        let finger_name = util::display_finger_name(&finger_key);
+
+        let result = verification::verify(&finger_key, |progress| {
+            if !progress.done {
+                let _ = tx.send(VerificationEvent::SetStatus(progress_status(
+                    &progress.status,
+                    &finger_name,
+                )));
+            }
+        })
+        .await;
+
+        let status = match result {
+            Ok(status) => {
+                info!("Verification finished: {}", status.as_str());
+                if status == VerifyStatus::Disconnected {
+                    let _ = tx.send(VerificationEvent::ReaderDisconnected);
+                }
+                progress_status(&status, &finger_name)
+            }
+            Err(DeviceError::NoDeviceAvailable) => {
+                StatusLine::text(Tone::Warning, &gettext("No fingerprint devices available."))
+            }
+            Err(e) => {
+                warn!("Verification could not run: {}", e);
+                StatusLine::text(
+                    Tone::Error,
+                    &gettext_f("Failed to start verification: {error}", &[("error", &e)]),
+                )
+            }
+        };
+        let _ = tx.send(VerificationEvent::SetStatus(status));
+    });
+}
+
+/// Message shown in the action label for a verification status.
+pub fn progress_status(status: &VerifyStatus, finger_name: &str) -> StatusLine {
+    match status {
+        VerifyStatus::Match => status_line(
+            Tone::Success,
+            "✅",
+            &gettext("Match!"),
+            &gettext_f("{finger} was recognised.", &[("finger", &finger_name)]),
+        ),
+        VerifyStatus::NoMatch => status_line(
+            Tone::Error,
+            "❌",
+            &gettext("No match."),
+            &gettext("Try again, or delete and re-enroll this finger."),
+        ),
+        VerifyStatus::RetryScan => status_line(
+            Tone::Warning,
+            "⚠️ ",
+            &gettext("Scan again."),
+            &gettext("Place your finger on the scanner…"),
+        ),
+        VerifyStatus::SwipeTooShort => status_line(
+            Tone::Warning,
+            "👆",
+            &gettext("Swipe too short."),
+            &gettext("Try a longer, smoother swipe."),
+        ),
+        VerifyStatus::FingerNotCentered => status_line(
+            Tone::Warning,
+            "🎯",
+            &gettext("Not centered."),
+            &gettext("Place your finger centered and flat."),
+        ),
+        VerifyStatus::RemoveAndRetry => status_line(
+            Tone::Warning,
+            "⚠️ ",
+            &gettext("Lift your finger."),
+            &gettext("Then place it on the scanner again…"),
+        ),
+        VerifyStatus::Disconnected => status_line(
+            Tone::Error,
+            "❌",
+            &gettext("Device disconnected."),
+            &gettext("Reconnect the reader and try again."),
+        ),
+        other => status_line(Tone::Neutral, "📊", &gettext("Status:"), other.as_str()),
+    }
+}
@@ -0,0 +1,72 @@
+//! Translation support through gettext.
+//!
+//! Strings are looked up in the `xfprintd-gui` domain, which Builder files
+//! also use for properties marked `translatable="yes"`. Messages with values
+//! use named `{placeholders}` so translators can reorder them.
+
+use gtk4::glib;
+use std::ffi::CString;
+use std::fmt::Display;
+use std::os::raw::{c_char, c_ulong};
+use xfprintd_gui_core::i18n::fill;
+pub use xfprintd_gui_core::i18n::gettext_noop;
+
+/// Gettext domain and base name of the installed `.mo` files.
+pub const GETTEXT_PACKAGE: &str = "xfprintd-gui";
+
+/// Environment variable pointing at a directory of compiled catalogs,
+/// so translations can be tried without installing them.
+const LOCALEDIR_ENV: &str = "XFPRINTD_GUI_LOCALEDIR";
+
+/// Where catalogs are installed unless the build overrides it.
+const DEFAULT_LOCALEDIR: &str = match option_env!("XFPRINTD_GUI_LOCALEDIR") {
+    Some(dir) => dir,
+    None => "/usr/share/locale",
+};
+
+extern "C" {
+    fn bindtextdomain(domainname: *const c_char, dirname: *const c_char) -> *mut c_char;
+    fn bind_textdomain_codeset(domainname: *const c_char, codeset: *const c_char) -> *mut c_char;
+    fn textdomain(domainname: *const c_char) -> *mut c_char;
+}
+
+/// Bind the translation domain and make it the default for Builder files.
+///
+/// GTK selects the locale from the environment when it initialises.
+pub fn init() {
+    let dir = std::env::var(LOCALEDIR_ENV).unwrap_or_else(|_| DEFAULT_LOCALEDIR.to_string());
+    let (Ok(domain), Ok(dir)) = (CString::new(GETTEXT_PACKAGE), CString::new(dir)) else {
+        return;
+    };
+
+    unsafe {
+        bindtextdomain(domain.as_ptr(), dir.as_ptr());
+        bind_textdomain_codeset(domain.as_ptr(), c"UTF-8".as_ptr());
+        textdomain(domain.as_ptr());
+    }
+}
+
+/// Translate a message.
+pub fn gettext(msgid: &str) -> String {
+    glib::dgettext(Some(GETTEXT_PACKAGE), msgid).into()
+}
+
+/// Translate a message whose wording depends on a count.
+pub fn ngettext(msgid: &str, msgid_plural: &str, n: usize) -> String {
+    glib::dngettext(Some(GETTEXT_PACKAGE), msgid, msgid_plural, n as c_ulong).into()
+}
+
+/// Translate a message and fill in its `{placeholders}`.
+pub fn gettext_f(msgid: &str, args: &[(&str, &dyn Display)]) -> String {
+    fill(&gettext(msgid), args)
+}
+
+/// Translate a counted message and fill in its `{placeholders}`.
+pub fn ngettext_f(
+    msgid: &str,
+    msgid_plural: &str,
+    n: usize,
+    args: &[(&str, &dyn Display)],
+) -> String {
+    fill(&ngettext(msgid, msgid_plural, n), args)
+}
@@ -0,0 +1,204 @@
+//! Application logger.
+//!
+//! Every record passing the filter is kept in memory for diagnostic reports
+//! and appended to a rotating file under `$XDG_STATE_HOME/xfprintd-gui/`.
+//! Records important enough for the console go to journald when the GUI was
+//! started from the desktop, and to stderr otherwise.
+
+use log::{LevelFilter, Log, Metadata, Record};
+use std::collections::VecDeque;
+use std::io::IsTerminal;
+use std::path::PathBuf;
+use std::sync::{Mutex, OnceLock};
+use xfprintd_gui_core::logging::{format_record, Filter, Journal, RotatingFile};
+
+/// Number of log lines kept in memory.
+const RECENT_LINES: usize = 500;
+
+/// Size at which the log file is rotated.
+const MAX_FILE_BYTES: u64 = 1024 * 1024;
+
+/// Rotated log files kept next to the current one.
+const KEEP_FILES: usize = 3;
+
+/// Directory and file name under the XDG state directory.
+const LOG_DIR: &str = "xfprintd-gui";
+const LOG_FILE: &str = "xfprintd-gui.log";
+
+/// Identifier shown in the journal.
+const JOURNAL_IDENTIFIER: &str = "xfprintd-gui";
+
+static RECENT: Mutex<VecDeque<String>> = Mutex::new(VecDeque::new());
+static LOG_PATH: OnceLock<PathBuf> = OnceLock::new();
+
+/// How much to log, chosen with `--verbose` or `--quiet`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verbosity {
+    Quiet,
+    Normal,
+    Verbose,
+}
+
+impl Verbosity {
+    /// Filters for the log file and the console.
+    ///
+    /// `RUST_LOG` applies to both unless a flag was given.
+    fn filters(self) -> (Filter, Filter) {
+        match self {
+            Verbosity::Quiet => (
+                Filter::new(LevelFilter::Warn),
+                Filter::new(LevelFilter::Error),
+            ),
+            Verbosity::Verbose => (
+                Filter::new(LevelFilter::Debug),
+                Filter::new(LevelFilter::Debug),
+            ),
+            Verbosity::Normal => match std::env::var("RUST_LOG")
+                .ok()
+                .and_then(|spec| Filter::parse(&spec))
+            {
+                Some(filter) => (filter.clone(), filter),
+                None => (
+                    Filter::new(LevelFilter::Info),
+                    Filter::new(LevelFilter::Warn),
+                ),
+            },
+        }
+    }
+}
+
+/// Where console output goes.
+enum Console {
+    Stderr,
+    Journal(Journal),
+}
+
+struct Logger {
+    file_filter: Filter,
+    console_filter: Filter,
+    file: Option<Mutex<RotatingFile>>,
+    console: Console,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        self.file_filter
+            .enabled(metadata.target(), metadata.level())
+            || self
+                .console_filter
+                .enabled(metadata.target(), metadata.level())
+    }
+
+    fn log(&self, record: &Record) {
+        let (target, level) = (record.target(), record.level());
+        let to_file = self.file_filter.enabled(target, level);
+        let to_console = self.console_filter.enabled(target, level);
+        if !to_file && !to_console {
+            return;
+        }
+
+        let line = format_record(record);
+
+        if to_file {
+            if let Ok(mut recent) = RECENT.lock() {
+                if recent.len() == RECENT_LINES {
+                    recent.pop_front();
+                }
+                recent.push_back(line.clone());
+            }
+            if let Some(file) = &self.file {
+                if let Ok(mut file) = file.lock() {
+                    let _ = file.write_line(&line);
+                }
+            }
+        }
+
+        if to_console {
+            match &self.console {
+                Console::Journal(journal) if journal.log(record).is_ok() => {}
+                _ => eprintln!("{}", line),
+            }
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+/// Install the logger.
+///
+/// The headless command line always writes console output to stderr so
+/// scripts can capture it.
+pub fn init(verbosity: Verbosity, headless: bool) {
+    let (file_filter, console_filter) = verbosity.filters();
+
+    let mut file_error = None;
+    let file = log_dir().and_then(|dir| {
+        match RotatingFile::open(dir.join(LOG_FILE), MAX_FILE_BYTES, KEEP_FILES) {
+            Ok(file) => {
+                let _ = LOG_PATH.set(file.path().to_path_buf());
+                Some(Mutex::new(file))
+            }
+            Err(e) => {
+                file_error = Some(format!("{}: {}", dir.display(), e));
+                None
+            }
+        }
+    });
+
+    // A terminal user expects to see output where they started the program
+    let console = if headless || std::io::stderr().is_terminal() {
+        Console::Stderr
+    } else {
+        Journal::connect(JOURNAL_IDENTIFIER)
+            .map(Console::Journal)
+            .unwrap_or(Console::Stderr)
+    };
+
+    log::set_max_level(file_filter.max_level().max(console_filter.max_level()));
+    log::set_boxed_logger(Box::new(Logger {
+        file_filter,
+        console_filter,
+        file,
+        console,
+    }))
+    .expect("Logger already initialised");
+
+    if let Some(e) = file_error {
+        log::warn!("Cannot write log file, logging to memory only: {}", e);
+    }
+}
+
+/// `$XDG_STATE_HOME/xfprintd-gui`, defaulting to `~/.local/state/xfprintd-gui`.
+fn log_dir() -> Option<PathBuf> {
+    let state = std::env::var_os("XDG_STATE_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| {
+            std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state"))
+        })?;
+    Some(state.join(LOG_DIR))
+}
+
+/// Path of the current log file, when one could be opened.
+pub fn log_file() -> Option<&'static PathBuf> {
+    LOG_PATH.get()
+}
+
+/// The most recent log lines, oldest first.
+pub fn recent() -> Vec<String> {
+    RECENT
+        .lock()
+        .map(|recent| recent.iter().cloned().collect())
+        .unwrap_or_default()
+}
+
+/// Log history for the viewer: the log file, or the in-memory lines without one.
+pub fn history() -> String {
+    log_file()
+        .and_then(|path| std::fs::read_to_string(path).ok())
+        .unwrap_or_else(|| {
+            let mut lines = recent().join("\n");
+            lines.push('\n');
+            lines
+        })
+}
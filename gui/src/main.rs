@@ -1,16 +1,30 @@
 use adw::prelude::*;
 use adw::Application;
+use clap::Parser;
 use log::info;
+use std::process::ExitCode;
 
+mod cli;
 mod config;
 mod core;
 mod fingerprints;
+mod i18n;
+mod logging;
 mod pam;
+mod preferences;
 mod ui;
 
-fn main() {
-    // Initialize logger
-    simple_logger::SimpleLogger::new().init().unwrap();
+fn main() -> ExitCode {
+    let args = cli::Cli::parse();
+
+    let headless = args.is_headless();
+    logging::init(args.verbosity(), headless);
+    if headless {
+        return cli::run(args);
+    }
+
+    // The command line keeps English output for scripts and bug reports
+    i18n::init();
 
     info!(
         "Starting {} v{}",
@@ -18,6 +32,9 @@ fn main() {
         config::app_info::VERSION
     );
     info!("Application ID: {}", config::app_info::ID);
+    if let Some(path) = logging::log_file() {
+        info!("Logging to {}", path.display());
+    }
 
     let app = Application::builder()
         .application_id(config::app_info::ID)
@@ -25,5 +42,7 @@ fn main() {
 
     app.connect_activate(ui::setup_application_ui);
 
-    app.run();
+    // Arguments were already handled by clap
+    let program: Vec<String> = std::env::args().take(1).collect();
+    app.run_with_args(&program).into()
 }
@@ -1,4 +1,3 @@
 //! PAM (Pluggable Authentication Modules) functionality.
 
-pub mod helper;
 pub mod switch;
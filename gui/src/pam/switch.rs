@@ -1,10 +1,20 @@
 //! Generic PAM switch handler functionality.
 
-use crate::pam::helper::PamHelper;
+use crate::i18n::{gettext, gettext_f};
+use crate::ui::context::PamSwitchRow;
+use crate::ui::dialogs;
+use crate::ui::notifications::{self, Topic};
 #[allow(unused_imports)]
 use gtk4::prelude::*;
 use gtk4::{glib, Switch};
-use log::{error, info};
+use log::{error, info, warn};
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::time::Duration;
+use xfprintd_gui_core::pam::{PamHelper, PamParams};
+
+/// Delay before re-applying changed parameters, so spinning through values prompts only once.
+const PARAMETER_APPLY_DELAY: Duration = Duration::from_millis(800);
 
 /// PAM service configuration for switch handlers.
 #[derive(Clone)]
@@ -16,7 +26,7 @@ pub struct PamService {
 /// Available PAM services that can be configured.
 pub mod services {
     use super::PamService;
-    use crate::pam::helper::{POLKIT_PATH, SUDO_PATH};
+    use xfprintd_gui_core::pam::{POLKIT_PATH, SUDO_PATH};
 
     pub const SUDO: PamService = PamService {
         name: "sudo",
@@ -30,17 +40,158 @@ pub mod services {
 }
 
 /// Set up a generic PAM switch handler for any service.
-pub fn setup_pam_switch(switch: &Switch, service: PamService) {
+pub fn setup_pam_switch(row: &PamSwitchRow, service: PamService) {
     let service_name = service.name.to_string();
     let service_path = service.path;
 
-    switch.connect_state_set(move |_switch, state| {
-        handle_pam_toggle(state, &service_name, service_path)
+    let row_clone = row.clone();
+    row.switch.connect_state_set(move |switch, state| {
+        // The files already have this state
+        if row_clone.is_syncing() {
+            return glib::Propagation::Proceed;
+        }
+        handle_pam_toggle(
+            switch,
+            state,
+            &service_name,
+            service_path,
+            &row_clone.parameters(),
+        )
     });
+
+    setup_parameter_handlers(row, service);
+}
+
+/// Re-apply the configuration when parameters change while the switch is on.
+fn setup_parameter_handlers(row: &PamSwitchRow, service: PamService) {
+    let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+
+    for spin in [&row.timeout, &row.max_tries] {
+        let row = row.clone();
+        let service = service.clone();
+        let pending = pending.clone();
+
+        spin.connect_value_changed(move |_| {
+            if !row.switch.is_active() || row.is_syncing() {
+                return;
+            }
+
+            if let Some(source) = pending.borrow_mut().take() {
+                source.remove();
+            }
+
+            let row = row.clone();
+            let service = service.clone();
+            let pending_inner = pending.clone();
+            let source = glib::timeout_add_local_once(PARAMETER_APPLY_DELAY, move || {
+                pending_inner.borrow_mut().take();
+                apply_parameters(&row, &service);
+            });
+            *pending.borrow_mut() = Some(source);
+        });
+    }
+}
+
+/// Re-apply the configuration for a service with the row's current parameters.
+fn apply_parameters(row: &PamSwitchRow, service: &PamService) {
+    let params = row.parameters();
+    info!(
+        "User changed {} fingerprint parameters: timeout={}s, max-tries={}",
+        service.name, params.timeout, params.max_tries
+    );
+
+    match PamHelper::apply_configuration(service.path, &params) {
+        Ok(()) => {
+            info!("Updated fingerprint parameters for {}", service.name);
+            notifications::notify(
+                Topic::Pam,
+                &gettext("Fingerprint Settings Updated"),
+                &gettext_f(
+                    "The new settings for {service} were applied.",
+                    &[("service", &service.name)],
+                ),
+            );
+        }
+        Err(e) => {
+            error!(
+                "Failed to update fingerprint parameters for {}: {}",
+                service.name, e
+            );
+            notifications::notify(
+                Topic::Pam,
+                &gettext("Cannot Update Fingerprint Settings"),
+                &e.to_string(),
+            );
+            dialogs::show_error_dialog(
+                &row.switch,
+                &gettext("Cannot Update Fingerprint Settings"),
+                &gettext_f(
+                    "The new settings for {service} were not applied.",
+                    &[("service", &service.name)],
+                ),
+                &e.to_string(),
+            );
+        }
+    }
+}
+
+/// Run pre-flight checks before enabling; returns the warnings to show, or None if enabling must be refused.
+fn run_preflight(
+    switch: &Switch,
+    service_name: &str,
+    service_path: &str,
+    params: &PamParams,
+) -> Option<Vec<String>> {
+    let report = match PamHelper::validate_configuration(service_path, params) {
+        Ok(report) => report,
+        Err(e) => {
+            error!("Pre-flight check for {} could not run: {}", service_name, e);
+            dialogs::show_error_dialog(
+                switch,
+                &gettext("Cannot Enable Fingerprint Authentication"),
+                &gettext_f(
+                    "The safety check for {service} could not be run.",
+                    &[("service", &service_name)],
+                ),
+                &e.to_string(),
+            );
+            return None;
+        }
+    };
+
+    if !report.is_safe() {
+        error!(
+            "Refusing to enable {} fingerprint authentication: {}",
+            service_name,
+            report.errors.join("; ")
+        );
+        dialogs::show_error_dialog(
+            switch,
+            &gettext("Cannot Enable Fingerprint Authentication"),
+            &gettext_f(
+                "Enabling fingerprint authentication for {service} could lock you out.",
+                &[("service", &service_name)],
+            ),
+            &report.errors.join("\n"),
+        );
+        return None;
+    }
+
+    for warning in &report.warnings {
+        warn!("Enabling {} despite warning: {}", service_name, warning);
+    }
+
+    Some(report.warnings)
 }
 
 /// Handle PAM toggle for any service (generic implementation).
-fn handle_pam_toggle(state: bool, service_name: &str, service_path: &str) -> glib::Propagation {
+fn handle_pam_toggle(
+    switch: &Switch,
+    state: bool,
+    service_name: &str,
+    service_path: &str,
+    params: &PamParams,
+) -> glib::Propagation {
     if state {
         info!(
             "User enabled {} fingerprint authentication switch",
@@ -53,8 +204,17 @@ fn handle_pam_toggle(state: bool, service_name: &str, service_path: &str) -> gli
         );
     }
 
+    let warnings = if state {
+        match run_preflight(switch, service_name, service_path, params) {
+            Some(warnings) => warnings,
+            None => return glib::Propagation::Stop,
+        }
+    } else {
+        Vec::new()
+    };
+
     let result = if state {
-        PamHelper::apply_configuration(service_path)
+        PamHelper::apply_configuration(service_path, params)
     } else {
         PamHelper::remove_configuration(service_path)
     };
@@ -66,12 +226,24 @@ fn handle_pam_toggle(state: bool, service_name: &str, service_path: &str) -> gli
                     "Successfully enabled fingerprint authentication for {}",
                     service_name
                 );
+                if !warnings.is_empty() {
+                    dialogs::show_warning_dialog(
+                        switch,
+                        &gettext("Enabled With Warnings"),
+                        &gettext_f(
+                            "Fingerprint authentication for {service} was enabled, but may not work as expected.",
+                            &[("service", &service_name)],
+                        ),
+                        &warnings.join("\n"),
+                    );
+                }
             } else {
                 info!(
                     "Successfully disabled fingerprint authentication for {}",
                     service_name
                 );
             }
+            notify_toggled(state, service_name);
             glib::Propagation::Proceed
         }
         Err(e) => {
@@ -81,7 +253,45 @@ fn handle_pam_toggle(state: bool, service_name: &str, service_path: &str) -> gli
                 service_name,
                 e
             );
+            notifications::notify(
+                Topic::Pam,
+                &gettext("Fingerprint Authentication Not Changed"),
+                &e.to_string(),
+            );
+            if e.kind() == std::io::ErrorKind::WouldBlock {
+                dialogs::show_error_dialog(
+                    switch,
+                    &gettext("PAM Configuration Busy"),
+                    &gettext_f(
+                        "Fingerprint authentication for {service} was not changed.",
+                        &[("service", &service_name)],
+                    ),
+                    &e.to_string(),
+                );
+            }
             glib::Propagation::Stop
         }
     }
 }
+
+/// Tell the user a switch change went through, e.g. after a slow pkexec prompt.
+fn notify_toggled(enabled: bool, service_name: &str) {
+    let (title, body) = if enabled {
+        (
+            gettext("Fingerprint Authentication Enabled"),
+            gettext_f(
+                "You can now use your fingerprint for {service}.",
+                &[("service", &service_name)],
+            ),
+        )
+    } else {
+        (
+            gettext("Fingerprint Authentication Disabled"),
+            gettext_f(
+                "{service} no longer accepts your fingerprint.",
+                &[("service", &service_name)],
+            ),
+        )
+    };
+    notifications::notify(Topic::Pam, &title, &body);
+}
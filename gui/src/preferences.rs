@@ -0,0 +1,43 @@
+//! User preferences.
+//!
+//! Stored as TOML in `$XDG_CONFIG_HOME/xfprintd-gui/preferences.toml`; the
+//! settings themselves and how the file is read and written live in
+//! [`xfprintd_gui_core::preferences`].
+
+use log::warn;
+use std::io;
+use std::path::PathBuf;
+
+pub use xfprintd_gui_core::preferences::{Appearance, ColorOverrides, Preferences, WindowSize};
+
+/// Directory and file name under the XDG config directory.
+const PREFERENCES_DIR: &str = "xfprintd-gui";
+const PREFERENCES_FILE: &str = "preferences.toml";
+
+/// Load the preferences, falling back to the defaults.
+pub fn load() -> Preferences {
+    preferences_path()
+        .map(|path| Preferences::load_from(&path))
+        .unwrap_or_default()
+}
+
+/// Apply `change` to the stored preferences, warning instead of failing.
+pub fn update(change: impl FnOnce(&mut Preferences)) {
+    let mut preferences = load();
+    change(&mut preferences);
+    let result = preferences_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "No home or config directory"))
+        .and_then(|path| preferences.save_to(&path));
+    if let Err(e) = result {
+        warn!("Cannot save preferences: {}", e);
+    }
+}
+
+/// `$XDG_CONFIG_HOME/xfprintd-gui/preferences.toml`, defaulting to `~/.config`.
+fn preferences_path() -> Option<PathBuf> {
+    let config = std::env::var_os("XDG_CONFIG_HOME")
+        .map(PathBuf::from)
+        .filter(|dir| dir.is_absolute())
+        .or_else(|| std::env::var_os("HOME").map(|home| PathBuf::from(home).join(".config")))?;
+    Some(config.join(PREFERENCES_DIR).join(PREFERENCES_FILE))
+}
@@ -1,22 +1,25 @@
 //! Application setup and initialization functionality.
 
 use crate::config;
-use crate::core::{system, FingerprintContext};
+use crate::core::FingerprintContext;
+use crate::preferences;
 use crate::ui::context::AppContext;
 use crate::ui::utils::extract_widget;
-use crate::ui::{button_handlers, fingerprint_ui, navigation, pam_ui};
+use crate::ui::{
+    button_handlers, diagnostic_report, fingerprint_ui, history, multi_enroll, navigation,
+    notifications, onboarding, pam_ui, preferences_dialog, system_status, theme,
+};
 use adw::prelude::*;
 use adw::Application;
-use gtk4::glib;
 use gtk4::{gio, ApplicationWindow, Builder, CssProvider};
-use log::{info, warn};
+use log::{debug, info, warn};
 
 use std::sync::Arc;
 use tokio::runtime::Runtime;
 
 /// Initialize and set up main application UI.
 pub fn setup_application_ui(app: &Application) {
-    info!("Initializing application components");
+    debug!("Initializing application components");
 
     let rt = Arc::new(
         tokio::runtime::Builder::new_multi_thread()
@@ -24,34 +27,41 @@ pub fn setup_application_ui(app: &Application) {
             .build()
             .expect("Failed to build Tokio runtime"),
     );
-    info!("Tokio async runtime initialized");
+    debug!("Tokio async runtime initialized");
 
     setup_resources_and_theme();
 
+    let preferences = preferences::load();
+    preferences_dialog::apply_preferences(&preferences);
+
     // Create single builder for all UI components
     let builder = Builder::from_resource(config::resources::MAIN_UI);
     let window = create_main_window(app, &builder);
+    preferences_dialog::persist_window_size(&window, preferences.window);
 
-    window.show();
-
-    let window_clone = window.clone();
-    glib::idle_add_local(move || {
-        info!("Performing system environment checks");
-        system::check_fprintd_service();
-        system::check_helper_tool();
-        system::check_pkexec_availability();
-        glib::ControlFlow::Break
-    });
+    window.present();
 
     let ctx = setup_ui_components(&window, rt, &builder);
 
     // Setup UI components by category
     pam_ui::setup_pam_switches(&ctx);
     navigation::setup_navigation_and_dialogs(&ctx, &builder, &window);
+    system_status::setup_system_status(&ctx, &builder, &window);
+    history::setup_history(&ctx, &builder, &window);
+    diagnostic_report::setup_diagnostic_report_action(app, &window, ctx.fingerprint_ctx.rt.clone());
+    preferences_dialog::setup_preferences_action(app, &ctx, &window);
+    notifications::setup_notifications(app, &ctx, &window);
     button_handlers::setup_button_handlers(&ctx);
+    multi_enroll::setup_multi_enroll(&ctx, &window);
     fingerprint_ui::perform_initial_fingerprint_scan(&ctx);
+    {
+        // The hand drawings read their colours from CSS when drawn
+        let ctx = ctx.fingerprint_ctx.clone();
+        theme::connect_theme_changed(move || fingerprint_ui::rebuild_finger_picker(&ctx));
+    }
+    onboarding::setup_onboarding(&ctx, &builder, &window);
 
-    info!("Setting initial view to main page");
+    debug!("Setting initial view to main page");
     ctx.fingerprint_ctx.ui.stack.set_visible_child_name("main");
     info!("XFPrintD GUI application startup complete");
 }
@@ -62,7 +72,7 @@ fn setup_resources_and_theme() {
         .expect("Failed to register gresources");
 
     if let Some(display) = gtk4::gdk::Display::default() {
-        info!("Setting up UI theme and styling");
+        debug!("Setting up UI theme and styling");
 
         let theme = gtk4::IconTheme::for_display(&display);
         // Don't inherit system icon themes
@@ -76,7 +86,8 @@ fn setup_resources_and_theme() {
             &css_provider,
             gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION,
         );
-        info!("UI theme and styling loaded successfully");
+        theme::init();
+        debug!("UI theme and styling loaded successfully");
     } else {
         warn!("No default display found - UI theming may not work properly");
     }
@@ -87,7 +98,7 @@ fn create_main_window(app: &Application, builder: &Builder) -> ApplicationWindow
     let window: ApplicationWindow = extract_widget(builder, "app_window");
 
     window.set_application(Some(app));
-    info!("Setting window icon to fingerprint");
+    debug!("Setting window icon to fingerprint");
     window.set_icon_name(Some("fingerprint"));
 
     window
@@ -105,17 +116,41 @@ fn setup_ui_components(
     let finger_label = extract_widget(builder, "finger_label");
     let action_label = extract_widget(builder, "action_label");
     let button_add = extract_widget(builder, "button_add");
+    let button_verify = extract_widget(builder, "button_verify");
     let button_delete = extract_widget(builder, "button_delete");
+    let pick_toggle = extract_widget(builder, "pick_toggle");
+    let pick_start = extract_widget(builder, "pick_start");
     let sw_term = extract_widget(builder, "sw_term");
     let sw_prompt = extract_widget(builder, "sw_prompt");
+    let lbl_term_hint = extract_widget(builder, "lbl_term_hint");
+    let lbl_prompt_hint = extract_widget(builder, "lbl_prompt_hint");
+    let term_timeout_spin = extract_widget(builder, "term_timeout_spin");
+    let term_tries_spin = extract_widget(builder, "term_tries_spin");
+    let prompt_timeout_spin = extract_widget(builder, "prompt_timeout_spin");
+    let prompt_tries_spin = extract_widget(builder, "prompt_tries_spin");
 
-    info!("All UI components successfully initialized from Glade builder");
+    debug!("All UI components successfully initialized from Glade builder");
 
     // Assemble UI components using builder pattern
-    let switches = crate::ui::context::PamSwitches::new(sw_term, sw_prompt);
+    let term_row = crate::ui::context::PamSwitchRow::new(
+        sw_term,
+        lbl_term_hint,
+        term_timeout_spin,
+        term_tries_spin,
+    );
+    let prompt_row = crate::ui::context::PamSwitchRow::new(
+        sw_prompt,
+        lbl_prompt_hint,
+        prompt_timeout_spin,
+        prompt_tries_spin,
+    );
+    let switches = crate::ui::context::PamSwitches::new(term_row, prompt_row);
     let labels = crate::ui::context::FingerprintLabels::new(finger_label, action_label);
-    let buttons = crate::ui::context::FingerprintButtons::new(button_add, button_delete);
-    let ui = crate::ui::context::UiComponents::new(fingers_flow, stack, switches, labels, buttons);
+    let buttons =
+        crate::ui::context::FingerprintButtons::new(button_add, button_verify, button_delete);
+    let pick = crate::ui::context::PickControls::new(pick_toggle, pick_start);
+    let ui =
+        crate::ui::context::UiComponents::new(fingers_flow, stack, switches, labels, buttons, pick);
 
     let selected_finger = std::rc::Rc::new(std::cell::RefCell::new(None));
     let fingerprint_ctx = FingerprintContext::new(rt, ui, selected_finger);
@@ -1,7 +1,7 @@
 //! Button click handlers functionality.
 
 use crate::core::FingerprintContext;
-use crate::fingerprints::{enroll, remove};
+use crate::fingerprints::{enroll, remove, verify};
 use crate::ui::context::AppContext;
 use gtk4::prelude::*;
 use gtk4::Button;
@@ -10,6 +10,7 @@ use log::info;
 /// Set up all button handlers.
 pub fn setup_button_handlers(ctx: &AppContext) {
     setup_enroll_button(&ctx.fingerprint_ctx.ui.buttons.add, &ctx.fingerprint_ctx);
+    setup_verify_button(&ctx.fingerprint_ctx.ui.buttons.verify, &ctx.fingerprint_ctx);
     setup_delete_button(&ctx.fingerprint_ctx.ui.buttons.delete, &ctx.fingerprint_ctx);
 }
 
@@ -26,6 +27,17 @@ fn setup_enroll_button(button_add: &Button, ctx: &FingerprintContext) {
     });
 }
 
+/// Set up verification button.
+fn setup_verify_button(button_verify: &Button, ctx: &FingerprintContext) {
+    let ctx_clone = ctx.clone();
+    button_verify.connect_clicked(move |_| {
+        if let Some(key) = ctx_clone.get_selected_finger() {
+            info!("User clicked 'Verify' button for finger: '{}'", key);
+            verify::start_verification(key, ctx_clone.clone());
+        }
+    });
+}
+
 /// Set up delete button.
 fn setup_delete_button(button_delete: &Button, ctx: &FingerprintContext) {
     let ctx_clone = ctx.clone();
@@ -1,7 +1,17 @@
 //! Application context and UI state management.
 
 use crate::core::FingerprintContext;
-use gtk4::{Button, FlowBox, Label, Stack, Switch};
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::ui::theme::set_tone;
+use crate::ui::utils::StatusLine;
+use gtk4::prelude::*;
+use gtk4::{
+    gio, AccessibleAnnouncementPriority, Button, FlowBox, Label, SpinButton, Stack, Switch,
+    ToggleButton,
+};
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use xfprintd_gui_core::pam::PamParams;
 
 /// Main application context with UI elements.
 #[derive(Clone)]
@@ -23,6 +33,7 @@ pub struct UiComponents {
     pub switches: PamSwitches,
     pub labels: FingerprintLabels,
     pub buttons: FingerprintButtons,
+    pub pick: PickControls,
 }
 
 impl UiComponents {
@@ -33,6 +44,7 @@ impl UiComponents {
         switches: PamSwitches,
         labels: FingerprintLabels,
         buttons: FingerprintButtons,
+        pick: PickControls,
     ) -> Self {
         Self {
             flow,
@@ -40,6 +52,7 @@ impl UiComponents {
             switches,
             labels,
             buttons,
+            pick,
         }
     }
 }
@@ -47,17 +60,134 @@ impl UiComponents {
 /// PAM authentication switches.
 #[derive(Clone)]
 pub struct PamSwitches {
-    pub term: Switch,
-    pub prompt: Switch,
+    pub term: PamSwitchRow,
+    pub prompt: PamSwitchRow,
 }
 
 impl PamSwitches {
-    /// Create PAM switches from individual switch widgets.
-    pub fn new(term: Switch, prompt: Switch) -> Self {
+    /// Create PAM switches from individual switch rows.
+    pub fn new(term: PamSwitchRow, prompt: PamSwitchRow) -> Self {
         Self { term, prompt }
     }
 }
 
+/// A PAM switch together with its hint label and pam_fprintd parameter controls.
+#[derive(Clone)]
+pub struct PamSwitchRow {
+    pub switch: Switch,
+    pub hint: Label,
+    pub timeout: SpinButton,
+    pub max_tries: SpinButton,
+    default_hint: String,
+    /// Files outside our block that already enable fingerprint auth.
+    system_sources: Rc<RefCell<Vec<String>>>,
+    /// Whether fingerprints are enrolled, so enabling makes sense.
+    available: Rc<Cell<bool>>,
+    /// Set while the row is updated from the files on disk.
+    syncing: Rc<Cell<bool>>,
+    monitor: Rc<RefCell<Option<gio::FileMonitor>>>,
+}
+
+impl PamSwitchRow {
+    /// Create a switch row from its switch, hint label and parameter widgets.
+    pub fn new(switch: Switch, hint: Label, timeout: SpinButton, max_tries: SpinButton) -> Self {
+        let default_hint = hint.label().to_string();
+        let row = Self {
+            switch,
+            hint,
+            timeout,
+            max_tries,
+            default_hint,
+            system_sources: Rc::new(RefCell::new(Vec::new())),
+            available: Rc::new(Cell::new(false)),
+            syncing: Rc::new(Cell::new(false)),
+            monitor: Rc::new(RefCell::new(None)),
+        };
+
+        // Turning a duplicate block off must not leave the switch unlockable
+        let this = row.clone();
+        row.switch
+            .connect_active_notify(move |_| this.update_sensitivity());
+        row
+    }
+
+    /// Whether a change comes from `sync` rather than from the user.
+    pub fn is_syncing(&self) -> bool {
+        self.syncing.get()
+    }
+
+    /// Show the state found on disk without running the change handlers' PAM updates.
+    pub fn sync(&self, configured: bool, params: Option<&PamParams>) {
+        self.syncing.set(true);
+        self.switch.set_active(configured);
+        if let Some(params) = params {
+            self.set_parameters(params);
+        }
+        self.syncing.set(false);
+    }
+
+    /// Keep the monitor watching this row's PAM file alive as long as the row.
+    pub fn set_monitor(&self, monitor: gio::FileMonitor) {
+        *self.monitor.borrow_mut() = Some(monitor);
+    }
+
+    /// Get the pam_fprintd parameters currently selected in the row.
+    pub fn parameters(&self) -> PamParams {
+        PamParams {
+            timeout: self.timeout.value_as_int().max(0) as u32,
+            max_tries: self.max_tries.value_as_int().max(0) as u32,
+        }
+    }
+
+    /// Show the given pam_fprintd parameters in the row.
+    pub fn set_parameters(&self, params: &PamParams) {
+        self.timeout.set_value(f64::from(params.timeout));
+        self.max_tries.set_value(f64::from(params.max_tries));
+    }
+
+    /// Enable or disable the switch depending on whether fingerprints are enrolled.
+    pub fn set_available(&self, available: bool) {
+        self.available.set(available);
+        self.update_sensitivity();
+    }
+
+    /// Record the system files that already enable fingerprint auth for this service.
+    pub fn set_system_sources(&self, sources: &[String]) {
+        *self.system_sources.borrow_mut() = sources.to_vec();
+        self.update_sensitivity();
+    }
+
+    /// While the system provides fingerprint auth, the switch can only turn our
+    /// duplicate block off, and its parameters would have no effect.
+    fn update_sensitivity(&self) {
+        let sources = self.system_sources.borrow();
+        if sources.is_empty() {
+            let available = self.available.get();
+            self.hint.set_label(&self.default_hint);
+            self.switch.set_sensitive(available);
+            self.timeout.set_sensitive(available);
+            self.max_tries.set_sensitive(available);
+            return;
+        }
+
+        let duplicate = self.switch.is_active();
+        self.hint.set_label(&if duplicate {
+            gettext_f(
+                "Already enabled by system configuration ({files}). Turn this off to avoid being asked twice.",
+                &[("files", &sources.join(", "))],
+            )
+        } else {
+            gettext_f(
+                "Already enabled by system configuration ({files}).",
+                &[("files", &sources.join(", "))],
+            )
+        });
+        self.switch.set_sensitive(duplicate);
+        self.timeout.set_sensitive(false);
+        self.max_tries.set_sensitive(false);
+    }
+}
+
 /// Fingerprint-related labels.
 #[derive(Clone)]
 pub struct FingerprintLabels {
@@ -70,18 +200,115 @@ impl FingerprintLabels {
     pub fn new(finger: Label, action: Label) -> Self {
         Self { finger, action }
     }
+
+    /// Show a status message and announce it to screen readers.
+    pub fn show_action_status(&self, status: &StatusLine) {
+        status.show(&self.action);
+        self.announce_action();
+    }
+
+    /// Show a plain status message and announce it to screen readers.
+    pub fn show_action_text(&self, text: &str) {
+        self.action.set_use_markup(false);
+        self.action.set_label(text);
+        set_tone(&self.action, None);
+        self.announce_action();
+    }
+
+    /// The label is not focused while operations run, so changes are announced explicitly.
+    fn announce_action(&self) {
+        self.action
+            .announce(&self.action.text(), AccessibleAnnouncementPriority::Medium);
+    }
 }
 
 /// Fingerprint operation buttons.
 #[derive(Clone)]
 pub struct FingerprintButtons {
     pub add: Button,
+    pub verify: Button,
     pub delete: Button,
 }
 
 impl FingerprintButtons {
     /// Create fingerprint buttons from individual button widgets.
-    pub fn new(add: Button, delete: Button) -> Self {
-        Self { add, delete }
+    pub fn new(add: Button, verify: Button, delete: Button) -> Self {
+        Self {
+            add,
+            verify,
+            delete,
+        }
+    }
+}
+
+/// Controls for ticking several fingers on the manage page and enrolling them together.
+#[derive(Clone)]
+pub struct PickControls {
+    pub toggle: ToggleButton,
+    pub start: Button,
+    picked: Rc<RefCell<Vec<String>>>,
+}
+
+impl PickControls {
+    /// Create pick controls from the toggle and start buttons.
+    pub fn new(toggle: ToggleButton, start: Button) -> Self {
+        Self {
+            toggle,
+            start,
+            picked: Rc::new(RefCell::new(Vec::new())),
+        }
+    }
+
+    /// Whether clicking a finger ticks it instead of opening its page.
+    pub fn is_active(&self) -> bool {
+        self.toggle.is_active()
+    }
+
+    /// Whether a finger is ticked.
+    pub fn is_picked(&self, finger: &str) -> bool {
+        self.picked.borrow().iter().any(|f| f == finger)
+    }
+
+    /// Tick or untick a finger and return whether it is ticked now.
+    pub fn toggle_finger(&self, finger: &str) -> bool {
+        let picked = {
+            let mut fingers = self.picked.borrow_mut();
+            if let Some(index) = fingers.iter().position(|f| f == finger) {
+                fingers.remove(index);
+                false
+            } else {
+                fingers.push(finger.to_string());
+                true
+            }
+        };
+        self.update_start();
+        picked
+    }
+
+    /// Ticked fingers in the order they were ticked.
+    pub fn picked(&self) -> Vec<String> {
+        self.picked.borrow().clone()
+    }
+
+    /// Untick every finger.
+    pub fn clear(&self) {
+        self.picked.borrow_mut().clear();
+        self.update_start();
+    }
+
+    /// Show the start button only while picking, with the number of ticked fingers.
+    fn update_start(&self) {
+        let count = self.picked.borrow().len();
+        self.start.set_visible(self.is_active());
+        self.start.set_sensitive(count > 0);
+        self.start.set_label(&match count {
+            0 => gettext("Enroll Selected"),
+            n => ngettext_f(
+                "Enroll {count} Finger",
+                "Enroll {count} Fingers",
+                n,
+                &[("count", &n)],
+            ),
+        });
     }
 }
@@ -0,0 +1,110 @@
+//! "Generate diagnostic report" action.
+
+use crate::config;
+use crate::i18n::gettext;
+use crate::logging;
+use crate::ui::dialogs;
+use adw::Application;
+use gtk4::prelude::*;
+use gtk4::{gio, glib};
+use gtk4::{ApplicationWindow, FileDialog, FileFilter};
+use log::{info, warn};
+use std::path::PathBuf;
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use xfprintd_gui_core::report::{DiagnosticReport, ReportFormat};
+
+/// Name of the application action.
+pub const ACTION_NAME: &str = "diagnostic-report";
+
+/// Register `app.diagnostic-report`, which asks where to save a report.
+pub fn setup_diagnostic_report_action(
+    app: &Application,
+    window: &ApplicationWindow,
+    rt: Arc<Runtime>,
+) {
+    let action = gio::SimpleAction::new(ACTION_NAME, None);
+
+    let window = window.clone();
+    action.connect_activate(move |_, _| {
+        info!("User requested a diagnostic report");
+        choose_report_file(&window, rt.clone());
+    });
+
+    app.add_action(&action);
+}
+
+/// Ask for a destination file, then collect and save the report.
+fn choose_report_file(window: &ApplicationWindow, rt: Arc<Runtime>) {
+    let text_filter = FileFilter::new();
+    text_filter.set_name(Some(&gettext("Text report")));
+    text_filter.add_pattern("*.txt");
+
+    let json_filter = FileFilter::new();
+    json_filter.set_name(Some(&gettext("JSON report")));
+    json_filter.add_pattern("*.json");
+
+    let filters = gio::ListStore::new::<FileFilter>();
+    filters.append(&text_filter);
+    filters.append(&json_filter);
+
+    let dialog = FileDialog::builder()
+        .title(gettext("Save Diagnostic Report"))
+        .accept_label(gettext("_Save"))
+        .initial_name("xfprintd-gui-report.txt")
+        .filters(&filters)
+        .default_filter(&text_filter)
+        .modal(true)
+        .build();
+
+    let window_clone = window.clone();
+    dialog.save(Some(window), gio::Cancellable::NONE, move |result| {
+        // Dismissing the dialog is reported as an error too
+        if let Some(path) = result.ok().and_then(|file| file.path()) {
+            save_report(&window_clone, rt, path);
+        }
+    });
+}
+
+/// Collect the report on the runtime and report the outcome on the main thread.
+///
+/// A `.json` file name selects the JSON format.
+fn save_report(window: &ApplicationWindow, rt: Arc<Runtime>, path: PathBuf) {
+    let format = ReportFormat::for_path(&path);
+    let logs = logging::recent();
+
+    let (tx, rx) = mpsc::channel();
+    let save_path = path.clone();
+    rt.spawn(async move {
+        let report = DiagnosticReport::collect(config::app_info::VERSION, logs).await;
+        let _ = tx.send(report.save(&save_path, format));
+    });
+
+    let window = window.clone();
+    glib::idle_add_local(move || match rx.try_recv() {
+        Ok(Ok(())) => {
+            dialogs::show_success_dialog(
+                &window,
+                &gettext("Report Saved"),
+                &gettext(
+                    "The diagnostic report was saved. User, home and host names have been removed.",
+                ),
+                &path.display().to_string(),
+            );
+            glib::ControlFlow::Break
+        }
+        Ok(Err(e)) => {
+            warn!("Failed to save diagnostic report: {}", e);
+            dialogs::show_error_dialog(
+                &window,
+                &gettext("Report Not Saved"),
+                &gettext("The diagnostic report could not be written."),
+                &format!("{}: {}", path.display(), e),
+            );
+            glib::ControlFlow::Break
+        }
+        Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+    });
+}
@@ -0,0 +1,77 @@
+//! Reusable dialog helpers.
+
+use crate::config;
+use crate::i18n::gettext;
+use crate::ui::utils::extract_widget;
+use adw::prelude::*;
+use gtk4::{Builder, Button, Image, Label, Widget, Window};
+
+/// Show a modal error dialog attached to the window containing `anchor`.
+pub fn show_error_dialog(anchor: &impl IsA<Widget>, title: &str, message: &str, details: &str) {
+    show_message_dialog(anchor, "dialog-error", title, message, details);
+}
+
+/// Show a modal warning dialog attached to the window containing `anchor`.
+pub fn show_warning_dialog(anchor: &impl IsA<Widget>, title: &str, message: &str, details: &str) {
+    show_message_dialog(anchor, "dialog-warning", title, message, details);
+}
+
+/// Show a modal confirmation dialog attached to the window containing `anchor`.
+pub fn show_success_dialog(anchor: &impl IsA<Widget>, title: &str, message: &str, details: &str) {
+    show_message_dialog(anchor, "checkmark", title, message, details);
+}
+
+/// Ask the user to confirm an action; `on_response` receives whether they accepted.
+pub fn show_confirm_dialog(
+    anchor: &impl IsA<Widget>,
+    title: &str,
+    message: &str,
+    confirm_label: &str,
+    on_response: impl Fn(bool) + 'static,
+) {
+    let dialog = adw::AlertDialog::new(Some(title), Some(message));
+    dialog.add_responses(&[("cancel", &gettext("Not Now")), ("confirm", confirm_label)]);
+    dialog.set_response_appearance("confirm", adw::ResponseAppearance::Suggested);
+    dialog.set_default_response(Some("confirm"));
+    dialog.set_close_response("cancel");
+
+    dialog.connect_response(None, move |_, response| on_response(response == "confirm"));
+    dialog.present(Some(anchor));
+}
+
+/// Build and present the shared message dialog with the given icon.
+fn show_message_dialog(
+    anchor: &impl IsA<Widget>,
+    icon_name: &str,
+    title: &str,
+    message: &str,
+    details: &str,
+) {
+    let builder = Builder::from_resource(config::resources::dialogs::ERROR);
+    let dialog: Window = extract_widget(&builder, "error_window");
+    let icon: Image = extract_widget(&builder, "error_icon");
+    let title_label: Label = extract_widget(&builder, "title_label");
+    let main_message: Label = extract_widget(&builder, "main_message");
+    let details_label: Label = extract_widget(&builder, "details_label");
+    let ok_button: Button = extract_widget(&builder, "ok_button");
+
+    icon.set_icon_name(Some(icon_name));
+    dialog.set_title(Some(title));
+    title_label.set_label(title);
+    main_message.set_label(message);
+    details_label.set_label(details);
+    details_label.set_visible(!details.is_empty());
+
+    if let Some(root) = anchor.as_ref().root() {
+        if let Some(parent) = root.downcast_ref::<Window>() {
+            dialog.set_transient_for(Some(parent));
+        }
+    }
+
+    let dialog_clone = dialog.clone();
+    ok_button.connect_clicked(move |_| {
+        dialog_clone.close();
+    });
+
+    dialog.present();
+}
@@ -1,15 +1,17 @@
 //! Fingerprint management UI functionality.
 
-use crate::core::{fprintd, util, FingerprintContext};
+use crate::core::{util, FingerprintContext};
+use crate::i18n::{gettext, gettext_f};
 use crate::ui::context::AppContext;
-use gtk4::glib;
+use crate::ui::hand_diagram::{self, Hand, HandDiagram};
 use gtk4::prelude::*;
-use gtk4::{
-    pango, Align, Box as GtkBox, Button, Image, Justification, Label, Orientation, Overlay,
-};
-use log::info;
+use gtk4::{accessible, gdk, glib};
+use gtk4::{Box as GtkBox, Button, EventControllerKey, PropagationPhase};
+use log::{debug, info};
+use xfprintd_gui_core::fingerprints::scan_enrolled_fingerprints;
 
 use std::collections::HashSet;
+use std::rc::Rc;
 use std::sync::mpsc::{self, TryRecvError};
 
 /// Perform initial fingerprint scan and enable switches if fingerprints found.
@@ -38,7 +40,7 @@ pub fn perform_initial_fingerprint_scan(ctx: &AppContext) {
     let rt = ctx.fingerprint_ctx.rt.clone();
     rt.spawn(async move {
         info!("Starting system fingerprint device detection and enrollment scan");
-        let enrolled = crate::fingerprints::scan_enrolled_fingerprints().await;
+        let enrolled = scan_enrolled_fingerprints().await;
         let has_any = !enrolled.is_empty();
 
         if has_any {
@@ -46,11 +48,11 @@ pub fn perform_initial_fingerprint_scan(ctx: &AppContext) {
                 "System ready: {} enrolled fingerprint(s) detected",
                 enrolled.len()
             );
-            info!("PAM authentication switches will be enabled");
+            debug!("PAM authentication switches will be enabled");
         } else {
             info!("No enrolled fingerprints found on initial scan");
-            info!("PAM authentication switches will remain disabled until enrollment");
-            info!("Click 'Enroll' to add your first fingerprint");
+            debug!("PAM authentication switches will remain disabled until enrollment");
+            debug!("Click 'Enroll' to add your first fingerprint");
         }
 
         let _ = tx.send(has_any);
@@ -78,7 +80,7 @@ pub fn refresh_fingerprint_display(ctx: FingerprintContext) {
     }
 
     ctx.rt.spawn(async move {
-        let enrolled = crate::fingerprints::scan_enrolled_fingerprints().await;
+        let enrolled = scan_enrolled_fingerprints().await;
         let _ = tx.send(enrolled);
     });
 }
@@ -94,12 +96,12 @@ fn update_fingerprint_ui(ctx: &FingerprintContext) {
 
     if has_any {
         info!("Enabling PAM authentication switches (fingerprints available)");
-        info!("- Login switch: enabled");
-        info!("- Sudo switch: enabled");
-        info!("- Polkit switch: enabled");
+        debug!("- Login switch: enabled");
+        debug!("- Sudo switch: enabled");
+        debug!("- Polkit switch: enabled");
     } else {
         info!("Disabling PAM authentication switches (no fingerprints enrolled)");
-        info!("User must enroll fingerprints before enabling authentication");
+        debug!("User must enroll fingerprints before enabling authentication");
     }
 
     ctx.set_pam_switches_sensitive(has_any);
@@ -107,119 +109,221 @@ fn update_fingerprint_ui(ctx: &FingerprintContext) {
     // Update button states based on selected finger and enrollment status
     update_button_states(ctx);
 
+    rebuild_finger_picker(ctx);
+
+    debug!("Finger selection UI updated successfully with hand separation");
+}
+
+/// Rebuild the hand diagrams from the current enrollment and pick state.
+pub fn rebuild_finger_picker(ctx: &FingerprintContext) {
+    // Rebuilding drops the focused button; keyboard users keep their place
+    let focused = ctx
+        .finger_buttons
+        .borrow()
+        .iter()
+        .find(|(_, button)| button.has_focus())
+        .map(|(key, _)| key.clone());
+
     while let Some(child) = ctx.ui.flow.first_child() {
         ctx.ui.flow.remove(&child);
     }
 
     create_finger_sections(ctx);
 
-    info!("Finger selection UI updated successfully with hand separation");
+    if let Some(key) = focused {
+        focus_finger(ctx, &key);
+    }
 }
 
-/// Create finger button sections for left and right hands.
+/// Create the hand diagrams, left hand first as the hands lie palm-down.
 fn create_finger_sections(ctx: &FingerprintContext) {
-    let left_fingers = &fprintd::FINGERS[0..5];
-    let right_fingers = &fprintd::FINGERS[5..10];
-
-    let right_hand_container = create_hand_section("Right Hand", right_fingers, ctx);
-    ctx.ui.flow.append(&right_hand_container);
+    let mut buttons = Vec::new();
+
+    for (hand, title) in [
+        (Hand::Left, gettext("Left Hand")),
+        (Hand::Right, gettext("Right Hand")),
+    ] {
+        let diagram = HandDiagram::new(hand, &title, |finger| ctx.is_finger_enrolled(finger));
+        for (finger, button) in &diagram.buttons {
+            setup_finger_button(finger, button, ctx);
+            if ctx.ui.pick.is_picked(finger) {
+                hand_diagram::set_picked(button, true);
+            }
+        }
+        append_to_flow(ctx, &diagram.widget);
+        buttons.extend(diagram.buttons);
+    }
 
-    let left_hand_container = create_hand_section("Left Hand", left_fingers, ctx);
-    ctx.ui.flow.append(&left_hand_container);
+    setup_keyboard_navigation(&buttons, ctx);
+    *ctx.finger_buttons.borrow_mut() = buttons;
 }
 
-/// Create hand section (left or right) with finger buttons.
-fn create_hand_section(title: &str, fingers: &[&str], ctx: &FingerprintContext) -> GtkBox {
-    let hand_container = GtkBox::new(Orientation::Vertical, 10);
-    hand_container.set_halign(Align::Center);
+/// Append a hand section; only the finger buttons inside take keyboard focus.
+fn append_to_flow(ctx: &FingerprintContext, section: &GtkBox) {
+    ctx.ui.flow.append(section);
+    if let Some(child) = section.parent() {
+        child.set_focusable(false);
+    }
+}
 
-    let title_label = Label::new(Some(title));
-    title_label.set_css_classes(&["hand-title"]);
-    hand_container.append(&title_label);
+/// Describe a finger's hit target and select the finger when it is clicked,
+/// or tick it while several fingers are being picked.
+fn setup_finger_button(finger: &str, button: &Button, ctx: &FingerprintContext) {
+    describe_finger_button(
+        button,
+        finger,
+        ctx.is_finger_enrolled(finger),
+        ctx.ui.pick.is_active(),
+    );
 
-    let finger_grid = GtkBox::new(Orientation::Horizontal, 8);
-    finger_grid.set_halign(Align::Center);
-    finger_grid.set_homogeneous(true);
+    let finger_key = finger.to_string();
+    let ctx_clone = ctx.clone();
 
-    for finger in fingers {
-        let finger_box = create_finger_button(finger, ctx);
-        finger_grid.append(&finger_box);
-    }
+    button.connect_clicked(move |button| {
+        if ctx_clone.ui.pick.is_active() {
+            pick_finger(&ctx_clone, &finger_key, button);
+        } else {
+            select_finger(&ctx_clone, &finger_key);
+        }
+    });
+}
 
-    hand_container.append(&finger_grid);
-    hand_container
+/// Set a finger button's tooltip and accessible name and description.
+pub fn describe_finger_button(button: &Button, finger: &str, is_enrolled: bool, picking: bool) {
+    // The drawing carries no text, so state is spelled out for screen readers
+    let display_name = util::display_finger_name(finger);
+    let (accessible_name, description) = if is_enrolled {
+        (
+            gettext_f("{finger}, enrolled", &[("finger", &display_name)]),
+            if picking {
+                gettext("Already enrolled.")
+            } else {
+                gettext("Press Enter to verify this fingerprint.")
+            },
+        )
+    } else {
+        (
+            gettext_f("{finger}, not enrolled", &[("finger", &display_name)]),
+            if picking {
+                gettext("Press Enter to add or remove this finger from the selection.")
+            } else {
+                gettext("Press Enter to enroll this finger.")
+            },
+        )
+    };
+    button.update_property(&[
+        accessible::Property::Label(&accessible_name),
+        accessible::Property::Description(&description),
+    ]);
+    button.set_tooltip_text(Some(&accessible_name));
 }
 
-/// Create finger button widget.
-fn create_finger_button(finger: &str, ctx: &FingerprintContext) -> GtkBox {
-    let container = GtkBox::new(Orientation::Vertical, 5);
-    container.set_halign(Align::Center);
-    container.set_size_request(120, 120);
-
-    let button = Button::new();
-    button.set_size_request(90, 90);
-
-    let is_enrolled = ctx.is_finger_enrolled(finger);
-
-    // Base fingerprint icon with optional enrollment badge overlay
-    let overlay = Overlay::new();
-    let base_image = Image::from_icon_name("fingerprint-symbolic");
-    base_image.set_pixel_size(64);
-    overlay.set_child(Some(&base_image));
-
-    if is_enrolled {
-        let badge = Image::from_icon_name("checkmark");
-        badge.set_pixel_size(32);
-        badge.set_halign(Align::End);
-        badge.set_valign(Align::End);
-        overlay.add_overlay(&badge);
+/// Tick or untick a finger for the next multi-finger enrollment.
+fn pick_finger(ctx: &FingerprintContext, finger_key: &str, button: &Button) {
+    if ctx.is_finger_enrolled(finger_key) {
+        return;
     }
 
-    button.set_child(Some(&overlay));
+    let picked = ctx.ui.pick.toggle_finger(finger_key);
+    hand_diagram::set_picked(button, picked);
+    info!(
+        "User {} finger '{}' for enrollment",
+        if picked { "picked" } else { "unpicked" },
+        finger_key
+    );
+}
 
-    if is_enrolled {
-        button.add_css_class("finger-enrolled");
+/// Open the finger page for `finger_key` and return its main action:
+/// Verify for an enrolled finger, Add otherwise.
+fn select_finger(ctx: &FingerprintContext, finger_key: &str) -> Button {
+    ctx.set_selected_finger(Some(finger_key.to_string()));
+    ctx.ui
+        .labels
+        .finger
+        .set_label(&util::display_finger_name(finger_key));
+    ctx.ui
+        .labels
+        .show_action_text(&gettext("Select an action below."));
+    ctx.ui.stack.set_visible_child_name("finger");
+    info!("User selected finger: '{}'", finger_key);
+
+    // Update button states when finger is selected
+    let is_enrolled = ctx.is_finger_enrolled(finger_key);
+    ctx.update_button_states(is_enrolled);
+
+    let primary = if is_enrolled {
+        ctx.ui.buttons.verify.clone()
     } else {
-        button.add_css_class("finger-unenrolled");
-    }
+        ctx.ui.buttons.add.clone()
+    };
+    primary.grab_focus();
+    primary
+}
 
-    let finger_key = finger.to_string();
-    let ctx_clone = ctx.clone();
+/// Left and Right move between fingers as they appear on screen, Up and Down
+/// jump to the same finger of the other hand, and Enter opens a finger and
+/// starts enrolling or verifying it right away.
+fn setup_keyboard_navigation(buttons: &[(String, Button)], ctx: &FingerprintContext) {
+    let order: Rc<Vec<Button>> = Rc::new(buttons.iter().map(|(_, b)| b.clone()).collect());
+
+    for (index, (finger_key, button)) in buttons.iter().enumerate() {
+        let controller = EventControllerKey::new();
+        // Runs before the button's own Enter binding
+        controller.set_propagation_phase(PropagationPhase::Capture);
+
+        let order = order.clone();
+        let finger_key = finger_key.clone();
+        let ctx = ctx.clone();
+        controller.connect_key_pressed(move |_, key, _, modifiers| {
+            if modifiers.intersects(gdk::ModifierType::CONTROL_MASK | gdk::ModifierType::ALT_MASK) {
+                return glib::Propagation::Proceed;
+            }
 
-    button.connect_clicked(move |_| {
-        ctx_clone.set_selected_finger(Some(finger_key.clone()));
-        ctx_clone
-            .ui
-            .labels
-            .finger
-            .set_label(&util::display_finger_name(&finger_key));
-        ctx_clone.ui.labels.action.set_use_markup(false);
-        ctx_clone
-            .ui
-            .labels
-            .action
-            .set_label("Select an action below.");
-        ctx_clone.ui.stack.set_visible_child_name("finger");
-        info!("User selected finger: '{}'", finger_key);
-
-        // Update button states when finger is selected
-        let is_enrolled = ctx_clone.is_finger_enrolled(&finger_key);
-        ctx_clone.update_button_states(is_enrolled);
-    });
+            let target = match key {
+                gdk::Key::Left => index.checked_sub(1),
+                gdk::Key::Right => Some(index + 1),
+                gdk::Key::Up | gdk::Key::Down => order.len().checked_sub(index + 1),
+                gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::ISO_Enter
+                    if ctx.ui.pick.is_active() =>
+                {
+                    pick_finger(&ctx, &finger_key, &order[index]);
+                    return glib::Propagation::Stop;
+                }
+                gdk::Key::Return | gdk::Key::KP_Enter | gdk::Key::ISO_Enter => {
+                    let action = select_finger(&ctx, &finger_key);
+                    if action.is_sensitive() {
+                        action.emit_clicked();
+                    }
+                    return glib::Propagation::Stop;
+                }
+                _ => return glib::Propagation::Proceed,
+            };
+
+            if let Some(button) = target.and_then(|i| order.get(i)) {
+                button.grab_focus();
+            }
+            glib::Propagation::Stop
+        });
+        button.add_controller(controller);
+    }
+}
 
-    let display_name = util::display_finger_name(finger);
-    let short_name = util::create_short_finger_name(&display_name);
-
-    let label = Label::new(Some(&short_name));
-    label.set_css_classes(&["finger-label"]);
-    label.set_wrap(true);
-    label.set_wrap_mode(pango::WrapMode::Word);
-    label.set_justify(Justification::Center);
-    label.set_size_request(90, -1);
-
-    container.append(&button);
-    container.append(&label);
-    container
+/// Move keyboard focus back to the selected finger's button on the manage page.
+pub fn focus_selected_finger(ctx: &FingerprintContext) {
+    if let Some(selected) = ctx.get_selected_finger() {
+        focus_finger(ctx, &selected);
+    }
+}
+
+fn focus_finger(ctx: &FingerprintContext, finger_key: &str) {
+    if let Some((_, button)) = ctx
+        .finger_buttons
+        .borrow()
+        .iter()
+        .find(|(key, _)| key == finger_key)
+    {
+        button.grab_focus();
+    }
 }
 
 /// Update button states based on selected finger and enrollment status
@@ -236,7 +340,8 @@ fn update_button_states(ctx: &FingerprintContext) {
     } else {
         // No finger selected, disable both buttons
         ctx.ui.buttons.add.set_sensitive(false);
+        ctx.ui.buttons.verify.set_sensitive(false);
         ctx.ui.buttons.delete.set_sensitive(false);
-        info!("No finger selected, both buttons disabled");
+        debug!("No finger selected, both buttons disabled");
     }
 }
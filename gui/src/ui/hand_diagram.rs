@@ -0,0 +1,330 @@
+//! Hand illustration used to pick a finger.
+//!
+//! Each hand is drawn palm-down with cairo, so the thumbs point towards each
+//! other. Every finger is covered by a transparent button that takes clicks
+//! on the finger's shape, keyboard focus, tooltips and accessible names; the
+//! drawing follows the buttons' hover, focus and checked state, the latter
+//! marking fingers picked for a multi-finger enrollment. Fingers take their colour
+//! from their button's CSS, so they follow the theme like status messages.
+
+use gtk4::prelude::*;
+use gtk4::subclass::prelude::*;
+use gtk4::{accessible, cairo, gdk, glib};
+use gtk4::{Align, Box as GtkBox, Button, DrawingArea, Fixed, Label, Orientation, Overlay};
+use std::cell::Cell;
+use std::f64::consts::PI;
+use std::rc::Rc;
+use xfprintd_gui_core::hand::{
+    self, FingerShape, HEIGHT, PALM, PALM_BOTTOM_RADIUS, PALM_TOP_RADIUS, RIGHT_HAND, WIDTH,
+};
+
+pub use xfprintd_gui_core::hand::Hand;
+
+mod imp {
+    use super::*;
+
+    /// Hand, finger and top-left corner of the button in the drawing.
+    #[derive(Clone, Copy)]
+    pub struct Placement {
+        pub hand: Hand,
+        pub shape: &'static FingerShape,
+        pub origin: (f64, f64),
+    }
+
+    #[derive(Default)]
+    pub struct FingerButton {
+        pub placement: Cell<Option<Placement>>,
+    }
+
+    #[glib::object_subclass]
+    impl ObjectSubclass for FingerButton {
+        const NAME: &'static str = "XfprintdFingerButton";
+        type Type = super::FingerButton;
+        type ParentType = Button;
+    }
+
+    impl ObjectImpl for FingerButton {}
+
+    impl WidgetImpl for FingerButton {
+        fn contains(&self, x: f64, y: f64) -> bool {
+            let Some(Placement {
+                hand,
+                shape,
+                origin,
+            }) = self.placement.get()
+            else {
+                return self.parent_contains(x, y);
+            };
+            hand::finger_at(hand, origin.0 + x, origin.1 + y)
+                .is_some_and(|finger| finger.name == shape.name)
+        }
+    }
+
+    impl ButtonImpl for FingerButton {}
+}
+
+glib::wrapper! {
+    /// Transparent button over a finger that only takes the pointer on the
+    /// finger as drawn, not on the palm or a neighbour inside its bounds.
+    pub struct FingerButton(ObjectSubclass<imp::FingerButton>)
+        @extends Button, gtk4::Widget,
+        @implements gtk4::Accessible, gtk4::Actionable, gtk4::Buildable, gtk4::ConstraintTarget;
+}
+
+impl FingerButton {
+    fn new(hand: Hand, shape: &'static FingerShape, origin: (f64, f64)) -> Self {
+        let button: Self = glib::Object::new();
+        button.imp().placement.set(Some(imp::Placement {
+            hand,
+            shape,
+            origin,
+        }));
+        button
+    }
+}
+
+/// A drawn hand and the buttons covering its fingers.
+pub struct HandDiagram {
+    pub widget: GtkBox,
+    /// Finger keys with their buttons, from left to right on screen.
+    pub buttons: Vec<(String, Button)>,
+}
+
+/// One finger as the drawing sees it.
+struct DrawnFinger {
+    shape: &'static FingerShape,
+    button: Button,
+    enrolled: bool,
+}
+
+impl HandDiagram {
+    /// Build the diagram; `is_enrolled` decides how each finger is coloured.
+    pub fn new(hand: Hand, title: &str, is_enrolled: impl Fn(&str) -> bool) -> Self {
+        let widget = GtkBox::new(Orientation::Vertical, 10);
+        widget.set_halign(Align::Center);
+
+        let title_label = Label::new(Some(title));
+        title_label.set_css_classes(&["hand-title"]);
+        widget.append(&title_label);
+
+        let area = DrawingArea::new();
+        area.set_content_width(WIDTH as i32);
+        area.set_content_height(HEIGHT as i32);
+
+        let hit_layer = Fixed::new();
+        hit_layer.update_relation(&[accessible::Relation::LabelledBy(
+            &[title_label.upcast_ref()],
+        )]);
+        let mut fingers = Vec::new();
+        for shape in &RIGHT_HAND {
+            let key = format!("{}-{}", hand.prefix(), shape.name);
+            let (x, y, width, height) = bounds(hand, shape);
+
+            let button = FingerButton::new(hand, shape, (x, y)).upcast::<Button>();
+            button.add_css_class("finger-hit");
+            button.set_size_request(width as i32, height as i32);
+            hit_layer.put(&button, x, y);
+
+            let area = area.clone();
+            button.connect_state_flags_changed(move |_, _| area.queue_draw());
+
+            let enrolled = is_enrolled(&key);
+            if enrolled {
+                button.add_css_class("enrolled");
+            }
+            fingers.push(DrawnFinger {
+                shape,
+                enrolled,
+                button,
+            });
+        }
+
+        let mut buttons: Vec<(String, Button, f64)> = fingers
+            .iter()
+            .map(|finger| {
+                let key = format!("{}-{}", hand.prefix(), finger.shape.name);
+                let (x, _, width, _) = bounds(hand, finger.shape);
+                (key, finger.button.clone(), x + width / 2.0)
+            })
+            .collect();
+        buttons.sort_by(|a, b| a.2.total_cmp(&b.2));
+
+        let fingers = Rc::new(fingers);
+        area.set_draw_func(move |area, cr, _, _| draw(area, cr, hand, &fingers));
+
+        let overlay = Overlay::new();
+        overlay.set_child(Some(&area));
+        overlay.add_overlay(&hit_layer);
+        widget.append(&overlay);
+
+        HandDiagram {
+            widget,
+            buttons: buttons
+                .into_iter()
+                .map(|(key, button, _)| (key, button))
+                .collect(),
+        }
+    }
+}
+
+/// Mark a finger's button as picked, for the drawing and for screen readers.
+pub fn set_picked(button: &Button, picked: bool) {
+    if picked {
+        button.set_state_flags(gtk4::StateFlags::CHECKED, false);
+    } else {
+        button.unset_state_flags(gtk4::StateFlags::CHECKED);
+    }
+    button.update_state(&[accessible::State::Pressed(if picked {
+        gtk4::AccessibleTristate::True
+    } else {
+        gtk4::AccessibleTristate::False
+    })]);
+}
+
+/// Bounding box of a finger: x, y, width and height.
+fn bounds(hand: Hand, shape: &FingerShape) -> (f64, f64, f64, f64) {
+    let radius = shape.width / 2.0;
+    let (x1, x2) = (hand.x(shape.base.0), hand.x(shape.tip.0));
+    let (y1, y2) = (shape.base.1, shape.tip.1);
+    let left = x1.min(x2) - radius;
+    let top = y1.min(y2) - radius;
+    (
+        left,
+        top,
+        x1.max(x2) + radius - left,
+        y1.max(y2) + radius - top,
+    )
+}
+
+fn draw(area: &DrawingArea, cr: &cairo::Context, hand: Hand, fingers: &[DrawnFinger]) {
+    let foreground = area.color();
+    let set_color = |color: &gdk::RGBA, alpha: f32| {
+        cr.set_source_rgba(
+            f64::from(color.red()),
+            f64::from(color.green()),
+            f64::from(color.blue()),
+            f64::from(color.alpha() * alpha),
+        );
+    };
+
+    palm_path(cr, hand);
+    set_color(&foreground, 0.10);
+    let _ = cr.fill_preserve();
+    set_color(&foreground, 0.35);
+    cr.set_line_width(1.5);
+    let _ = cr.stroke();
+
+    // Fingers stop at the palm instead of overlapping its translucent fill
+    cr.save().ok();
+    cr.rectangle(0.0, 0.0, WIDTH, HEIGHT);
+    palm_path(cr, hand);
+    cr.set_fill_rule(cairo::FillRule::EvenOdd);
+    cr.clip();
+
+    for finger in fingers {
+        let flags = finger.button.state_flags();
+        let hovered = flags.contains(gtk4::StateFlags::PRELIGHT);
+        let focused = flags.contains(gtk4::StateFlags::FOCUS_VISIBLE);
+
+        finger_path(cr, hand, finger.shape, 0.0);
+        if finger.enrolled || is_picked(finger) {
+            set_color(&finger.button.color(), if hovered { 0.9 } else { 0.7 });
+        } else {
+            set_color(&foreground, if hovered { 0.28 } else { 0.14 });
+        }
+        let _ = cr.fill_preserve();
+        set_color(&foreground, 0.35);
+        cr.set_line_width(1.5);
+        let _ = cr.stroke();
+
+        if focused {
+            finger_path(cr, hand, finger.shape, 3.0);
+            set_color(&foreground, 0.9);
+            cr.set_line_width(2.0);
+            let _ = cr.stroke();
+        }
+    }
+    cr.restore().ok();
+
+    // Enrolled fingers carry a check mark, so state does not rely on colour alone
+    for finger in fingers.iter().filter(|finger| finger.enrolled) {
+        let (x, y) = mark_position(hand, finger.shape);
+        cr.move_to(x - 5.0, y);
+        cr.line_to(x - 1.5, y + 4.0);
+        cr.line_to(x + 5.0, y - 4.5);
+        cr.set_source_rgba(1.0, 1.0, 1.0, 0.95);
+        cr.set_line_width(2.5);
+        cr.set_line_cap(cairo::LineCap::Round);
+        cr.set_line_join(cairo::LineJoin::Round);
+        let _ = cr.stroke();
+    }
+
+    // Picked fingers carry a plus sign
+    for finger in fingers.iter().filter(|finger| is_picked(finger)) {
+        let (x, y) = mark_position(hand, finger.shape);
+        cr.move_to(x - 5.0, y);
+        cr.line_to(x + 5.0, y);
+        cr.move_to(x, y - 5.0);
+        cr.line_to(x, y + 5.0);
+        cr.set_source_rgba(1.0, 1.0, 1.0, 0.95);
+        cr.set_line_width(2.5);
+        cr.set_line_cap(cairo::LineCap::Round);
+        let _ = cr.stroke();
+    }
+}
+
+fn is_picked(finger: &DrawnFinger) -> bool {
+    !finger.enrolled
+        && finger
+            .button
+            .state_flags()
+            .contains(gtk4::StateFlags::CHECKED)
+}
+
+/// Outline of the palm.
+fn palm_path(cr: &cairo::Context, hand: Hand) {
+    let (left, top, right, bottom) = PALM;
+    let (top_r, bottom_r) = (PALM_TOP_RADIUS, PALM_BOTTOM_RADIUS);
+    let (left, right) = (
+        hand.x(left).min(hand.x(right)),
+        hand.x(left).max(hand.x(right)),
+    );
+
+    cr.new_sub_path();
+    cr.arc(left + top_r, top + top_r, top_r, PI, 1.5 * PI);
+    cr.arc(right - top_r, top + top_r, top_r, 1.5 * PI, 2.0 * PI);
+    cr.arc(right - bottom_r, bottom - bottom_r, bottom_r, 0.0, 0.5 * PI);
+    cr.arc(left + bottom_r, bottom - bottom_r, bottom_r, 0.5 * PI, PI);
+    cr.close_path();
+}
+
+/// Capsule outline of a finger, grown by `grow` on every side.
+fn finger_path(cr: &cairo::Context, hand: Hand, shape: &FingerShape, grow: f64) {
+    let (bx, by) = (hand.x(shape.base.0), shape.base.1);
+    let (tx, ty) = (hand.x(shape.tip.0), shape.tip.1);
+    let length = (tx - bx).hypot(ty - by);
+    let radius = shape.width / 2.0 + grow;
+
+    cr.save().ok();
+    cr.translate(bx, by);
+    cr.rotate((ty - by).atan2(tx - bx));
+    cr.new_sub_path();
+    cr.move_to(-grow, -radius);
+    cr.line_to(length, -radius);
+    cr.arc(length, 0.0, radius, -0.5 * PI, 0.5 * PI);
+    cr.line_to(-grow, radius);
+    cr.close_path();
+    cr.restore().ok();
+}
+
+/// Where the check mark of an enrolled finger goes, just below its tip.
+fn mark_position(hand: Hand, shape: &FingerShape) -> (f64, f64) {
+    let (bx, by) = (hand.x(shape.base.0), shape.base.1);
+    let (tx, ty) = (hand.x(shape.tip.0), shape.tip.1);
+    let length = (tx - bx).hypot(ty - by);
+    let offset = shape.width / 2.0 + 2.0;
+    (
+        tx - (tx - bx) / length * offset,
+        ty - (ty - by) / length * offset,
+    )
+}
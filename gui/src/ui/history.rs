@@ -0,0 +1,180 @@
+//! History of PAM changes made through the helper.
+
+use crate::config;
+use crate::i18n::{gettext, gettext_f};
+use crate::ui::context::AppContext;
+use crate::ui::utils::extract_widget;
+use adw::prelude::*;
+use gtk4::glib;
+use gtk4::{ApplicationWindow, Builder, Button, Image, Label, ListBox, Window};
+use log::{debug, warn};
+use std::fmt::Display;
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use xfprintd_gui_core::pam::{AuditEntry, PamHelper, POLKIT_PATH, SUDO_PATH};
+
+/// Number of changes shown.
+const HISTORY_LIMIT: usize = 200;
+
+/// Set up the header bar history button.
+pub fn setup_history(ctx: &AppContext, builder: &Builder, window: &ApplicationWindow) {
+    let history_btn: Button = extract_widget(builder, "history_btn");
+    let rt = ctx.fingerprint_ctx.rt.clone();
+
+    let window = window.clone();
+    history_btn.connect_clicked(move |_| {
+        debug!("User clicked 'History' button - showing PAM change history");
+        show_history_dialog(&window, rt.clone());
+    });
+}
+
+/// Human readable name of a PAM target.
+fn service_name(target: &str) -> String {
+    match target {
+        SUDO_PATH => gettext("terminal (sudo)"),
+        POLKIT_PATH => gettext("system prompts (polkit)"),
+        other => other.to_string(),
+    }
+}
+
+/// Row title describing what was done.
+fn entry_title(entry: &AuditEntry) -> String {
+    let service = service_name(&entry.target);
+    let args: &[(&str, &dyn Display)] = &[("service", &service), ("action", &entry.action)];
+    match (entry.action.as_str(), entry.succeeded()) {
+        ("apply", true) => gettext_f("Enabled for {service}", args),
+        ("remove", true) => gettext_f("Disabled for {service}", args),
+        ("apply", false) => gettext_f("Failed to enable for {service}", args),
+        ("remove", false) => gettext_f("Failed to disable for {service}", args),
+        ("reapply", true) => gettext_f("Restored after an update for {service}", args),
+        ("reapply", false) => gettext_f("Failed to restore for {service}", args),
+        ("merge", true) => gettext_f("Merged packaged configuration for {service}", args),
+        ("merge", false) => gettext_f("Failed to merge packaged configuration for {service}", args),
+        (_, true) => gettext_f("{action} {service}", args),
+        (_, false) => gettext_f("Failed: {action} {service}", args),
+    }
+}
+
+/// Row subtitle with time, user and outcome.
+fn entry_subtitle(entry: &AuditEntry) -> String {
+    let user = match &entry.user {
+        Some(name) => format!("{} (uid {})", name, entry.uid),
+        None => format!("uid {}", entry.uid),
+    };
+    let outcome = match &entry.error {
+        Some(error) => error.clone(),
+        None if entry.changed() => gettext("File changed"),
+        None => gettext("File already up to date"),
+    };
+
+    glib::markup_escape_text(&format!(
+        "{}\n{}",
+        gettext_f(
+            "{time} by {user}",
+            &[("time", &entry.time.replace('T', " ")), ("user", &user)],
+        ),
+        outcome
+    ))
+    .to_string()
+}
+
+fn entry_row(entry: &AuditEntry) -> adw::ActionRow {
+    let row = adw::ActionRow::builder()
+        .title(entry_title(entry))
+        .subtitle(entry_subtitle(entry))
+        .build();
+
+    let icon = Image::from_icon_name(if entry.succeeded() {
+        "checkmark"
+    } else {
+        "dialog-error"
+    });
+    icon.set_pixel_size(16);
+    row.add_prefix(&icon);
+
+    if let (Some(before), Some(after)) = (&entry.before, &entry.after) {
+        row.set_tooltip_text(Some(&gettext_f(
+            "SHA-256 before: {before}\nSHA-256 after: {after}",
+            &[("before", before), ("after", after)],
+        )));
+    }
+
+    row
+}
+
+/// Load the history in the background and fill the list.
+fn load_history(rt: &Runtime, summary: &Label, list: &ListBox, refresh: &Button) {
+    summary.set_label(&gettext("Loading history…"));
+    summary.set_visible(true);
+    refresh.set_sensitive(false);
+
+    let (tx, rx) = mpsc::channel();
+    rt.spawn_blocking(move || {
+        let _ = tx.send(PamHelper::history(HISTORY_LIMIT).map_err(|e| e.to_string()));
+    });
+
+    let summary = summary.clone();
+    let list = list.clone();
+    let refresh = refresh.clone();
+    glib::idle_add_local(move || match rx.try_recv() {
+        Ok(result) => {
+            while let Some(child) = list.first_child() {
+                list.remove(&child);
+            }
+
+            match result {
+                Ok(entries) if entries.is_empty() => {
+                    summary.set_label(&gettext("No PAM changes have been recorded yet."));
+                }
+                Ok(entries) => {
+                    for entry in &entries {
+                        list.append(&entry_row(entry));
+                    }
+                    summary.set_visible(false);
+                }
+                Err(e) => {
+                    warn!("Failed to read PAM change history: {}", e);
+                    summary.set_label(&gettext_f(
+                        "The history could not be read: {error}",
+                        &[("error", &e)],
+                    ));
+                }
+            }
+
+            list.set_visible(list.first_child().is_some());
+            refresh.set_sensitive(true);
+            glib::ControlFlow::Break
+        }
+        Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+    });
+}
+
+/// Show the history dialog.
+fn show_history_dialog(main_window: &ApplicationWindow, rt: Arc<Runtime>) {
+    let builder = Builder::from_resource(config::resources::dialogs::HISTORY);
+
+    let history_window: Window = extract_widget(&builder, "history_window");
+    let summary: Label = extract_widget(&builder, "summary_label");
+    let list: ListBox = extract_widget(&builder, "history_list");
+    let refresh_button: Button = extract_widget(&builder, "refresh_button");
+    let close_button: Button = extract_widget(&builder, "close_button");
+
+    history_window.set_transient_for(Some(main_window));
+
+    {
+        let rt = rt.clone();
+        let summary = summary.clone();
+        let list = list.clone();
+        refresh_button.connect_clicked(move |button| load_history(&rt, &summary, &list, button));
+    }
+
+    let history_window_clone = history_window.clone();
+    close_button.connect_clicked(move |_| {
+        history_window_clone.close();
+    });
+
+    load_history(&rt, &summary, &list, &refresh_button);
+    history_window.present();
+}
@@ -0,0 +1,61 @@
+//! Log viewer reachable from the About dialog.
+
+use crate::config;
+use crate::i18n::{gettext, gettext_f};
+use crate::logging;
+use crate::ui::utils::extract_widget;
+use gtk4::prelude::*;
+use gtk4::{Builder, Button, Label, TextView, Window};
+use log::info;
+
+/// Show the application log in a window on top of `parent`.
+pub fn show_log_viewer(parent: &impl IsA<Window>) {
+    let builder = Builder::from_resource(config::resources::dialogs::LOG_VIEWER);
+
+    let log_window: Window = extract_widget(&builder, "log_window");
+    let path_label: Label = extract_widget(&builder, "path_label");
+    let log_view: TextView = extract_widget(&builder, "log_view");
+    let refresh_button: Button = extract_widget(&builder, "refresh_button");
+    let copy_button: Button = extract_widget(&builder, "copy_button");
+    let close_button: Button = extract_widget(&builder, "close_button");
+
+    log_window.set_transient_for(Some(parent));
+
+    path_label.set_label(&match logging::log_file() {
+        Some(path) => gettext_f("Log file: {path}", &[("path", &path.display())]),
+        None => gettext("The log file could not be opened; showing messages from this session."),
+    });
+
+    load_log(&log_view);
+
+    {
+        let log_view = log_view.clone();
+        refresh_button.connect_clicked(move |_| load_log(&log_view));
+    }
+
+    {
+        let log_view = log_view.clone();
+        copy_button.connect_clicked(move |button| {
+            let buffer = log_view.buffer();
+            let text = buffer.text(&buffer.start_iter(), &buffer.end_iter(), false);
+            button.clipboard().set_text(&text);
+            info!("Copied application log to the clipboard");
+        });
+    }
+
+    let log_window_clone = log_window.clone();
+    close_button.connect_clicked(move |_| {
+        log_window_clone.close();
+    });
+
+    log_window.present();
+}
+
+/// Replace the view's content with the current log and scroll to the newest line.
+fn load_log(log_view: &TextView) {
+    let buffer = log_view.buffer();
+    buffer.set_text(&logging::history());
+
+    let mut end = buffer.end_iter();
+    log_view.scroll_to_iter(&mut end, 0.0, false, 0.0, 1.0);
+}
@@ -4,15 +4,37 @@
 //! - `app`: Application setup and initialization
 //! - `pam_ui`: PAM authentication switches UI
 //! - `navigation`: Navigation buttons and dialogs
+//! - `notifications`: Desktop notifications while the window is in the background
 //! - `button_handlers`: Button click handlers
 //! - `fingerprint_ui`: Fingerprint management UI
+//! - `hand_diagram`: Hand illustration used to pick a finger
+//! - `dialogs`: Reusable dialog helpers
+//! - `diagnostic_report`: Diagnostic report export action
+//! - `history`: History of PAM changes made through the helper
+//! - `log_viewer`: Application log viewer
+//! - `multi_enroll`: Guided enrollment of several fingers
+//! - `onboarding`: First-run setup
+//! - `preferences_dialog`: Preferences dialog and applying stored preferences
+//! - `system_status`: System status panel and fix-it actions
+//! - `theme`: Status colours that follow the theme
 
 pub mod app;
 pub mod button_handlers;
 pub mod context;
+pub mod diagnostic_report;
+pub mod dialogs;
 pub mod fingerprint_ui;
+pub mod hand_diagram;
+pub mod history;
+pub mod log_viewer;
+pub mod multi_enroll;
 pub mod navigation;
+pub mod notifications;
+pub mod onboarding;
 pub mod pam_ui;
+pub mod preferences_dialog;
+pub mod system_status;
+pub mod theme;
 pub mod utils;
 
 // Re-export commonly used items
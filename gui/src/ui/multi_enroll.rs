@@ -0,0 +1,362 @@
+//! Guided enrollment of several fingers in one device session.
+
+use crate::config;
+use crate::core::{util, FingerprintContext};
+use crate::fingerprints::enroll::{failure_reason, progress_status};
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::preferences;
+use crate::ui::context::AppContext;
+use crate::ui::fingerprint_ui;
+use crate::ui::notifications::{self, Topic};
+use crate::ui::theme::Tone;
+use crate::ui::utils::{extract_widget, status_line, StatusLine};
+use adw::prelude::*;
+use gtk4::{glib, AccessibleAnnouncementPriority};
+use gtk4::{ApplicationWindow, Builder, Button, Image, Label, ListBox, Window};
+use log::{info, warn};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::runtime::Runtime;
+use xfprintd_gui_core::device_manager::DeviceError;
+use xfprintd_gui_core::enrollment::{self, EnrollOutcome, EnrollStatus, SessionUpdate};
+
+/// Updates sent from the enrollment session to the dialog.
+enum SessionEvent {
+    Started(usize),
+    Progress(StatusLine),
+    /// A finger finished; the error explains why it was not enrolled.
+    Finished(usize, Result<(), String>),
+    /// The reader went away, which ends the session.
+    ReaderDisconnected,
+    /// The session ended; an error means the reader could not be used at all.
+    Ended(Result<(), String>),
+}
+
+/// Wire the pick toggle and the start button on the manage page.
+pub fn setup_multi_enroll(ctx: &AppContext, window: &ApplicationWindow) {
+    let fingerprint_ctx = ctx.fingerprint_ctx.clone();
+    let pick = fingerprint_ctx.ui.pick.clone();
+
+    {
+        let ctx = fingerprint_ctx.clone();
+        pick.toggle.connect_toggled(move |toggle| {
+            info!(
+                "Multi-finger selection {}",
+                if toggle.is_active() {
+                    "started"
+                } else {
+                    "ended"
+                }
+            );
+            ctx.ui.pick.clear();
+            fingerprint_ui::rebuild_finger_picker(&ctx);
+
+            if toggle.is_active() {
+                toggle.announce(
+                    &gettext("Select the fingers to enroll, then start the enrollment."),
+                    AccessibleAnnouncementPriority::Medium,
+                );
+            }
+        });
+    }
+
+    let window = window.clone();
+    pick.start.connect_clicked(move |_| {
+        let fingers = fingerprint_ctx.ui.pick.picked();
+        if fingers.is_empty() {
+            return;
+        }
+        info!(
+            "User started enrollment of {} fingers: {:?}",
+            fingers.len(),
+            fingers
+        );
+        show_multi_enroll_dialog(&window, fingerprint_ctx.clone(), fingers, || {});
+    });
+}
+
+/// One finger's row in the dialog.
+struct FingerRow {
+    row: adw::ActionRow,
+    icon: Image,
+}
+
+impl FingerRow {
+    fn new(finger: &str) -> Self {
+        let row = adw::ActionRow::builder()
+            .title(util::display_finger_name(finger))
+            .subtitle(gettext("Waiting"))
+            .build();
+        let icon = Image::new();
+        icon.set_pixel_size(16);
+        row.add_prefix(&icon);
+        Self { row, icon }
+    }
+
+    fn set(&self, icon: Option<&str>, subtitle: &str) {
+        self.icon.set_icon_name(icon);
+        self.row.set_subtitle(&glib::markup_escape_text(subtitle));
+    }
+}
+
+/// Widgets of an open enrollment dialog.
+struct SessionDialog {
+    fingers: Vec<String>,
+    rows: Vec<FingerRow>,
+    current: Label,
+    progress: Label,
+    stop: Button,
+    close: Button,
+    enrolled: usize,
+    finished: Vec<bool>,
+    disconnected: bool,
+}
+
+impl SessionDialog {
+    fn handle(&mut self, event: SessionEvent) {
+        match event {
+            SessionEvent::Started(index) => {
+                let total = self.fingers.len();
+                self.rows[index].set(Some("fingerprint-symbolic"), &gettext("Scanning…"));
+                self.show_current(&gettext_f(
+                    "{finger} ({number} of {total})",
+                    &[
+                        ("finger", &util::display_finger_name(&self.fingers[index])),
+                        ("number", &(index + 1)),
+                        ("total", &total),
+                    ],
+                ));
+                status_line(
+                    Tone::Accent,
+                    "🔍",
+                    &gettext_f("Scan {stage}", &[("stage", &1)]),
+                    &gettext("Place your finger firmly on the scanner…"),
+                )
+                .show(&self.progress);
+            }
+            SessionEvent::Progress(status) => status.show(&self.progress),
+            SessionEvent::Finished(index, result) => {
+                self.finished[index] = true;
+                match result {
+                    Ok(()) => {
+                        self.enrolled += 1;
+                        self.rows[index].set(Some("checkmark"), &gettext("Enrolled"));
+                    }
+                    Err(reason) => self.rows[index].set(Some("dialog-error"), &reason),
+                }
+            }
+            SessionEvent::ReaderDisconnected => self.disconnected = true,
+            SessionEvent::Ended(result) => self.finish(result),
+        }
+    }
+
+    /// Show the summary and let the user close the dialog.
+    fn finish(&self, result: Result<(), String>) {
+        for (row, _) in self
+            .rows
+            .iter()
+            .zip(&self.finished)
+            .filter(|(_, finished)| !**finished)
+        {
+            row.set(Some("dialog-warning"), &gettext("Skipped"));
+        }
+
+        let total = self.fingers.len();
+        let summary = match result {
+            Ok(()) => ngettext_f(
+                "{count} of {total} finger enrolled",
+                "{count} of {total} fingers enrolled",
+                total,
+                &[("count", &self.enrolled), ("total", &total)],
+            ),
+            Err(_) => gettext("Enrollment could not start"),
+        };
+        self.show_current(&summary);
+        if self.disconnected {
+            notifications::notify_reader_disconnected();
+        } else {
+            match &result {
+                Ok(()) => notifications::notify(
+                    Topic::EnrollmentSession,
+                    &gettext("Enrollment Finished"),
+                    &summary,
+                ),
+                Err(e) => notifications::notify(Topic::EnrollmentSession, &summary, e),
+            }
+        }
+        let status = match result {
+            Err(e) => StatusLine::text(Tone::Warning, &e),
+            Ok(()) if self.enrolled == total => status_line(
+                Tone::Success,
+                "🎉",
+                &gettext("All done!"),
+                &gettext("Every selected finger is enrolled."),
+            ),
+            Ok(()) => status_line(
+                Tone::Warning,
+                "⚠️ ",
+                &gettext("Some fingers were not enrolled."),
+                &gettext("Select them again to retry."),
+            ),
+        };
+        status.show(&self.progress);
+
+        self.stop.set_visible(false);
+        self.close.set_visible(true);
+        self.close.grab_focus();
+    }
+
+    fn show_current(&self, text: &str) {
+        self.current.set_label(text);
+        self.current
+            .announce(text, AccessibleAnnouncementPriority::Medium);
+    }
+}
+
+/// Open the dialog and enroll `fingers` one after another; `on_ended` runs
+/// once the session is over.
+pub fn show_multi_enroll_dialog(
+    parent: &impl IsA<Window>,
+    ctx: FingerprintContext,
+    fingers: Vec<String>,
+    on_ended: impl FnOnce() + 'static,
+) {
+    let builder = Builder::from_resource(config::resources::dialogs::MULTI_ENROLL);
+
+    let window: Window = extract_widget(&builder, "multi_enroll_window");
+    let list: ListBox = extract_widget(&builder, "fingers_list");
+
+    let rows: Vec<FingerRow> = fingers
+        .iter()
+        .map(|finger| FingerRow::new(finger))
+        .collect();
+    for row in &rows {
+        list.append(&row.row);
+    }
+
+    let mut dialog = SessionDialog {
+        finished: vec![false; fingers.len()],
+        fingers: fingers.clone(),
+        rows,
+        current: extract_widget(&builder, "current_label"),
+        progress: extract_widget(&builder, "progress_label"),
+        stop: extract_widget(&builder, "stop_button"),
+        close: extract_widget(&builder, "close_button"),
+        enrolled: 0,
+        disconnected: false,
+    };
+
+    window.set_transient_for(Some(parent));
+
+    // Fingers are never interrupted mid-scan; stopping takes effect between fingers
+    let stop_requested = Arc::new(AtomicBool::new(false));
+    {
+        let stop_requested = stop_requested.clone();
+        dialog.stop.connect_clicked(move |button| {
+            info!("User asked to stop enrolling after the current finger");
+            stop_requested.store(true, Ordering::SeqCst);
+            button.set_sensitive(false);
+            button.set_label(&gettext("Stopping After This Finger…"));
+        });
+    }
+    {
+        let stop_requested = stop_requested.clone();
+        window.connect_close_request(move |_| {
+            stop_requested.store(true, Ordering::SeqCst);
+            glib::Propagation::Proceed
+        });
+    }
+    {
+        let window = window.clone();
+        dialog.close.connect_clicked(move |_| window.close());
+    }
+
+    let (tx, rx) = mpsc::channel::<SessionEvent>();
+    let timeout = preferences::load().enroll_timeout();
+    spawn_session(&ctx.rt, fingers, timeout, stop_requested, tx);
+
+    let mut on_ended = Some(on_ended);
+    glib::idle_add_local(move || loop {
+        match rx.try_recv() {
+            Ok(SessionEvent::Ended(result)) => {
+                dialog.handle(SessionEvent::Ended(result));
+                ctx.ui.pick.toggle.set_active(false);
+                fingerprint_ui::refresh_fingerprint_display(ctx.clone());
+                if let Some(on_ended) = on_ended.take() {
+                    on_ended();
+                }
+                return glib::ControlFlow::Break;
+            }
+            Ok(event) => dialog.handle(event),
+            Err(TryRecvError::Empty) => return glib::ControlFlow::Continue,
+            Err(TryRecvError::Disconnected) => return glib::ControlFlow::Break,
+        }
+    });
+
+    window.present();
+}
+
+/// Run the session on the runtime, reporting to the dialog through `tx`.
+fn spawn_session(
+    rt: &Runtime,
+    fingers: Vec<String>,
+    timeout: Option<Duration>,
+    stop_requested: Arc<AtomicBool>,
+    tx: mpsc::Sender<SessionEvent>,
+) {
+    rt.spawn(async move {
+        let result = enrollment::enroll_many(
+            &fingers,
+            timeout,
+            |update| {
+                let event = match update {
+                    SessionUpdate::Started(index) => SessionEvent::Started(index),
+                    SessionUpdate::Progress(_, progress) if progress.done => return,
+                    SessionUpdate::Progress(_, progress) => {
+                        SessionEvent::Progress(progress_status(progress))
+                    }
+                    SessionUpdate::Finished(index, result) => {
+                        if matches!(
+                            result,
+                            Ok(EnrollOutcome::Failed(EnrollStatus::Disconnected))
+                        ) {
+                            let _ = tx.send(SessionEvent::ReaderDisconnected);
+                        }
+                        SessionEvent::Finished(index, failure_reason(result))
+                    }
+                };
+                let _ = tx.send(event);
+            },
+            || stop_requested.load(Ordering::SeqCst),
+        )
+        .await;
+
+        let ended = match result {
+            Ok(results) => {
+                let enrolled = results
+                    .iter()
+                    .filter(|(_, result)| matches!(result, Ok(EnrollOutcome::Completed { .. })))
+                    .count();
+                info!(
+                    "Enrollment session finished: {} of {} fingers enrolled",
+                    enrolled,
+                    fingers.len()
+                );
+                Ok(())
+            }
+            Err(DeviceError::NoDeviceAvailable) => {
+                Err(gettext("No fingerprint devices available."))
+            }
+            Err(e) => {
+                warn!("Enrollment session could not start: {}", e);
+                Err(gettext_f(
+                    "Failed to start enrollment: {error}",
+                    &[("error", &e)],
+                ))
+            }
+        };
+        let _ = tx.send(SessionEvent::Ended(ended));
+    });
+}
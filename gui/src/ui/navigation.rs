@@ -3,9 +3,10 @@
 use crate::config;
 use crate::ui::context::AppContext;
 use crate::ui::utils::extract_widget;
+use crate::ui::{fingerprint_ui, log_viewer};
 use gtk4::prelude::*;
 use gtk4::{ApplicationWindow, Builder, Button};
-use log::info;
+use log::debug;
 
 /// Set up navigation buttons and dialogs.
 pub fn setup_navigation_and_dialogs(
@@ -26,7 +27,7 @@ fn setup_navigation_buttons(ctx: &AppContext, builder: &Builder) {
     {
         let stack = ctx.fingerprint_ctx.ui.stack.clone();
         manage_btn.connect_clicked(move |_| {
-            info!("User clicked 'Manage' button - navigating to management page");
+            debug!("User clicked 'Manage' button - navigating to management page");
             stack.set_visible_child_name("manage");
         });
     }
@@ -34,16 +35,17 @@ fn setup_navigation_buttons(ctx: &AppContext, builder: &Builder) {
     {
         let stack = ctx.fingerprint_ctx.ui.stack.clone();
         back_btn.connect_clicked(move |_| {
-            info!("User clicked 'Back' button - returning to main page");
+            debug!("User clicked 'Back' button - returning to main page");
             stack.set_visible_child_name("main");
         });
     }
 
     {
-        let stack = ctx.fingerprint_ctx.ui.stack.clone();
+        let fingerprint_ctx = ctx.fingerprint_ctx.clone();
         button_back.connect_clicked(move |_| {
-            info!("User clicked 'Back' button - returning to management page");
-            stack.set_visible_child_name("manage");
+            debug!("User clicked 'Back' button - returning to management page");
+            fingerprint_ctx.ui.stack.set_visible_child_name("manage");
+            fingerprint_ui::focus_selected_finger(&fingerprint_ctx);
         });
     }
 }
@@ -54,25 +56,32 @@ fn setup_info_button(window: &ApplicationWindow, builder: &Builder) {
 
     let window_clone = window.clone();
     info_btn.connect_clicked(move |_| {
-        info!("User clicked 'About' button - showing info dialog");
+        debug!("User clicked 'About' button - showing info dialog");
         show_info_dialog(&window_clone);
     });
 }
 
-/// Show the info dialog with credits and donation links.
+/// Show the info dialog with credits, donation links and the log viewer.
 fn show_info_dialog(main_window: &ApplicationWindow) {
     let builder = Builder::from_resource(config::resources::dialogs::INFO);
 
     let info_window: gtk4::Window = extract_widget(&builder, "info_window");
 
     let close_button: Button = extract_widget(&builder, "close_button");
+    let logs_button: Button = extract_widget(&builder, "logs_button");
 
     info_window.set_transient_for(Some(main_window));
 
+    let info_window_clone = info_window.clone();
+    logs_button.connect_clicked(move |_| {
+        debug!("User clicked 'View Logs' button - showing log viewer");
+        log_viewer::show_log_viewer(&info_window_clone);
+    });
+
     let info_window_clone = info_window.clone();
     close_button.connect_clicked(move |_| {
         info_window_clone.close();
     });
 
-    info_window.show();
+    info_window.present();
 }
@@ -0,0 +1,112 @@
+//! Desktop notifications for results the user may miss in another window.
+//!
+//! Notifications are only sent while no window of the application has
+//! focus; clicking one brings the main window back on the page the result
+//! belongs to.
+
+use crate::i18n::gettext;
+use crate::ui::context::AppContext;
+use adw::prelude::*;
+use adw::Application;
+use gtk4::{gio, glib, ApplicationWindow};
+use log::{debug, warn};
+
+/// Name of the application action that shows a page of the main window.
+pub const ACTION_NAME: &str = "show-page";
+
+/// Notification ids, so a newer result replaces an older one of its kind.
+const IDS: [&str; 3] = ["enrollment", "pam", "reader"];
+
+/// What a notification is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Topic {
+    /// Enrollment of the finger shown on the finger page.
+    Enrollment,
+    /// A guided session for several fingers, summarised on the manage page.
+    EnrollmentSession,
+    /// A change to the PAM configuration, made on the main page.
+    Pam,
+    /// The reader went away during an operation.
+    Reader,
+}
+
+impl Topic {
+    fn id(self) -> &'static str {
+        match self {
+            Topic::Enrollment | Topic::EnrollmentSession => "enrollment",
+            Topic::Pam => "pam",
+            Topic::Reader => "reader",
+        }
+    }
+
+    /// Stack page that shows the result.
+    fn page(self) -> &'static str {
+        match self {
+            Topic::Enrollment => "finger",
+            Topic::EnrollmentSession | Topic::Reader => "manage",
+            Topic::Pam => "main",
+        }
+    }
+}
+
+/// Register `app.show-page` and withdraw notifications once the user is back.
+pub fn setup_notifications(app: &Application, ctx: &AppContext, window: &ApplicationWindow) {
+    let action = gio::SimpleAction::new(ACTION_NAME, Some(glib::VariantTy::STRING));
+
+    {
+        let stack = ctx.fingerprint_ctx.ui.stack.clone();
+        let window = window.clone();
+        action.connect_activate(move |_, parameter| {
+            let Some(page) = parameter.and_then(|p| p.str()) else {
+                return;
+            };
+            debug!("Showing page '{}' from a notification", page);
+            // The finger page only makes sense while a finger is selected
+            if stack.child_by_name(page).is_some()
+                && (page != "finger" || stack.visible_child_name().as_deref() == Some("finger"))
+            {
+                stack.set_visible_child_name(page);
+            }
+            window.present();
+        });
+    }
+    app.add_action(&action);
+
+    let app = app.clone();
+    window.connect_is_active_notify(move |window| {
+        if window.is_active() {
+            for id in IDS {
+                app.withdraw_notification(id);
+            }
+        }
+    });
+}
+
+/// Tell the user about a result if they are looking at another window.
+pub fn notify(topic: Topic, title: &str, body: &str) {
+    let Some(app) = gio::Application::default().and_downcast::<gtk4::Application>() else {
+        warn!("No application to send a notification from");
+        return;
+    };
+    if app.active_window().is_some_and(|window| window.is_active()) {
+        return;
+    }
+
+    debug!("Sending notification: {}", title);
+    let notification = gio::Notification::new(title);
+    notification.set_body(Some(body));
+    let action = format!("app.{}", ACTION_NAME);
+    let page = topic.page().to_variant();
+    notification.set_default_action_and_target_value(&action, Some(&page));
+    notification.add_button_with_target_value(&gettext("Show"), &action, Some(&page));
+    app.send_notification(Some(topic.id()), &notification);
+}
+
+/// Tell the user the reader was disconnected during an operation.
+pub fn notify_reader_disconnected() {
+    notify(
+        Topic::Reader,
+        &gettext("Fingerprint Reader Disconnected"),
+        &gettext("Reconnect the reader and try again."),
+    );
+}
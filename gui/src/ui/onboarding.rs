@@ -0,0 +1,473 @@
+//! First-run setup.
+//!
+//! A fresh install has no fingerprints, so the PAM switches stay locked. The
+//! setup window checks the system, enrolls at least two fingers, verifies one
+//! and offers the PAM switches, then records in the preferences that it ran.
+
+use crate::config;
+use crate::core::{util, FingerprintContext};
+use crate::fingerprints::verify;
+use crate::i18n::{gettext, gettext_f, ngettext_f};
+use crate::preferences;
+use crate::ui::context::AppContext;
+use crate::ui::fingerprint_ui;
+use crate::ui::hand_diagram::{self, Hand, HandDiagram};
+use crate::ui::multi_enroll;
+use crate::ui::system_status::StatusPanel;
+use crate::ui::theme::{set_tone, Tone};
+use crate::ui::utils::{extract_widget, status_line, StatusLine};
+use adw::prelude::*;
+use gtk4::{glib, AccessibleAnnouncementPriority};
+use gtk4::{ApplicationWindow, Box as GtkBox, Builder, Button, Label, Widget, Window};
+use log::{info, warn};
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::rc::Rc;
+use std::sync::mpsc::{self, TryRecvError};
+use xfprintd_gui_core::device_manager::DeviceError;
+use xfprintd_gui_core::fingerprints::scan_enrolled_fingerprints;
+use xfprintd_gui_core::system::Severity;
+use xfprintd_gui_core::verification::{self, VerifyStatus};
+
+/// Fingers enrolled before the setup can continue.
+const MIN_FINGERS: usize = 2;
+
+/// Fingers ticked when the enrollment step opens.
+const SUGGESTED_FINGERS: [&str; 2] = ["right-index-finger", "left-index-finger"];
+
+/// Show the setup on first run, unless fingerprints are already enrolled.
+pub fn setup_onboarding(ctx: &AppContext, builder: &Builder, window: &ApplicationWindow) {
+    if preferences::load().onboarding_completed {
+        return;
+    }
+
+    let ctx = ctx.fingerprint_ctx.clone();
+    let status_btn: Button = extract_widget(builder, "status_btn");
+    let window = window.clone();
+    scan_enrolled(&ctx.clone(), move |enrolled| {
+        if enrolled.is_empty() {
+            info!("No fingerprints enrolled on first run, starting setup");
+            show_onboarding(&window, ctx, status_btn, enrolled);
+        } else {
+            info!("Fingerprints already enrolled, skipping first-run setup");
+            preferences::update(|prefs| prefs.onboarding_completed = true);
+        }
+    });
+}
+
+/// List the enrolled fingers on the runtime and hand them to `on_scan` on the main thread.
+fn scan_enrolled(ctx: &FingerprintContext, on_scan: impl FnOnce(HashSet<String>) + 'static) {
+    let (tx, rx) = mpsc::channel();
+    ctx.rt.spawn(async move {
+        let _ = tx.send(scan_enrolled_fingerprints().await);
+    });
+
+    let mut on_scan = Some(on_scan);
+    glib::idle_add_local(move || match rx.try_recv() {
+        Ok(enrolled) => {
+            if let Some(on_scan) = on_scan.take() {
+                on_scan(enrolled);
+            }
+            glib::ControlFlow::Break
+        }
+        Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+    });
+}
+
+/// Widgets and state of the open setup window.
+#[derive(Clone)]
+struct Onboarding {
+    ctx: FingerprintContext,
+    window: Window,
+    carousel: adw::Carousel,
+    enroll_page: Widget,
+    enroll_hands: GtkBox,
+    enroll_summary: Label,
+    enroll_button: Button,
+    enroll_continue: Button,
+    verify_page: Widget,
+    verify_status: Label,
+    verify_button: Button,
+    verify_continue: Button,
+    auth_page: Widget,
+    auth_continue: Button,
+    done_page: Widget,
+    finish: Button,
+    enrolled: Rc<RefCell<HashSet<String>>>,
+    picked: Rc<RefCell<Vec<String>>>,
+}
+
+impl Onboarding {
+    /// Scroll to a step and focus its main button.
+    fn go_to(&self, page: &Widget, focus: &Button) {
+        self.carousel.scroll_to(page, true);
+        focus.grab_focus();
+        if let Some(title) = page
+            .downcast_ref::<adw::StatusPage>()
+            .map(|page| page.title())
+        {
+            page.announce(&title, AccessibleAnnouncementPriority::Medium);
+        }
+    }
+
+    /// Rebuild the hands from the enrolled and ticked fingers.
+    fn show_fingers(&self) {
+        while let Some(child) = self.enroll_hands.first_child() {
+            self.enroll_hands.remove(&child);
+        }
+
+        let enrolled = self.enrolled.borrow().clone();
+        for (hand, title) in [
+            (Hand::Left, gettext("Left Hand")),
+            (Hand::Right, gettext("Right Hand")),
+        ] {
+            let diagram = HandDiagram::new(hand, &title, |finger| enrolled.contains(finger));
+            for (finger, button) in &diagram.buttons {
+                let is_enrolled = enrolled.contains(finger);
+                fingerprint_ui::describe_finger_button(button, finger, is_enrolled, true);
+                hand_diagram::set_picked(button, self.picked.borrow().contains(finger));
+
+                if !is_enrolled {
+                    let onboarding = self.clone();
+                    let finger = finger.clone();
+                    button.connect_clicked(move |button| onboarding.toggle_finger(&finger, button));
+                }
+            }
+            self.enroll_hands.append(&diagram.widget);
+        }
+
+        self.update_enroll_step();
+    }
+
+    fn toggle_finger(&self, finger: &str, button: &Button) {
+        let picked = {
+            let mut picked = self.picked.borrow_mut();
+            if let Some(index) = picked.iter().position(|f| f == finger) {
+                picked.remove(index);
+                false
+            } else {
+                picked.push(finger.to_string());
+                true
+            }
+        };
+        hand_diagram::set_picked(button, picked);
+        self.update_enroll_step();
+    }
+
+    /// Show how many fingers are enrolled and what is still needed.
+    fn update_enroll_step(&self) {
+        let enrolled = self.enrolled.borrow().len();
+        let picked = self.picked.borrow().len();
+
+        self.enroll_summary.set_label(&if enrolled >= MIN_FINGERS {
+            ngettext_f(
+                "{count} finger enrolled. You can add more or continue.",
+                "{count} fingers enrolled. You can add more or continue.",
+                enrolled,
+                &[("count", &enrolled)],
+            )
+        } else {
+            let missing = MIN_FINGERS - enrolled;
+            ngettext_f(
+                "Enroll {count} more finger to continue.",
+                "Enroll {count} more fingers to continue.",
+                missing,
+                &[("count", &missing)],
+            )
+        });
+
+        self.enroll_button.set_sensitive(picked > 0);
+        self.enroll_button.set_label(&match picked {
+            0 => gettext("Enroll Selected"),
+            n => ngettext_f(
+                "Enroll {count} Finger",
+                "Enroll {count} Fingers",
+                n,
+                &[("count", &n)],
+            ),
+        });
+
+        let ready = enrolled >= MIN_FINGERS;
+        self.enroll_continue.set_sensitive(ready);
+        if ready {
+            self.enroll_continue.add_css_class("suggested-action");
+            self.enroll_button.remove_css_class("suggested-action");
+        } else {
+            self.enroll_continue.remove_css_class("suggested-action");
+            self.enroll_button.add_css_class("suggested-action");
+        }
+    }
+
+    /// Enroll the ticked fingers, then re-read what is enrolled.
+    fn enroll_picked(&self) {
+        let fingers = self.picked.borrow().clone();
+        if fingers.is_empty() {
+            return;
+        }
+        info!("Setup enrolling {} fingers: {:?}", fingers.len(), fingers);
+
+        let onboarding = self.clone();
+        multi_enroll::show_multi_enroll_dialog(
+            &self.window,
+            self.ctx.clone(),
+            fingers,
+            move || {
+                let inner = onboarding.clone();
+                scan_enrolled(&onboarding.ctx, move |enrolled| {
+                    inner
+                        .picked
+                        .borrow_mut()
+                        .retain(|finger| !enrolled.contains(finger));
+                    *inner.enrolled.borrow_mut() = enrolled;
+                    inner.show_fingers();
+                    if inner.enroll_continue.is_sensitive() {
+                        inner.enroll_continue.grab_focus();
+                    }
+                });
+            },
+        );
+    }
+
+    /// Finger the verification step asks for.
+    fn verify_finger(&self) -> Option<String> {
+        let mut enrolled: Vec<String> = self.enrolled.borrow().iter().cloned().collect();
+        enrolled.sort();
+        SUGGESTED_FINGERS
+            .iter()
+            .map(|finger| finger.to_string())
+            .find(|finger| enrolled.contains(finger))
+            .or_else(|| enrolled.into_iter().next())
+    }
+
+    fn start_verify_step(&self) {
+        let Some(finger) = self.verify_finger() else {
+            return;
+        };
+        self.verify_status
+            .set_markup(&glib::markup_escape_text(&gettext_f(
+                "Finger to scan: {finger}. Press Scan Now, then place it on the reader.",
+                &[("finger", &util::display_finger_name(&finger))],
+            )));
+        set_tone(&self.verify_status, None);
+        self.go_to(&self.verify_page, &self.verify_button);
+    }
+
+    /// Scan the finger and allow continuing once it matches.
+    fn verify(&self) {
+        let Some(finger) = self.verify_finger() else {
+            return;
+        };
+        info!("Setup verifying finger '{}'", finger);
+        self.verify_button.set_sensitive(false);
+        self.show_verify_status(&status_line(
+            Tone::Accent,
+            "🔍",
+            &gettext("Verifying."),
+            &gettext("Place your finger on the scanner…"),
+        ));
+
+        let (tx, rx) = mpsc::channel::<(StatusLine, Option<bool>)>();
+        self.ctx.rt.spawn(async move {
+            let finger_name = util::display_finger_name(&finger);
+            let result = verification::verify(&finger, |progress| {
+                if !progress.done {
+                    let status = verify::progress_status(&progress.status, &finger_name);
+                    let _ = tx.send((status, None));
+                }
+            })
+            .await;
+
+            let message = match result {
+                Ok(status) => (
+                    verify::progress_status(&status, &finger_name),
+                    Some(status == VerifyStatus::Match),
+                ),
+                Err(DeviceError::NoDeviceAvailable) => (
+                    StatusLine::text(Tone::Warning, &gettext("No fingerprint devices available.")),
+                    Some(false),
+                ),
+                Err(e) => {
+                    warn!("Setup verification could not run: {}", e);
+                    (
+                        StatusLine::text(
+                            Tone::Error,
+                            &gettext_f("Failed to start verification: {error}", &[("error", &e)]),
+                        ),
+                        Some(false),
+                    )
+                }
+            };
+            let _ = tx.send(message);
+        });
+
+        let onboarding = self.clone();
+        glib::idle_add_local(move || loop {
+            match rx.try_recv() {
+                Ok((status, finished)) => {
+                    onboarding.show_verify_status(&status);
+                    if let Some(matched) = finished {
+                        onboarding.verify_finished(matched);
+                        return glib::ControlFlow::Break;
+                    }
+                }
+                Err(TryRecvError::Empty) => return glib::ControlFlow::Continue,
+                Err(TryRecvError::Disconnected) => return glib::ControlFlow::Break,
+            }
+        });
+    }
+
+    fn show_verify_status(&self, status: &StatusLine) {
+        status.show(&self.verify_status);
+        self.verify_status.announce(
+            &self.verify_status.text(),
+            AccessibleAnnouncementPriority::Medium,
+        );
+    }
+
+    fn verify_finished(&self, matched: bool) {
+        self.verify_button.set_sensitive(true);
+        if matched {
+            self.verify_continue.set_sensitive(true);
+            self.verify_continue.grab_focus();
+        } else {
+            self.verify_button.set_label(&gettext("Try Again"));
+            self.verify_button.grab_focus();
+        }
+    }
+}
+
+/// Open the setup window over the main window.
+fn show_onboarding(
+    main_window: &ApplicationWindow,
+    ctx: FingerprintContext,
+    status_btn: Button,
+    enrolled: HashSet<String>,
+) {
+    let builder = Builder::from_resource(config::resources::dialogs::ONBOARDING);
+    let window: Window = extract_widget(&builder, "onboarding_window");
+    window.set_transient_for(Some(main_window));
+
+    let check_continue: Button = extract_widget(&builder, "check_continue_button");
+
+    let picked = SUGGESTED_FINGERS
+        .iter()
+        .filter(|finger| !enrolled.contains(**finger))
+        .map(|finger| finger.to_string())
+        .collect();
+    let onboarding = Onboarding {
+        ctx: ctx.clone(),
+        window: window.clone(),
+        carousel: extract_widget(&builder, "carousel"),
+        enroll_page: extract_widget(&builder, "page_enroll"),
+        enroll_hands: extract_widget(&builder, "enroll_hands"),
+        enroll_summary: extract_widget(&builder, "enroll_summary"),
+        enroll_button: extract_widget(&builder, "enroll_button"),
+        enroll_continue: extract_widget(&builder, "enroll_continue_button"),
+        verify_page: extract_widget(&builder, "page_verify"),
+        verify_status: extract_widget(&builder, "verify_status"),
+        verify_button: extract_widget(&builder, "verify_button"),
+        verify_continue: extract_widget(&builder, "verify_continue_button"),
+        auth_page: extract_widget(&builder, "page_auth"),
+        auth_continue: extract_widget(&builder, "auth_continue_button"),
+        done_page: extract_widget(&builder, "page_done"),
+        finish: extract_widget(&builder, "finish_button"),
+        enrolled: Rc::new(RefCell::new(enrolled)),
+        picked: Rc::new(RefCell::new(picked)),
+    };
+
+    // Readiness: errors block the setup, warnings do not
+    {
+        let check_continue = check_continue.clone();
+        let panel = StatusPanel::new(
+            ctx.rt.clone(),
+            extract_widget(&builder, "check_summary"),
+            extract_widget(&builder, "check_list"),
+            extract_widget(&builder, "check_again_button"),
+            status_btn,
+        )
+        .with_report_handler(move |report| {
+            check_continue.set_sensitive(report.severity() != Severity::Error);
+        });
+        panel.refresh();
+    }
+    {
+        let onboarding = onboarding.clone();
+        check_continue.connect_clicked(move |_| {
+            onboarding.show_fingers();
+            onboarding.go_to(&onboarding.enroll_page, &onboarding.enroll_button);
+        });
+    }
+
+    {
+        let onboarding_clone = onboarding.clone();
+        onboarding
+            .enroll_button
+            .connect_clicked(move |_| onboarding_clone.enroll_picked());
+    }
+    {
+        let onboarding_clone = onboarding.clone();
+        onboarding
+            .enroll_continue
+            .connect_clicked(move |_| onboarding_clone.start_verify_step());
+    }
+
+    {
+        let onboarding_clone = onboarding.clone();
+        onboarding
+            .verify_button
+            .connect_clicked(move |_| onboarding_clone.verify());
+    }
+    {
+        let onboarding_clone = onboarding.clone();
+        onboarding.verify_continue.connect_clicked(move |_| {
+            onboarding_clone.go_to(&onboarding_clone.auth_page, &onboarding_clone.auth_continue);
+        });
+    }
+
+    // The rows drive the main window's switches, which apply the PAM change
+    let switches = &ctx.ui.switches;
+    for (row_id, switch) in [
+        ("auth_sudo_row", &switches.term.switch),
+        ("auth_polkit_row", &switches.prompt.switch),
+    ] {
+        let row: adw::SwitchRow = extract_widget(&builder, row_id);
+        switch
+            .bind_property("active", &row, "active")
+            .bidirectional()
+            .sync_create()
+            .build();
+        switch
+            .bind_property("sensitive", &row, "sensitive")
+            .sync_create()
+            .build();
+    }
+    {
+        let onboarding_clone = onboarding.clone();
+        onboarding.auth_continue.connect_clicked(move |_| {
+            onboarding_clone.go_to(&onboarding_clone.done_page, &onboarding_clone.finish);
+        });
+    }
+
+    {
+        let window = window.clone();
+        onboarding.finish.connect_clicked(move |_| window.close());
+    }
+    {
+        let skip: Button = extract_widget(&builder, "skip_button");
+        let window = window.clone();
+        skip.connect_clicked(move |_| {
+            info!("User skipped the first-run setup");
+            window.close();
+        });
+    }
+
+    // Skipping counts as done; the main window offers everything the setup does
+    window.connect_close_request(|_| {
+        info!("First-run setup closed");
+        preferences::update(|prefs| prefs.onboarding_completed = true);
+        glib::Propagation::Proceed
+    });
+
+    window.present();
+}
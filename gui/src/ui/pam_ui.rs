@@ -1,13 +1,45 @@
 //! PAM authentication switches UI functionality.
 
-use crate::pam::{helper::PamHelper, switch as pam_switch};
+use crate::i18n::{gettext, gettext_f};
+use crate::pam::switch as pam_switch;
 use crate::ui::context::AppContext;
-use log::info;
+use crate::ui::dialogs;
+use gtk4::gio;
+use gtk4::glib;
+use gtk4::prelude::*;
+use log::{debug, error, info, warn};
+use std::cell::{Cell, RefCell};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::time::Duration;
+use xfprintd_gui_core::pam::{self, PamHelper, PendingConfig, POLKIT_PATH, SUDO_PATH};
+
+/// Quiet period after the last file change before re-reading, so one save triggers one check.
+const RELOAD_DELAY: Duration = Duration::from_millis(300);
+
+thread_local! {
+    /// Packaged files the user chose not to merge during this session.
+    static DECLINED_MERGES: RefCell<HashSet<PathBuf>> = RefCell::new(HashSet::new());
+    /// Set while a merge offer is on screen.
+    static MERGE_OFFER_OPEN: Cell<bool> = const { Cell::new(false) };
+}
 
 /// Set up PAM authentication switches.
 pub fn setup_pam_switches(ctx: &AppContext) {
     info!("Checking current PAM configurations for switches initialization");
+    load_pam_state(ctx);
+
+    debug!("Temporarily disabling PAM switches until fingerprint enrollment check");
+    ctx.fingerprint_ctx.set_pam_switches_sensitive(false);
 
+    setup_pam_switch_handlers(ctx);
+    watch_pam_files(ctx);
+    offer_pending_merges(ctx);
+}
+
+/// Show the configuration found in the PAM files on the switches.
+fn load_pam_state(ctx: &AppContext) {
     let (sudo_configured, polkit_configured) = PamHelper::check_sudo_and_polkit_configurations();
 
     info!(
@@ -27,21 +59,166 @@ pub fn setup_pam_switches(ctx: &AppContext) {
         }
     );
 
-    ctx.fingerprint_ctx
-        .ui
-        .switches
-        .term
-        .set_active(sudo_configured);
-    ctx.fingerprint_ctx
-        .ui
-        .switches
+    let switches = &ctx.fingerprint_ctx.ui.switches;
+    let (sudo_params, polkit_params) = PamHelper::read_sudo_and_polkit_parameters();
+    switches.term.sync(sudo_configured, sudo_params.as_ref());
+    switches
         .prompt
-        .set_active(polkit_configured);
+        .sync(polkit_configured, polkit_params.as_ref());
 
-    info!("Temporarily disabling PAM switches until fingerprint enrollment check");
-    ctx.fingerprint_ctx.set_pam_switches_sensitive(false);
+    info!("Checking whether the system PAM stack already enables fingerprint authentication");
+    let (sudo_sources, polkit_sources) = PamHelper::inspect_sudo_and_polkit_stacks();
+    switches.term.set_system_sources(&sudo_sources);
+    switches.prompt.set_system_sources(&polkit_sources);
+}
 
-    setup_pam_switch_handlers(ctx);
+/// Re-read the switches whenever a managed PAM file changes on disk.
+///
+/// Admins editing the files or package upgrades replacing them would
+/// otherwise leave the switches showing a stale state.
+fn watch_pam_files(ctx: &AppContext) {
+    let pending: Rc<RefCell<Option<glib::SourceId>>> = Rc::new(RefCell::new(None));
+    let switches = &ctx.fingerprint_ctx.ui.switches;
+
+    for (row, path) in [(&switches.term, SUDO_PATH), (&switches.prompt, POLKIT_PATH)] {
+        let monitor = match gio::File::for_path(path)
+            .monitor_file(gio::FileMonitorFlags::WATCH_MOVES, gio::Cancellable::NONE)
+        {
+            Ok(monitor) => monitor,
+            Err(e) => {
+                warn!("Cannot watch {} for changes: {}", path, e);
+                continue;
+            }
+        };
+
+        let ctx = ctx.clone();
+        let pending = pending.clone();
+        monitor.connect_changed(move |_, _, _, event| {
+            if matches!(
+                event,
+                gio::FileMonitorEvent::Unmounted | gio::FileMonitorEvent::AttributeChanged
+            ) {
+                return;
+            }
+            debug!("{} changed on disk ({:?})", path, event);
+
+            if let Some(source) = pending.borrow_mut().take() {
+                source.remove();
+            }
+
+            let ctx = ctx.clone();
+            let pending_inner = pending.clone();
+            let source = glib::timeout_add_local_once(RELOAD_DELAY, move || {
+                pending_inner.borrow_mut().take();
+                reload_pam_state(&ctx);
+            });
+            *pending.borrow_mut() = Some(source);
+        });
+
+        row.set_monitor(monitor);
+    }
+}
+
+/// Bring the switches in line with the files after an outside change.
+fn reload_pam_state(ctx: &AppContext) {
+    info!("PAM configuration changed on disk, re-reading switch state");
+    load_pam_state(ctx);
+
+    let has_fingerprints = !ctx.fingerprint_ctx.get_enrolled().is_empty();
+    ctx.fingerprint_ctx
+        .set_pam_switches_sensitive(has_fingerprints);
+
+    offer_pending_merges(ctx);
+}
+
+/// Offer to merge a `.pacnew`/`.rpmnew` file left next to a managed PAM file.
+///
+/// Package upgrades do not overwrite a PAM file carrying our block, so the
+/// packaged changes wait beside it until merged.
+fn offer_pending_merges(ctx: &AppContext) {
+    if MERGE_OFFER_OPEN.get() {
+        return;
+    }
+
+    let Some(pending) = pam::pending_configs().into_iter().find(|pending| {
+        !DECLINED_MERGES.with_borrow(|declined| declined.contains(&pending.new_file))
+    }) else {
+        return;
+    };
+    info!(
+        "Package manager left {} next to {}",
+        pending.new_file.display(),
+        pending.target
+    );
+
+    let switches = &ctx.fingerprint_ctx.ui.switches;
+    let row = if pending.target == SUDO_PATH {
+        &switches.term
+    } else {
+        &switches.prompt
+    };
+    let mut message = gettext_f(
+        "A package upgrade installed a new version of {file} as {new_file}. Merging replaces \
+         the file with the new version, so other local edits to it are dropped.",
+        &[
+            ("file", &pending.target),
+            ("new_file", &pending.new_file.display()),
+        ],
+    );
+    if row.switch.is_active() {
+        message.push(' ');
+        message.push_str(&gettext("Fingerprint authentication stays enabled."));
+    }
+
+    MERGE_OFFER_OPEN.set(true);
+    let ctx = ctx.clone();
+    let anchor = row.switch.clone();
+    dialogs::show_confirm_dialog(
+        &row.switch,
+        &gettext("Updated PAM Configuration"),
+        &message,
+        &gettext("Merge"),
+        move |accepted| {
+            MERGE_OFFER_OPEN.set(false);
+            if accepted {
+                merge_pending(&anchor, &pending);
+                load_pam_state(&ctx);
+            } else {
+                info!("User postponed merging {}", pending.new_file.display());
+                DECLINED_MERGES.with_borrow_mut(|declined| {
+                    declined.insert(pending.new_file.clone());
+                });
+            }
+            offer_pending_merges(&ctx);
+        },
+    );
+}
+
+/// Merge a packaged PAM file through the helper and report the outcome.
+fn merge_pending(anchor: &gtk4::Switch, pending: &PendingConfig) {
+    match PamHelper::merge_new_config(pending.target) {
+        Ok(()) => dialogs::show_success_dialog(
+            anchor,
+            &gettext("Configuration Merged"),
+            &gettext_f(
+                "{file} now uses the packaged version.",
+                &[("file", &pending.target)],
+            ),
+            "",
+        ),
+        Err(e) => {
+            error!("Failed to merge {}: {}", pending.new_file.display(), e);
+            DECLINED_MERGES.with_borrow_mut(|declined| {
+                declined.insert(pending.new_file.clone());
+            });
+            dialogs::show_error_dialog(
+                anchor,
+                &gettext("Cannot Merge Configuration"),
+                &gettext_f("{file} was not changed.", &[("file", &pending.target)]),
+                &e.to_string(),
+            );
+        }
+    }
 }
 
 /// Set up PAM switch event handlers using generic implementation.
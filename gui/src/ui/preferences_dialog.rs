@@ -0,0 +1,303 @@
+//! Preferences dialog and applying the stored preferences.
+
+use crate::config;
+use crate::i18n::{gettext, gettext_f, gettext_noop};
+use crate::preferences::{self, Appearance, ColorOverrides, Preferences, WindowSize};
+use crate::ui::context::AppContext;
+use crate::ui::fingerprint_ui;
+use crate::ui::theme::{self, set_tone, Tone};
+use crate::ui::utils::extract_widget;
+use adw::prelude::*;
+use adw::Application;
+use gtk4::{gdk, gio, glib};
+use gtk4::{ApplicationWindow, Builder, Button, ColorDialog, ColorDialogButton, Label, StringList};
+use log::{info, warn};
+use std::cell::Cell;
+use std::rc::Rc;
+use std::sync::mpsc::{self, TryRecvError};
+use xfprintd_gui_core::fprintd;
+
+/// Name of the application action.
+pub const ACTION_NAME: &str = "preferences";
+
+/// A status colour the user can change.
+struct ColorRole {
+    tone: Tone,
+    title: &'static str,
+    subtitle: &'static str,
+    stored: fn(&mut ColorOverrides) -> &mut Option<String>,
+}
+
+static COLOR_ROLES: [ColorRole; 4] = [
+    ColorRole {
+        tone: Tone::Accent,
+        title: gettext_noop("Progress"),
+        subtitle: gettext_noop("Scans in progress and fingers picked for enrollment"),
+        stored: |overrides| &mut overrides.accent,
+    },
+    ColorRole {
+        tone: Tone::Success,
+        title: gettext_noop("Success"),
+        subtitle: gettext_noop("Finished tasks and enrolled fingers"),
+        stored: |overrides| &mut overrides.success,
+    },
+    ColorRole {
+        tone: Tone::Warning,
+        title: gettext_noop("Warning"),
+        subtitle: gettext_noop("Scans to retry and other advice"),
+        stored: |overrides| &mut overrides.warning,
+    },
+    ColorRole {
+        tone: Tone::Error,
+        title: gettext_noop("Error"),
+        subtitle: gettext_noop("Failures"),
+        stored: |overrides| &mut overrides.error,
+    },
+];
+
+/// Apply the style, colours and reader from the stored preferences.
+pub fn apply_preferences(prefs: &Preferences) {
+    apply_appearance(prefs.appearance);
+    theme::apply_color_overrides(&prefs.colors);
+    fprintd::set_preferred_device(prefs.device.clone());
+}
+
+fn apply_appearance(appearance: Appearance) {
+    adw::StyleManager::default().set_color_scheme(match appearance {
+        Appearance::System => adw::ColorScheme::Default,
+        Appearance::Light => adw::ColorScheme::ForceLight,
+        Appearance::Dark => adw::ColorScheme::ForceDark,
+    });
+}
+
+/// Restore the main window's size and remember it when the window closes.
+pub fn persist_window_size(window: &ApplicationWindow, saved: Option<WindowSize>) {
+    if let Some(size) = saved {
+        window.set_default_size(size.width, size.height);
+        if size.maximized {
+            window.maximize();
+        }
+    }
+
+    window.connect_close_request(|window| {
+        let maximized = window.is_maximized();
+        let (width, height) = window.default_size();
+        preferences::update(|prefs| {
+            // A maximized window keeps the size it had before
+            let (width, height) = match (maximized, prefs.window) {
+                (true, Some(previous)) => (previous.width, previous.height),
+                _ => (width, height),
+            };
+            prefs.window = Some(WindowSize {
+                width,
+                height,
+                maximized,
+            });
+        });
+        glib::Propagation::Proceed
+    });
+}
+
+/// Register `app.preferences` and its keyboard shortcut.
+pub fn setup_preferences_action(app: &Application, ctx: &AppContext, window: &ApplicationWindow) {
+    let action = gio::SimpleAction::new(ACTION_NAME, None);
+
+    let ctx = ctx.clone();
+    let window = window.clone();
+    action.connect_activate(move |_, _| {
+        info!("User opened preferences");
+        show_preferences_dialog(&ctx, &window);
+    });
+
+    app.add_action(&action);
+    app.set_accels_for_action(&format!("app.{}", ACTION_NAME), &["<Control>comma"]);
+}
+
+fn show_preferences_dialog(ctx: &AppContext, window: &ApplicationWindow) {
+    let builder = Builder::from_resource(config::resources::dialogs::PREFERENCES);
+    let dialog: adw::PreferencesDialog = extract_widget(&builder, "preferences_dialog");
+    let prefs = preferences::load();
+
+    setup_device_row(ctx, &builder, prefs.device.clone());
+
+    let timeout_row: adw::SpinRow = extract_widget(&builder, "timeout_row");
+    timeout_row.set_value(f64::from(prefs.enroll_timeout));
+    timeout_row.connect_value_notify(|row| {
+        let seconds = row.value() as u32;
+        info!("Enrollment scan timeout set to {} s", seconds);
+        preferences::update(|prefs| prefs.enroll_timeout = seconds);
+    });
+
+    let style_row: adw::ComboRow = extract_widget(&builder, "style_row");
+    style_row.set_selected(match prefs.appearance {
+        Appearance::System => 0,
+        Appearance::Light => 1,
+        Appearance::Dark => 2,
+    });
+    style_row.connect_selected_notify(|row| {
+        let appearance = match row.selected() {
+            1 => Appearance::Light,
+            2 => Appearance::Dark,
+            _ => Appearance::System,
+        };
+        info!("Appearance set to {:?}", appearance);
+        apply_appearance(appearance);
+        preferences::update(|prefs| prefs.appearance = appearance);
+    });
+
+    setup_color_rows(ctx, &builder, &prefs.colors);
+
+    dialog.present(Some(window));
+}
+
+/// Fill the reader list once the connected readers are known.
+fn setup_device_row(ctx: &AppContext, builder: &Builder, saved: Option<String>) {
+    let row: adw::ComboRow = extract_widget(builder, "device_row");
+    let model = StringList::new(&[&gettext("Automatic")]);
+    row.set_model(Some(&model));
+    row.set_sensitive(false);
+
+    let (tx, rx) = mpsc::channel::<Vec<String>>();
+    ctx.fingerprint_ctx.rt.spawn(async move {
+        let names = match fprintd::Client::connect().await {
+            Ok(client) => fprintd::device_names(&client).await,
+            Err(e) => Err(e),
+        };
+        let names = names.unwrap_or_else(|e| {
+            warn!("Cannot list fingerprint readers: {}", e);
+            Vec::new()
+        });
+        let _ = tx.send(names);
+    });
+
+    let ctx = ctx.clone();
+    glib::idle_add_local(move || match rx.try_recv() {
+        Ok(mut names) => {
+            // Keep a chosen reader listed while it is unplugged
+            if let Some(saved) = &saved {
+                if !names.contains(saved) {
+                    names.push(saved.clone());
+                }
+            }
+            for name in &names {
+                model.append(name);
+            }
+            if let Some(index) = saved
+                .as_ref()
+                .and_then(|saved| names.iter().position(|name| name == saved))
+            {
+                row.set_selected(index as u32 + 1);
+            }
+            row.set_sensitive(true);
+
+            let ctx = ctx.clone();
+            row.connect_selected_notify(move |row| {
+                let device = match row.selected() {
+                    0 => None,
+                    index => names.get(index as usize - 1).cloned(),
+                };
+                info!("Preferred reader set to {:?}", device);
+                fprintd::set_preferred_device(device.clone());
+                preferences::update(|prefs| prefs.device = device);
+                // Enrolled fingers are stored per reader
+                fingerprint_ui::refresh_fingerprint_display(ctx.fingerprint_ctx.clone());
+            });
+            glib::ControlFlow::Break
+        }
+        Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+    });
+}
+
+/// Add a colour button per status colour, plus the reset button.
+fn setup_color_rows(ctx: &AppContext, builder: &Builder, saved: &ColorOverrides) {
+    let group: adw::PreferencesGroup = extract_widget(builder, "colors_group");
+    let reset: Button = extract_widget(builder, "reset_colors_button");
+    reset.set_sensitive(*saved != ColorOverrides::default());
+
+    // Set while the buttons are updated to show the colours in use
+    let syncing = Rc::new(Cell::new(false));
+    let mut samples = Vec::new();
+    for role in &COLOR_ROLES {
+        let title = gettext(role.title);
+        let button = ColorDialogButton::new(Some(
+            ColorDialog::builder()
+                .title(gettext_f("{color} Color", &[("color", &title)]))
+                .with_alpha(false)
+                .build(),
+        ));
+        button.set_valign(gtk4::Align::Center);
+
+        // Styled like real status messages, so it shows the colour in use
+        let sample = Label::new(Some(&gettext("Sample")));
+        set_tone(&sample, Some(role.tone));
+
+        let row = adw::ActionRow::builder()
+            .title(&title)
+            .subtitle(gettext(role.subtitle))
+            .activatable_widget(&button)
+            .build();
+        row.add_suffix(&sample);
+        row.add_suffix(&button);
+        group.add(&row);
+
+        let ctx = ctx.clone();
+        let reset = reset.clone();
+        let syncing = syncing.clone();
+        button.connect_rgba_notify(move |button| {
+            if syncing.get() {
+                return;
+            }
+            let color = hex(&button.rgba());
+            info!("Status color {} set to {}", role.tone.css_class(), color);
+            let mut overrides = preferences::load().colors;
+            *(role.stored)(&mut overrides) = Some(color);
+            save_colors(&ctx, overrides);
+            reset.set_sensitive(true);
+        });
+        samples.push((sample, button));
+    }
+
+    // Colours are known once styles apply, after the rows are shown
+    let sync_buttons = Rc::new(move || {
+        syncing.set(true);
+        for (sample, button) in &samples {
+            button.set_rgba(&sample.color());
+        }
+        syncing.set(false);
+    });
+    {
+        let sync_buttons = sync_buttons.clone();
+        group.connect_map(move |_| {
+            let sync_buttons = sync_buttons.clone();
+            glib::idle_add_local_once(move || sync_buttons());
+        });
+    }
+
+    let ctx = ctx.clone();
+    reset.connect_clicked(move |reset| {
+        info!("Status colors reset to the theme's");
+        save_colors(&ctx, ColorOverrides::default());
+        let sync_buttons = sync_buttons.clone();
+        glib::idle_add_local_once(move || sync_buttons());
+        reset.set_sensitive(false);
+    });
+}
+
+/// Store and apply new colour overrides, then redraw what uses them.
+fn save_colors(ctx: &AppContext, overrides: ColorOverrides) {
+    theme::apply_color_overrides(&overrides);
+    preferences::update(|prefs| prefs.colors = overrides);
+    fingerprint_ui::rebuild_finger_picker(&ctx.fingerprint_ctx);
+}
+
+/// `#rrggbb` form of an opaque colour.
+fn hex(rgba: &gdk::RGBA) -> String {
+    let channel = |value: f32| (value.clamp(0.0, 1.0) * 255.0).round() as u8;
+    format!(
+        "#{:02x}{:02x}{:02x}",
+        channel(rgba.red()),
+        channel(rgba.green()),
+        channel(rgba.blue())
+    )
+}
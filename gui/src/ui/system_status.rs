@@ -0,0 +1,267 @@
+//! System status panel with fix-it actions.
+
+use crate::config;
+use crate::i18n::{gettext, ngettext_f};
+use crate::ui::context::AppContext;
+use crate::ui::dialogs;
+use crate::ui::utils::extract_widget;
+use adw::prelude::*;
+use gtk4::glib;
+use gtk4::{Align, ApplicationWindow, Builder, Button, Image, Label, ListBox, Window};
+use log::{info, warn};
+use std::rc::Rc;
+use std::sync::mpsc::{self, TryRecvError};
+use std::sync::Arc;
+use tokio::runtime::Runtime;
+use xfprintd_gui_core::system::{self, Check, Fix, Message, Severity, SystemReport};
+
+/// Set up the header bar status button and run the startup checks.
+pub fn setup_system_status(ctx: &AppContext, builder: &Builder, window: &ApplicationWindow) {
+    let status_btn: Button = extract_widget(builder, "status_btn");
+    let rt = ctx.fingerprint_ctx.rt.clone();
+
+    {
+        let window = window.clone();
+        let rt = rt.clone();
+        let status_btn_clone = status_btn.clone();
+        status_btn.connect_clicked(move |_| {
+            info!("User clicked 'System Status' button - showing status panel");
+            show_system_status_dialog(&window, rt.clone(), &status_btn_clone);
+        });
+    }
+
+    info!("Performing system environment checks");
+    run_diagnosis(&rt, move |report| {
+        report.log();
+        update_status_button(&status_btn, &report);
+    });
+}
+
+/// Run the checks on the runtime and hand the report to `on_report` on the main thread.
+fn run_diagnosis(rt: &Runtime, on_report: impl FnOnce(SystemReport) + 'static) {
+    let (tx, rx) = mpsc::channel();
+    rt.spawn(async move {
+        let _ = tx.send(system::diagnose().await);
+    });
+
+    let mut on_report = Some(on_report);
+    glib::idle_add_local(move || match rx.try_recv() {
+        Ok(report) => {
+            if let Some(on_report) = on_report.take() {
+                on_report(report);
+            }
+            glib::ControlFlow::Break
+        }
+        Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+        Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+    });
+}
+
+/// Highlight the header bar button while problems remain.
+fn update_status_button(button: &Button, report: &SystemReport) {
+    button.remove_css_class("warning");
+    button.remove_css_class("error");
+
+    let problems = report.problems().count();
+    match report.severity() {
+        Severity::Ok => button.set_tooltip_text(Some(&gettext("System Status"))),
+        severity => {
+            button.add_css_class(severity.as_str());
+            button.set_tooltip_text(Some(&ngettext_f(
+                "System Status: {count} problem found",
+                "System Status: {count} problems found",
+                problems,
+                &[("count", &problems)],
+            )));
+        }
+    }
+}
+
+/// Icon shown next to a check.
+fn severity_icon(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Ok => "checkmark",
+        Severity::Warning => "dialog-warning",
+        Severity::Error => "dialog-error",
+    }
+}
+
+/// Called with every report a panel shows.
+type ReportHandler = Rc<dyn Fn(&SystemReport)>;
+
+/// Widgets of an open status panel.
+#[derive(Clone)]
+pub struct StatusPanel {
+    rt: Arc<Runtime>,
+    summary: Label,
+    list: ListBox,
+    recheck: Button,
+    status_btn: Button,
+    on_report: Option<ReportHandler>,
+}
+
+impl StatusPanel {
+    /// Show checks in `list` and an overall summary in `summary`; `recheck`
+    /// runs them again and `status_btn` is the header bar button kept in sync.
+    pub fn new(
+        rt: Arc<Runtime>,
+        summary: Label,
+        list: ListBox,
+        recheck: Button,
+        status_btn: Button,
+    ) -> Self {
+        let panel = Self {
+            rt,
+            summary,
+            list,
+            recheck,
+            status_btn,
+            on_report: None,
+        };
+
+        let panel_clone = panel.clone();
+        panel
+            .recheck
+            .connect_clicked(move |_| panel_clone.refresh());
+        panel
+    }
+
+    /// Also hand every new report to `on_report`.
+    pub fn with_report_handler(mut self, on_report: impl Fn(&SystemReport) + 'static) -> Self {
+        self.on_report = Some(Rc::new(on_report));
+        self
+    }
+
+    /// Re-run every check and rebuild the list.
+    pub fn refresh(&self) {
+        self.summary.set_label(&gettext("Checking your system…"));
+        self.recheck.set_sensitive(false);
+
+        let panel = self.clone();
+        run_diagnosis(&self.rt, move |report| panel.show(&report));
+    }
+
+    /// Fill the list from a report.
+    fn show(&self, report: &SystemReport) {
+        while let Some(child) = self.list.first_child() {
+            self.list.remove(&child);
+        }
+        for check in &report.checks {
+            self.list.append(&self.check_row(check));
+        }
+
+        let problems = report.problems().count();
+        self.summary.set_label(&match problems {
+            0 => gettext("Everything needed for fingerprint authentication is in place."),
+            n => ngettext_f(
+                "{count} problem needs attention.",
+                "{count} problems need attention.",
+                n,
+                &[("count", &n)],
+            ),
+        });
+        self.recheck.set_sensitive(true);
+        update_status_button(&self.status_btn, report);
+        if let Some(on_report) = &self.on_report {
+            on_report(report);
+        }
+    }
+
+    /// Row describing one check, with a fix-it button when one is available.
+    fn check_row(&self, check: &Check) -> adw::ActionRow {
+        let mut subtitle = glib::markup_escape_text(&translate(&check.summary)).to_string();
+        if let Some(hint) = &check.hint {
+            subtitle.push('\n');
+            subtitle.push_str(&glib::markup_escape_text(&translate(hint)));
+        }
+
+        let row = adw::ActionRow::builder()
+            .title(gettext(check.kind.title()))
+            .subtitle(subtitle)
+            .build();
+
+        let icon = Image::from_icon_name(severity_icon(check.severity));
+        icon.set_pixel_size(16);
+        row.add_prefix(&icon);
+
+        if let Some(fix) = check.fix {
+            let button = Button::with_label(&gettext(fix.label()));
+            button.set_valign(Align::Center);
+            button.add_css_class("suggested-action");
+
+            let panel = self.clone();
+            button.connect_clicked(move |button| panel.apply(fix, button));
+            row.add_suffix(&button);
+        }
+
+        row
+    }
+
+    /// Run a fix and check again once it finishes.
+    fn apply(&self, fix: Fix, button: &Button) {
+        info!("User requested fix: {:?}", fix);
+        button.set_sensitive(false);
+
+        let (tx, rx) = mpsc::channel();
+        self.rt.spawn(async move {
+            let _ = tx.send(system::apply_fix(fix).await.map_err(|e| e.to_string()));
+        });
+
+        let panel = self.clone();
+        let button = button.clone();
+        glib::idle_add_local(move || match rx.try_recv() {
+            Ok(result) => {
+                if let Err(e) = result {
+                    warn!("Fix {:?} failed: {}", fix, e);
+                    dialogs::show_error_dialog(
+                        &button,
+                        &gettext("Fix Failed"),
+                        &gettext(fix.failure()),
+                        &e,
+                    );
+                }
+                panel.refresh();
+                glib::ControlFlow::Break
+            }
+            Err(TryRecvError::Empty) => glib::ControlFlow::Continue,
+            Err(TryRecvError::Disconnected) => glib::ControlFlow::Break,
+        });
+    }
+}
+
+/// Translate a message from the core checks.
+fn translate(message: &Message) -> String {
+    message.fill(&gettext(message.msgid))
+}
+
+/// Show the system status panel.
+fn show_system_status_dialog(
+    main_window: &ApplicationWindow,
+    rt: Arc<Runtime>,
+    status_btn: &Button,
+) {
+    let builder = Builder::from_resource(config::resources::dialogs::SYSTEM_STATUS);
+
+    let status_window: Window = extract_widget(&builder, "status_window");
+    let close_button: Button = extract_widget(&builder, "close_button");
+
+    let panel = StatusPanel::new(
+        rt,
+        extract_widget(&builder, "summary_label"),
+        extract_widget(&builder, "checks_list"),
+        extract_widget(&builder, "recheck_button"),
+        status_btn.clone(),
+    );
+
+    status_window.set_transient_for(Some(main_window));
+    // Lets the report button reach the application's actions
+    status_window.set_application(main_window.application().as_ref());
+
+    let status_window_clone = status_window.clone();
+    close_button.connect_clicked(move |_| {
+        status_window_clone.close();
+    });
+
+    panel.refresh();
+    status_window.present();
+}
@@ -0,0 +1,105 @@
+//! Status colours that follow the style, the accent colour and high contrast.
+//!
+//! Status messages carry a [`Tone`] instead of a colour; the tone becomes a
+//! CSS class on the label, and `style.css` maps each class to a
+//! `--status-<class>` variable based on the libadwaita colours. User colour
+//! choices only redefine those variables.
+
+use crate::preferences::ColorOverrides;
+use gtk4::prelude::*;
+use gtk4::{gdk, CssProvider, Widget};
+
+/// Meaning of a status message, shown as a colour.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tone {
+    /// Work in progress, in the accent colour.
+    Accent,
+    Success,
+    Warning,
+    Error,
+    /// Anything else, in a dimmed foreground colour.
+    Neutral,
+}
+
+impl Tone {
+    pub const ALL: [Tone; 5] = [
+        Tone::Accent,
+        Tone::Success,
+        Tone::Warning,
+        Tone::Error,
+        Tone::Neutral,
+    ];
+
+    /// CSS class that colours a widget in this tone.
+    pub fn css_class(self) -> &'static str {
+        match self {
+            Tone::Accent => "accent",
+            Tone::Success => "success",
+            Tone::Warning => "warning",
+            Tone::Error => "error",
+            Tone::Neutral => "neutral",
+        }
+    }
+}
+
+/// Show `widget` as a status in `tone`, or uncoloured for `None`.
+pub fn set_tone(widget: &impl IsA<Widget>, tone: Option<Tone>) {
+    widget.add_css_class("status");
+    for other in Tone::ALL {
+        if Some(other) != tone {
+            widget.remove_css_class(other.css_class());
+        }
+    }
+    if let Some(tone) = tone {
+        widget.add_css_class(tone.css_class());
+    }
+}
+
+thread_local! {
+    /// Redefines the tone variables chosen by the user.
+    static OVERRIDES: CssProvider = CssProvider::new();
+}
+
+/// Register the provider for the user's colours; call once a display exists.
+pub fn init() {
+    let Some(display) = gdk::Display::default() else {
+        return;
+    };
+    OVERRIDES.with(|provider| {
+        // Above the application stylesheet, which defines the defaults
+        gtk4::style_context_add_provider_for_display(
+            &display,
+            provider,
+            gtk4::STYLE_PROVIDER_PRIORITY_APPLICATION + 1,
+        );
+    });
+}
+
+/// Use the user's colours in place of the theme's, where set.
+pub fn apply_color_overrides(overrides: &ColorOverrides) {
+    // Only parsed colours reach the stylesheet
+    let declarations: String = overrides
+        .parsed(|color| gdk::RGBA::parse(color).ok())
+        .into_iter()
+        .map(|(name, rgba)| format!("--status-{}: {}; ", name, rgba))
+        .collect();
+
+    let css = if declarations.is_empty() {
+        String::new()
+    } else {
+        format!(":root {{ {}}}", declarations)
+    };
+    OVERRIDES.with(|provider| provider.load_from_string(&css));
+}
+
+/// Follow changes to the light or dark style, accent colour and contrast.
+pub fn connect_theme_changed(on_changed: impl Fn() + 'static) {
+    let style_manager = adw::StyleManager::default();
+    let on_changed = std::rc::Rc::new(on_changed);
+
+    let callback = on_changed.clone();
+    style_manager.connect_dark_notify(move |_| callback());
+    let callback = on_changed.clone();
+    style_manager.connect_accent_color_notify(move |_| callback());
+    style_manager.connect_high_contrast_notify(move |_| on_changed());
+}
@@ -1,8 +1,9 @@
-//! UI utility functions for widget extraction.
+//! UI utility functions for widget extraction and status messages.
 
+use crate::ui::theme::{set_tone, Tone};
 use gtk4::glib;
 use gtk4::prelude::*;
-use gtk4::Builder;
+use gtk4::{Builder, Label};
 
 /// Helper to extract widgets from builder with consistent error handling.
 pub fn extract_widget<T: IsA<glib::Object>>(builder: &Builder, name: &str) -> T {
@@ -10,3 +11,39 @@ pub fn extract_widget<T: IsA<glib::Object>>(builder: &Builder, name: &str) -> T
         .object(name)
         .unwrap_or_else(|| panic!("Failed to get widget with id '{}'", name))
 }
+
+/// A status message in Pango markup, with the tone its label takes.
+#[derive(Debug, Clone)]
+pub struct StatusLine {
+    pub tone: Tone,
+    pub markup: String,
+}
+
+impl StatusLine {
+    /// A plain message in `tone`.
+    pub fn text(tone: Tone, text: &str) -> Self {
+        Self {
+            tone,
+            markup: glib::markup_escape_text(text).to_string(),
+        }
+    }
+
+    /// Show the message on `label`.
+    pub fn show(&self, label: &Label) {
+        label.set_markup(&self.markup);
+        set_tone(label, Some(self.tone));
+    }
+}
+
+/// Status line: an icon and bold headline followed by advice.
+pub fn status_line(tone: Tone, icon: &str, headline: &str, advice: &str) -> StatusLine {
+    StatusLine {
+        tone,
+        markup: format!(
+            "<b>{} {}</b> {}",
+            icon,
+            glib::markup_escape_text(headline),
+            glib::markup_escape_text(advice)
+        ),
+    }
+}
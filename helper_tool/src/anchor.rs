@@ -0,0 +1,379 @@
+//! Insertion points for fenced configuration blocks.
+//!
+//! Patch files may start with metadata lines of the form `#@ key: value`.
+//! The `anchor` key selects where the block is inserted:
+//!
+//! - `after-header` (default): after the `#%PAM-1.0` header, or after the
+//!   leading comments when the file has no header
+//! - `before-first-auth`: before the first auth rule or `@include`
+//! - `after-module <module> [args...]`: after the first auth rule using the
+//!   module with all the given arguments, falling back to `before-first-auth`
+//! - `end-of-auth`: after the last auth rule or `@include`
+
+use crate::rules::{self, PamLine};
+use crate::PAM_HEADER;
+use std::{io, str::FromStr};
+
+/// Prefix of metadata lines in patch files
+const METADATA_PREFIX: &str = "#@";
+
+/// Where a fenced block is inserted into a PAM file
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub enum Anchor {
+    #[default]
+    AfterHeader,
+    BeforeFirstAuth,
+    AfterModule {
+        module: String,
+        args: Vec<String>,
+    },
+    EndOfAuth,
+}
+
+impl FromStr for Anchor {
+    type Err = io::Error;
+
+    fn from_str(value: &str) -> Result<Self, Self::Err> {
+        let mut tokens = value.split_whitespace();
+
+        match tokens.next() {
+            Some("after-header") => Ok(Anchor::AfterHeader),
+            Some("before-first-auth") => Ok(Anchor::BeforeFirstAuth),
+            Some("end-of-auth") => Ok(Anchor::EndOfAuth),
+            Some("after-module") => {
+                let module = tokens.next().ok_or_else(|| {
+                    io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        "after-module anchor requires a module name",
+                    )
+                })?;
+                Ok(Anchor::AfterModule {
+                    module: module.to_string(),
+                    args: tokens.map(str::to_string).collect(),
+                })
+            }
+            _ => Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("Unknown anchor: '{}'", value.trim()),
+            )),
+        }
+    }
+}
+
+/// A patch file split into its metadata and the block body
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Patch {
+    pub anchor: Anchor,
+    pub body: String,
+}
+
+impl Patch {
+    /// Parses patch content, stripping the metadata lines from the body
+    pub fn parse(content: &str) -> io::Result<Self> {
+        let mut anchor = Anchor::default();
+        let mut body = Vec::new();
+
+        for line in content.lines() {
+            let Some(metadata) = line.trim().strip_prefix(METADATA_PREFIX) else {
+                body.push(line);
+                continue;
+            };
+
+            let (key, value) = metadata.split_once(':').ok_or_else(|| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Malformed patch metadata: '{}'", line.trim()),
+                )
+            })?;
+
+            match key.trim() {
+                "anchor" => anchor = value.parse()?,
+                other => {
+                    return Err(io::Error::new(
+                        io::ErrorKind::InvalidData,
+                        format!("Unknown patch metadata key: '{}'", other),
+                    ))
+                }
+            }
+        }
+
+        Ok(Self {
+            anchor,
+            body: body.join("\n").trim_matches('\n').to_string(),
+        })
+    }
+}
+
+/// A statement spanning one or more physical lines (continuations joined)
+struct Statement {
+    start: usize,
+    end: usize,
+    text: String,
+    parsed: Option<PamLine>,
+}
+
+impl Statement {
+    fn is_auth(&self) -> bool {
+        match &self.parsed {
+            Some(PamLine::Rule(rule)) => rule.kind == "auth",
+            Some(PamLine::AtInclude(_)) => true,
+            None => false,
+        }
+    }
+}
+
+/// Groups physical lines into statements
+fn statements(lines: &[&str]) -> Vec<Statement> {
+    let mut result = Vec::new();
+    let mut start = 0;
+
+    while start < lines.len() {
+        let mut end = start;
+        let mut text = String::new();
+
+        loop {
+            let line = lines[end].trim_end_matches('\r');
+            end += 1;
+            match line.strip_suffix('\\') {
+                Some(stripped) if end < lines.len() => {
+                    text.push_str(stripped);
+                    text.push(' ');
+                }
+                _ => {
+                    text.push_str(line.strip_suffix('\\').unwrap_or(line));
+                    break;
+                }
+            }
+        }
+
+        let text = text.trim().to_string();
+        let parsed = rules::parse_line(&text);
+        result.push(Statement {
+            start,
+            end,
+            text,
+            parsed,
+        });
+        start = end;
+    }
+
+    result
+}
+
+/// Finds the physical line index the block should be inserted before
+fn insertion_index(lines: &[&str], anchor: &Anchor) -> usize {
+    let statements = statements(lines);
+
+    let before_first_auth = || {
+        statements
+            .iter()
+            .find(|s| s.is_auth())
+            .map_or(lines.len(), |s| s.start)
+    };
+
+    match anchor {
+        Anchor::AfterHeader => {
+            let mut index = 0;
+            for statement in statements.iter().take_while(|s| s.parsed.is_none()) {
+                if statement.text == PAM_HEADER {
+                    return statement.end;
+                }
+                // Only skip over comments; stop at the first blank line after them
+                if statement.text.is_empty() {
+                    break;
+                }
+                index = statement.end;
+            }
+            index
+        }
+        Anchor::BeforeFirstAuth => before_first_auth(),
+        Anchor::AfterModule { module, args } => statements
+            .iter()
+            .find(|s| match &s.parsed {
+                Some(PamLine::Rule(rule)) => {
+                    rule.kind == "auth"
+                        && rule.module_name() == module
+                        && args.iter().all(|arg| rule.args.contains(arg))
+                }
+                _ => false,
+            })
+            .map_or_else(before_first_auth, |s| s.end),
+        Anchor::EndOfAuth => statements
+            .iter()
+            .rev()
+            .find(|s| s.is_auth())
+            .map_or(lines.len(), |s| s.end),
+    }
+}
+
+/// Inserts an already fenced block into content at the given anchor
+pub fn insert_block(content: &str, fenced_block: &str, anchor: &Anchor) -> String {
+    let lines: Vec<&str> = content.lines().collect();
+    let mut result = String::with_capacity(content.len() + fenced_block.len() + 32);
+
+    if lines.is_empty() {
+        // No existing content - add PAM header then the block
+        result.push_str(PAM_HEADER);
+        result.push('\n');
+        result.push_str(fenced_block);
+        return result;
+    }
+
+    let index = insertion_index(&lines, anchor);
+
+    for line in &lines[..index] {
+        result.push_str(line);
+        result.push('\n');
+    }
+    result.push_str(fenced_block);
+    for line in &lines[index..] {
+        result.push_str(line);
+        result.push('\n');
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BLOCK: &str =
+        "# BEGIN xfprintd-gui\nauth sufficient pam_fprintd.so\n# END xfprintd-gui\n";
+
+    /// Arch Linux /etc/pam.d/sudo
+    const ARCH_SUDO: &str = "#%PAM-1.0
+auth\t\tinclude\t\tsystem-auth
+account\t\tinclude\t\tsystem-auth
+session\t\tinclude\t\tsystem-auth
+";
+
+    /// Debian /etc/pam.d/sudo
+    const DEBIAN_SUDO: &str = "#%PAM-1.0
+
+# Set up user limits from /etc/security/limits.conf.
+session    required   pam_limits.so
+
+@include common-auth
+@include common-account
+@include common-session-noninteractive
+";
+
+    /// Fedora-style system-auth with faillock
+    const FEDORA_SYSTEM_AUTH: &str = "#%PAM-1.0
+# Generated by authselect
+auth        required      pam_env.so
+auth        required      pam_faillock.so preauth silent deny=4
+auth        sufficient    pam_unix.so try_first_pass nullok
+auth        [default=die] pam_faillock.so authfail
+auth        required      pam_deny.so
+
+account     required      pam_unix.so
+";
+
+    /// A file that starts with a comment instead of the PAM header
+    const COMMENT_FIRST: &str = "# Managed by configuration management
+# Do not edit by hand
+auth       sufficient   pam_rootok.so
+auth       include      system-auth
+";
+
+    fn lines_after_insert(content: &str, anchor: &Anchor) -> Vec<String> {
+        insert_block(content, BLOCK, anchor)
+            .lines()
+            .map(str::to_string)
+            .collect()
+    }
+
+    fn block_start(lines: &[String]) -> usize {
+        lines
+            .iter()
+            .position(|l| l == "# BEGIN xfprintd-gui")
+            .expect("block inserted")
+    }
+
+    #[test]
+    fn after_header_inserts_after_pam_header() {
+        let lines = lines_after_insert(ARCH_SUDO, &Anchor::AfterHeader);
+        assert_eq!(block_start(&lines), 1);
+        assert_eq!(lines[0], "#%PAM-1.0");
+        assert_eq!(lines[4], "auth\t\tinclude\t\tsystem-auth");
+    }
+
+    #[test]
+    fn after_header_skips_leading_comments_without_header() {
+        let lines = lines_after_insert(COMMENT_FIRST, &Anchor::AfterHeader);
+        assert_eq!(block_start(&lines), 2);
+        assert_eq!(lines[1], "# Do not edit by hand");
+    }
+
+    #[test]
+    fn empty_content_gets_header_and_block() {
+        let result = insert_block("", BLOCK, &Anchor::EndOfAuth);
+        assert_eq!(result, format!("#%PAM-1.0\n{}", BLOCK));
+    }
+
+    #[test]
+    fn before_first_auth_handles_debian_includes() {
+        let lines = lines_after_insert(DEBIAN_SUDO, &Anchor::BeforeFirstAuth);
+        let start = block_start(&lines);
+        assert_eq!(lines[start - 1], "");
+        assert_eq!(lines[start + 3], "@include common-auth");
+    }
+
+    #[test]
+    fn after_module_matches_arguments() {
+        let anchor: Anchor = "after-module pam_faillock.so preauth".parse().unwrap();
+        let lines = lines_after_insert(FEDORA_SYSTEM_AUTH, &anchor);
+        let start = block_start(&lines);
+        assert!(lines[start - 1].contains("pam_faillock.so preauth"));
+        assert!(lines[start + 3].contains("pam_unix.so"));
+    }
+
+    #[test]
+    fn after_module_falls_back_to_first_auth() {
+        let anchor: Anchor = "after-module pam_faillock.so preauth".parse().unwrap();
+        let lines = lines_after_insert(COMMENT_FIRST, &anchor);
+        let start = block_start(&lines);
+        assert!(lines[start + 3].contains("pam_rootok.so"));
+    }
+
+    #[test]
+    fn end_of_auth_inserts_before_account_section() {
+        let lines = lines_after_insert(FEDORA_SYSTEM_AUTH, &Anchor::EndOfAuth);
+        let start = block_start(&lines);
+        assert!(lines[start - 1].contains("pam_deny.so"));
+        assert_eq!(lines[start + 3], "");
+    }
+
+    #[test]
+    fn continuation_lines_stay_together() {
+        let content =
+            "#%PAM-1.0\nauth required pam_faillock.so \\\n    preauth\nauth include system-auth\n";
+        let anchor: Anchor = "after-module pam_faillock.so preauth".parse().unwrap();
+        let lines = lines_after_insert(content, &anchor);
+        assert_eq!(block_start(&lines), 3);
+        assert_eq!(lines[2], "    preauth");
+    }
+
+    #[test]
+    fn patch_metadata_is_parsed_and_stripped() {
+        let patch =
+            Patch::parse("#@ anchor: end-of-auth\nauth sufficient pam_fprintd.so\n").unwrap();
+        assert_eq!(patch.anchor, Anchor::EndOfAuth);
+        assert_eq!(patch.body, "auth sufficient pam_fprintd.so");
+    }
+
+    #[test]
+    fn patch_without_metadata_defaults_to_after_header() {
+        let patch = Patch::parse("auth sufficient pam_fprintd.so").unwrap();
+        assert_eq!(patch.anchor, Anchor::AfterHeader);
+    }
+
+    #[test]
+    fn invalid_metadata_is_rejected() {
+        assert!(Patch::parse("#@ anchor: somewhere\n").is_err());
+        assert!(Patch::parse("#@ colour: blue\n").is_err());
+        assert!(Patch::parse("#@ after-module\n").is_err());
+    }
+}
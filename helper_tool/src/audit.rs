@@ -0,0 +1,242 @@
+//! Audit trail of privileged PAM changes.
+//!
+//! Every `apply`, `remove`, `reapply` and `merge` records who asked for it, which file it
+//! touched, SHA-256 digests of the file before and after, and whether it
+//! succeeded. Entries go to syslog (and through it to the journal) under
+//! the `authpriv` facility and are appended as JSON lines to
+//! `/var/log/xfprintd-gui/audit.log`, which the helper never rewrites.
+
+use crate::{root, utc::format_utc};
+use serde::Serialize;
+use sha2::{Digest, Sha256};
+use std::{
+    env,
+    ffi::{CStr, CString},
+    fs,
+    io::{self, BufRead, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::Path,
+    time::SystemTime,
+};
+
+/// Directory holding the audit log
+const AUDIT_DIR: &str = "/var/log/xfprintd-gui";
+
+/// Audit log file name inside `AUDIT_DIR`
+const AUDIT_FILE: &str = "audit.log";
+
+/// Identifier of syslog messages
+const SYSLOG_IDENT: &CStr = c"xfprintd-gui-helper";
+
+/// Modifying helper commands
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Action {
+    Apply,
+    Remove,
+    Reapply,
+    Merge,
+}
+
+impl Action {
+    fn as_str(self) -> &'static str {
+        match self {
+            Action::Apply => "apply",
+            Action::Remove => "remove",
+            Action::Reapply => "reapply",
+            Action::Merge => "merge",
+        }
+    }
+}
+
+/// One recorded change
+#[derive(Debug, Clone, Serialize)]
+pub struct Entry {
+    /// UTC time of the change (RFC 3339)
+    pub time: String,
+    pub action: Action,
+    /// Invoking user, taken from `PKEXEC_UID` when run through pkexec
+    pub uid: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user: Option<String>,
+    pub target: String,
+    /// SHA-256 of the file before and after; `None` when it did not exist
+    /// or the target was rejected
+    pub before: Option<String>,
+    pub after: Option<String>,
+    /// "success" or "error"
+    pub result: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Runs a modifying operation on `target` and records its outcome
+pub fn record<T>(
+    action: Action,
+    target: &str,
+    operation: impl FnOnce() -> io::Result<T>,
+) -> io::Result<T> {
+    // Files outside the allowlist are never read, not even to hash them
+    let path = crate::target_path(target).ok();
+    let before = path.as_deref().and_then(digest);
+    let result = operation();
+    let after = path.as_deref().and_then(digest);
+
+    let uid = invoking_uid();
+    let entry = Entry {
+        time: format_utc(SystemTime::now()),
+        action,
+        uid,
+        user: user_name(uid),
+        target: target.to_string(),
+        before,
+        after,
+        result: if result.is_ok() { "success" } else { "error" }.to_string(),
+        error: result.as_ref().err().map(|e| e.to_string()),
+    };
+
+    if let Err(e) = append(&entry) {
+        eprintln!("Warning: could not write audit log: {}", e);
+    }
+    // The test harness must not write to the host's system log
+    if !root::is_set() {
+        syslog(&entry);
+    }
+
+    result
+}
+
+/// Reads the last `limit` raw entries from the audit log, oldest first
+pub fn tail(limit: Option<usize>) -> io::Result<Vec<String>> {
+    let file = match fs::File::open(audit_path()) {
+        Ok(file) => file,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e),
+    };
+
+    let mut lines = io::BufReader::new(file)
+        .lines()
+        .filter(|line| !matches!(line, Ok(l) if l.trim().is_empty()))
+        .collect::<io::Result<Vec<_>>>()?;
+
+    if let Some(limit) = limit {
+        let skip = lines.len().saturating_sub(limit);
+        lines.drain(..skip);
+    }
+    Ok(lines)
+}
+
+fn audit_path() -> std::path::PathBuf {
+    root::path(AUDIT_DIR).join(AUDIT_FILE)
+}
+
+/// Appends one JSON line; `O_APPEND` keeps concurrent writers from interleaving
+fn append(entry: &Entry) -> io::Result<()> {
+    let dir = root::path(AUDIT_DIR);
+    fs::DirBuilder::new()
+        .recursive(true)
+        .mode(0o755)
+        .create(&dir)?;
+
+    let mut line = serde_json::to_string(entry).map_err(io::Error::other)?;
+    line.push('\n');
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .mode(0o644)
+        .open(dir.join(AUDIT_FILE))?;
+    file.write_all(line.as_bytes())?;
+    file.sync_data()
+}
+
+fn syslog(entry: &Entry) {
+    let mut message = format!(
+        "{} {} by uid {}: {} (before {}, after {})",
+        entry.action.as_str(),
+        entry.target,
+        entry.uid,
+        entry.result,
+        entry.before.as_deref().unwrap_or("absent"),
+        entry.after.as_deref().unwrap_or("absent")
+    );
+    if let Some(error) = &entry.error {
+        message.push_str(": ");
+        message.push_str(error);
+    }
+
+    let Ok(message) = CString::new(message.replace('\0', "")) else {
+        return;
+    };
+    let priority = if entry.error.is_some() {
+        libc::LOG_ERR
+    } else {
+        libc::LOG_NOTICE
+    };
+
+    unsafe {
+        libc::openlog(SYSLOG_IDENT.as_ptr(), libc::LOG_PID, libc::LOG_AUTHPRIV);
+        libc::syslog(priority, c"%s".as_ptr(), message.as_ptr());
+        libc::closelog();
+    }
+}
+
+/// Digest of a file's content, `None` if it cannot be read
+fn digest(path: &Path) -> Option<String> {
+    let data = fs::read(path).ok()?;
+    Some(
+        Sha256::digest(&data)
+            .iter()
+            .map(|byte| format!("{:02x}", byte))
+            .collect(),
+    )
+}
+
+/// The user on whose behalf the helper runs
+fn invoking_uid() -> u32 {
+    env::var("PKEXEC_UID")
+        .ok()
+        .and_then(|uid| uid.parse().ok())
+        .unwrap_or_else(|| unsafe { libc::getuid() })
+}
+
+/// Looks up a login name in the password database
+fn user_name(uid: u32) -> Option<String> {
+    let mut buffer = vec![0 as libc::c_char; 4096];
+    let mut passwd: libc::passwd = unsafe { std::mem::zeroed() };
+    let mut result = std::ptr::null_mut();
+
+    let rc = unsafe {
+        libc::getpwuid_r(
+            uid,
+            &mut passwd,
+            buffer.as_mut_ptr(),
+            buffer.len(),
+            &mut result,
+        )
+    };
+    if rc != 0 || result.is_null() {
+        return None;
+    }
+
+    let name = unsafe { CStr::from_ptr(passwd.pw_name) };
+    Some(name.to_string_lossy().into_owned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn digests_file_content() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("sudo");
+        fs::write(&path, "abc").unwrap();
+
+        assert_eq!(
+            digest(&path).as_deref(),
+            Some("ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad")
+        );
+        assert_eq!(digest(&dir.path().join("missing")), None);
+    }
+}
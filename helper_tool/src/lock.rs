@@ -0,0 +1,79 @@
+//! Exclusive lock serialising privileged modifications.
+//!
+//! Concurrent helper runs (rapid switch toggles, package hooks) would
+//! otherwise read the same file, write it independently and lose one of
+//! the changes. Every modifying command holds this lock while it reads
+//! and rewrites its targets.
+
+use std::{
+    fs,
+    io::{self, ErrorKind},
+    os::unix::{fs::PermissionsExt, io::AsRawFd},
+    thread,
+    time::{Duration, Instant},
+};
+
+/// Directory holding the lock file
+const LOCK_DIR: &str = "/run/xfprintd-gui";
+
+/// Lock file name inside `LOCK_DIR`
+const LOCK_FILE: &str = "helper.lock";
+
+/// How long to wait for a concurrent run to finish
+pub const LOCK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Interval between lock attempts
+const RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Exit code used when the lock cannot be acquired (EX_TEMPFAIL)
+pub const EXIT_LOCK_TIMEOUT: i32 = 75;
+
+/// Held exclusive lock; released when dropped (closing the descriptor drops the flock)
+#[derive(Debug)]
+pub struct HelperLock {
+    _file: fs::File,
+}
+
+impl HelperLock {
+    /// Acquires the helper lock, waiting up to `timeout` for other holders
+    pub fn acquire(timeout: Duration) -> io::Result<Self> {
+        let dir = &crate::root::path(LOCK_DIR);
+        if !dir.is_dir() {
+            fs::create_dir_all(dir)?;
+            fs::set_permissions(dir, fs::Permissions::from_mode(0o755))?;
+        }
+
+        let path = dir.join(LOCK_FILE);
+        let file = fs::OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)?;
+
+        let deadline = Instant::now() + timeout;
+        loop {
+            let result = unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX | libc::LOCK_NB) };
+            if result == 0 {
+                return Ok(Self { _file: file });
+            }
+
+            let err = io::Error::last_os_error();
+            if err.kind() != ErrorKind::WouldBlock {
+                return Err(err);
+            }
+
+            if Instant::now() >= deadline {
+                return Err(io::Error::new(
+                    ErrorKind::TimedOut,
+                    format!(
+                        "Timed out after {}s waiting for lock {}",
+                        timeout.as_secs(),
+                        path.display()
+                    ),
+                ));
+            }
+
+            thread::sleep(RETRY_INTERVAL);
+        }
+    }
+}
@@ -5,17 +5,37 @@
 //!
 //! Patch files are stored in: /opt/xfprintd-gui/patches/<encoded-path>.patch
 //! For example: /opt/xfprintd-gui/patches/etc/pam.d/sudo.patch
+//!
+//! Every modification is recorded in /var/log/xfprintd-gui/audit.log, and the
+//! enabled targets in /var/lib/xfprintd-gui/state.json so `reapply` can
+//! restore them after a package upgrade.
 
 use clap::{Parser, Subcommand};
 use serde::{Deserialize, Serialize};
 use std::{
     fs,
     io::{self, Write},
-    os::unix::fs::PermissionsExt,
+    os::unix::fs::{MetadataExt, PermissionsExt},
     path::{Path, PathBuf},
-    time::{SystemTime, UNIX_EPOCH},
+    time::{Duration, SystemTime, UNIX_EPOCH},
 };
 
+mod anchor;
+mod audit;
+mod lock;
+mod paths;
+mod root;
+mod rules;
+mod stack;
+mod state;
+mod validate;
+mod xattr;
+// Shared with the core crate rather than depending on all of it
+#[path = "../../core/src/utc.rs"]
+mod utc;
+
+use validate::Severity;
+
 /// Markers used to fence the configuration blocks
 const BEGIN_MARK: &str = "# BEGIN xfprintd-gui";
 const END_MARK: &str = "# END xfprintd-gui";
@@ -26,9 +46,29 @@ const PAM_HEADER: &str = "#%PAM-1.0";
 /// Base directory for patches (relative to binary location)
 const PATCHES_BASE_DIR: &str = "/opt/xfprintd-gui/patches";
 
+/// Suffixes package managers give to a new configuration file they did not install over a modified one
+const NEW_CONFIG_SUFFIXES: &[&str] = &[".pacnew", ".rpmnew"];
+
 /// Allowlisted PAM configuration directories
 const ALLOWED_DIRS: &[&str] = &["/etc/pam.d"];
 
+/// Directories a target's 'default' file may be read from
+const DEFAULT_DIRS: &[&str] = &["/usr/lib/pam.d", "/etc/pam.d"];
+
+/// Patch template placeholder for the pam_fprintd `max-tries=` argument
+const MAX_TRIES_PLACEHOLDER: &str = "@MAX_TRIES@";
+
+/// Patch template placeholder for the pam_fprintd `timeout=` argument
+const TIMEOUT_PLACEHOLDER: &str = "@TIMEOUT@";
+
+/// pam_fprintd defaults, used when no value is requested
+const DEFAULT_MAX_TRIES: u32 = 3;
+const DEFAULT_TIMEOUT: u32 = 30;
+
+/// Accepted ranges (pam_fprintd enforces a 10 second minimum timeout)
+const MAX_TRIES_RANGE: std::ops::RangeInclusive<u32> = 1..=10;
+const TIMEOUT_RANGE: std::ops::RangeInclusive<u32> = 10..=300;
+
 /// Target configuration with optional default file fallback
 #[derive(Debug, Clone, Serialize, Deserialize)]
 struct TargetConfig {
@@ -37,6 +77,12 @@ struct TargetConfig {
     /// Optional default file to use if target doesn't exist (e.g., "/usr/lib/pam.d/polkit-1")
     #[serde(skip_serializing_if = "Option::is_none")]
     default: Option<String>,
+    /// Optional fingerprint attempts before falling back to password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    max_tries: Option<u32>,
+    /// Optional seconds to wait for a finger before falling back to password
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    timeout: Option<u32>,
 }
 
 impl TargetConfig {
@@ -44,6 +90,8 @@ impl TargetConfig {
         Self {
             file,
             default: None,
+            max_tries: None,
+            timeout: None,
         }
     }
 
@@ -60,6 +108,20 @@ impl TargetConfig {
     about = "Apply/remove/check PAM config blocks using patch files"
 )]
 struct Cli {
+    /// Relocate all system paths below this directory (test harness only)
+    #[arg(long, global = true, hide = true, value_name = "DIR")]
+    root: Option<PathBuf>,
+
+    /// Seconds to wait for the helper lock instead of the default (test harness only)
+    #[arg(
+        long,
+        global = true,
+        hide = true,
+        requires = "root",
+        value_name = "SECS"
+    )]
+    lock_timeout: Option<u64>,
+
     #[command(subcommand)]
     cmd: Command,
 }
@@ -69,7 +131,13 @@ struct Cli {
 enum Command {
     /// Insert fenced configuration block into specified PAM files
     Apply {
-        /// JSON objects with 'file' and optional 'default' fields
+        /// Fingerprint attempts before falling back to password (1-10, default 3)
+        #[arg(long, value_parser = range_parser(MAX_TRIES_RANGE))]
+        max_tries: Option<u32>,
+        /// Seconds to wait for a finger before falling back to password (10-300, default 30)
+        #[arg(long, value_parser = range_parser(TIMEOUT_RANGE))]
+        timeout: Option<u32>,
+        /// JSON objects with 'file' and optional 'default', 'max_tries' and 'timeout' fields
         /// Example: '{"file":"/etc/pam.d/sudo"}' or '{"file":"/etc/pam.d/polkit-1","default":"/usr/lib/pam.d/polkit-1"}'
         #[arg(required = true)]
         targets: Vec<String>,
@@ -80,33 +148,81 @@ enum Command {
         #[arg(required = true)]
         paths: Vec<String>,
     },
+    /// Run pre-flight safety checks without modifying anything
+    Validate {
+        /// JSON objects with 'file' and optional 'default' fields (same format as apply)
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
+    /// Report where pam_fprintd.so is already active in the resolved PAM stack
+    Inspect {
+        /// JSON objects with 'file' and optional 'default' fields (same format as apply)
+        #[arg(required = true)]
+        targets: Vec<String>,
+    },
     /// Check if configuration is applied to specified PAM files
     Check {
         /// PAM configuration file paths (e.g., /etc/pam.d/sudo)
         #[arg(required = true)]
         paths: Vec<String>,
     },
+    /// Re-apply the configuration to enabled targets that lost their block
+    Reapply,
+    /// Replace PAM files with their .pacnew/.rpmnew version, keeping the fingerprint block
+    Merge {
+        /// PAM configuration file paths (e.g., /etc/pam.d/sudo)
+        #[arg(required = true)]
+        paths: Vec<String>,
+    },
+    /// Print recorded configuration changes as JSON lines, oldest first
+    Log {
+        /// Only print the most recent entries
+        #[arg(long)]
+        limit: Option<usize>,
+    },
 }
 
 /// Converts a file path to its corresponding patch file path
 /// Example: /etc/pam.d/sudo -> /opt/xfprintd-gui/patches/etc/pam.d/sudo.patch
 fn get_patch_path(target_path: &str) -> PathBuf {
     let normalized = target_path.strip_prefix('/').unwrap_or(target_path);
-    PathBuf::from(PATCHES_BASE_DIR)
+    root::path(PATCHES_BASE_DIR)
         .join(normalized)
         .with_extension("patch")
 }
 
-/// Checks if a path is in the allowlist of supported PAM configuration directories
-fn is_allowlisted_path(path: &Path) -> bool {
-    let path_str = match path.to_str() {
-        Some(s) => s,
-        None => return false,
-    };
+/// Allowlisted PAM configuration directories as paths
+fn allowed_dirs() -> Vec<PathBuf> {
+    ALLOWED_DIRS.iter().map(root::path).collect()
+}
+
+/// Directories a default file may be read from, as paths
+fn default_dirs() -> Vec<PathBuf> {
+    DEFAULT_DIRS.iter().map(root::path).collect()
+}
 
-    ALLOWED_DIRS
-        .iter()
-        .any(|allowed| path_str.starts_with(allowed))
+/// Maps a target path into the active root and validates it against the allowlist
+fn target_path(target_path: &str) -> io::Result<PathBuf> {
+    let rooted = root::path(target_path);
+    paths::validate_target_path(&rooted.to_string_lossy(), &allowed_dirs())
+}
+
+/// Ensures a patch exists for the target, so only files we manage can be touched
+fn require_patch(target_path: &str) -> io::Result<()> {
+    let patch_path = get_patch_path(target_path);
+
+    if patch_path.is_file() {
+        Ok(())
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::NotFound,
+            format!(
+                "No patch available for {} (expected {})",
+                target_path,
+                patch_path.display()
+            ),
+        ))
+    }
 }
 
 /// Reads patch file content for the given target path
@@ -126,6 +242,83 @@ fn read_patch_content(target_path: &str) -> io::Result<String> {
     Ok(content.trim_end().to_string())
 }
 
+/// Command line parser accepting the same values as `check_range`
+fn range_parser(range: std::ops::RangeInclusive<u32>) -> clap::builder::RangedI64ValueParser<u32> {
+    clap::value_parser!(u32).range(i64::from(*range.start())..=i64::from(*range.end()))
+}
+
+/// Validates a template parameter against its accepted range
+fn check_range(name: &str, value: u32, range: &std::ops::RangeInclusive<u32>) -> io::Result<u32> {
+    if range.contains(&value) {
+        Ok(value)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "{} must be between {} and {}, got {}",
+                name,
+                range.start(),
+                range.end(),
+                value
+            ),
+        ))
+    }
+}
+
+/// Substitutes pam_fprintd parameters into a patch template
+fn render_patch_template(template: &str, target: &TargetConfig) -> io::Result<String> {
+    let max_tries = check_range(
+        "max-tries",
+        target.max_tries.unwrap_or(DEFAULT_MAX_TRIES),
+        &MAX_TRIES_RANGE,
+    )?;
+    let timeout = check_range(
+        "timeout",
+        target.timeout.unwrap_or(DEFAULT_TIMEOUT),
+        &TIMEOUT_RANGE,
+    )?;
+
+    Ok(template
+        .replace(MAX_TRIES_PLACEHOLDER, &max_tries.to_string())
+        .replace(TIMEOUT_PLACEHOLDER, &timeout.to_string()))
+}
+
+/// Extracts the pam_fprintd `max-tries`/`timeout` arguments from our fenced block
+fn applied_parameters(content: &str) -> (Option<u32>, Option<u32>) {
+    let mut inside_block = false;
+    let mut max_tries = None;
+    let mut timeout = None;
+
+    for line in rules::logical_lines(content) {
+        if line == BEGIN_MARK {
+            inside_block = true;
+            continue;
+        }
+        if line == END_MARK {
+            inside_block = false;
+            continue;
+        }
+        if !inside_block {
+            continue;
+        }
+
+        if let Some(rules::PamLine::Rule(rule)) = rules::parse_line(&line) {
+            if rule.module_name() != stack::FPRINTD_MODULE {
+                continue;
+            }
+            for arg in &rule.args {
+                if let Some(value) = arg.strip_prefix("max-tries=") {
+                    max_tries = value.parse().ok();
+                } else if let Some(value) = arg.strip_prefix("timeout=") {
+                    timeout = value.parse().ok();
+                }
+            }
+        }
+    }
+
+    (max_tries, timeout)
+}
+
 /// Creates a fenced configuration block with begin/end markers
 fn create_fenced_block(content: &str) -> String {
     format!("{}\n{}\n{}\n", BEGIN_MARK, content, END_MARK)
@@ -140,68 +333,109 @@ fn read_file_or_default(path: &Path, default: &str) -> io::Result<String> {
     }
 }
 
-/// Removes any existing fenced blocks from the content
-fn remove_fenced_blocks(content: &str) -> String {
+/// Checks whether the content contains a fenced block marker
+fn has_fenced_block(content: &str) -> bool {
+    content
+        .lines()
+        .any(|line| matches!(line.trim(), BEGIN_MARK | END_MARK))
+}
+
+/// Removes any existing fenced blocks from the content.
+///
+/// An unterminated block is an error rather than a reason to drop the
+/// rest of the file.
+fn remove_fenced_blocks(content: &str) -> io::Result<String> {
     let mut result = String::with_capacity(content.len());
-    let mut inside_block = false;
+    let mut block_start = None;
 
-    for line in content.lines() {
+    for (number, line) in content.lines().enumerate() {
         let trimmed = line.trim();
 
         if trimmed == BEGIN_MARK {
-            inside_block = true;
+            if let Some(start) = block_start {
+                return Err(unterminated_block(start));
+            }
+            block_start = Some(number + 1);
             continue;
         }
 
         if trimmed == END_MARK {
-            inside_block = false;
+            if block_start.take().is_none() {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!(
+                        "'{}' on line {} has no matching begin",
+                        END_MARK,
+                        number + 1
+                    ),
+                ));
+            }
             continue;
         }
 
-        if !inside_block {
+        if block_start.is_none() {
             result.push_str(line);
             result.push('\n');
         }
     }
 
-    result
+    match block_start {
+        Some(start) => Err(unterminated_block(start)),
+        None => Ok(result),
+    }
+}
+
+fn unterminated_block(line: usize) -> io::Error {
+    io::Error::new(
+        io::ErrorKind::InvalidData,
+        format!(
+            "'{}' on line {} is not closed by '{}'",
+            BEGIN_MARK, line, END_MARK
+        ),
+    )
 }
 
-/// Inserts a configuration block after the first line (typically the PAM header)
-fn insert_block_after_header(mut base_content: String, block: &str) -> String {
-    // Ensure content ends with newline for predictable processing
-    if !base_content.ends_with('\n') {
-        base_content.push('\n');
+/// Resolves the file that is actually replaced when the target is a symlink.
+///
+/// Symlinked targets are written through only if the resolved file is
+/// still allowlisted; renaming over the link itself would silently turn
+/// it into a regular file.
+fn resolve_write_target(path: &Path) -> io::Result<PathBuf> {
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|meta| meta.file_type().is_symlink())
+        .unwrap_or(false);
+
+    if !is_symlink {
+        return Ok(path.to_path_buf());
     }
 
-    let lines: Vec<&str> = base_content.lines().collect();
-    let fenced_block = create_fenced_block(block);
-    let mut result = String::with_capacity(base_content.len() + fenced_block.len() + 32);
+    let resolved = fs::canonicalize(path).map_err(|e| {
+        io::Error::new(
+            e.kind(),
+            format!("Cannot resolve symlink {}: {}", path.display(), e),
+        )
+    })?;
 
-    if lines.is_empty() {
-        // No existing content - add PAM header then the block
-        result.push_str(PAM_HEADER);
-        result.push('\n');
-        result.push_str(&fenced_block);
-    } else {
-        // Preserve first line (usually PAM header), insert block, then remaining lines
-        result.push_str(lines[0]);
-        result.push('\n');
-        result.push_str(&fenced_block);
-
-        if lines.len() > 1 {
-            for line in &lines[1..] {
-                result.push_str(line);
-                result.push('\n');
-            }
-        }
+    if !paths::is_within_allowed(&resolved, &allowed_dirs()) {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            format!(
+                "Refusing to write through symlink {} -> {}: target is not allowlisted",
+                path.display(),
+                resolved.display()
+            ),
+        ));
     }
 
-    result
+    Ok(resolved)
 }
 
-/// Atomically writes data to a file using a temporary file and rename
+/// Atomically writes data to a file using a temporary file and rename.
+///
+/// Mode, ownership and extended attributes (including SELinux labels) of
+/// an existing file are carried over to the replacement.
 fn atomic_write(path: &Path, data: &[u8]) -> io::Result<()> {
+    let path = &resolve_write_target(path)?;
     let parent = path.parent().ok_or_else(|| {
         io::Error::new(io::ErrorKind::InvalidInput, "Path has no parent directory")
     })?;
@@ -219,27 +453,37 @@ fn atomic_write(path: &Path, data: &[u8]) -> io::Result<()> {
     let temp_name = format!(".{}.{}-{}.tmp", file_name, pid, timestamp);
     let temp_path = parent.join(temp_name);
 
-    // Preserve existing file permissions or use default 0644
-    let mode = if path.exists() {
-        fs::metadata(path)?.permissions().mode()
-    } else {
-        0o644
+    // Metadata of the file being replaced, if any
+    let original = match fs::metadata(path) {
+        Ok(meta) => Some(meta),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e),
     };
 
-    // Write to temporary file
-    {
+    let result = (|| {
         let mut file = fs::OpenOptions::new()
-            .create(true)
+            .create_new(true)
             .write(true)
-            .truncate(true)
             .open(&temp_path)?;
         file.write_all(data)?;
+
+        match &original {
+            Some(meta) => {
+                file.set_permissions(fs::Permissions::from_mode(meta.permissions().mode()))?;
+                std::os::unix::fs::fchown(&file, Some(meta.uid()), Some(meta.gid()))?;
+                xattr::copy_all(path, &file)?;
+            }
+            None => file.set_permissions(fs::Permissions::from_mode(0o644))?,
+        }
+
         file.sync_all()?;
-    }
+        fs::rename(&temp_path, path)
+    })();
 
-    // Set permissions and atomically replace
-    fs::set_permissions(&temp_path, fs::Permissions::from_mode(mode))?;
-    fs::rename(&temp_path, path)?;
+    if let Err(e) = result {
+        let _ = fs::remove_file(&temp_path);
+        return Err(e);
+    }
 
     // Sync directory for durability
     if let Ok(dir) = fs::File::open(parent) {
@@ -249,24 +493,16 @@ fn atomic_write(path: &Path, data: &[u8]) -> io::Result<()> {
     Ok(())
 }
 
-/// Applies configuration to the specified target
-fn apply_config(target: &TargetConfig) -> io::Result<()> {
-    let path = Path::new(&target.file);
-
-    if !is_allowlisted_path(path) {
-        return Err(io::Error::new(
-            io::ErrorKind::PermissionDenied,
-            format!("Target path is not allowlisted: {}", target.file),
-        ));
-    }
-
-    // Read the patch content
-    let patch_content = read_patch_content(&target.file)?;
+/// Renders the content the target file would have after applying the configuration
+fn render_config(target: &TargetConfig) -> io::Result<String> {
+    let path = &target_path(&target.file)?;
+    require_patch(&target.file)?;
 
     // Use default file if specified and target doesn't exist
     let base_content = if !path.exists() {
         if let Some(default_path) = &target.default {
-            let default = Path::new(default_path);
+            let rooted = root::path(default_path);
+            let default = &paths::validate_target_path(&rooted.to_string_lossy(), &default_dirs())?;
             if default.is_file() {
                 fs::read_to_string(default)?
             } else {
@@ -279,42 +515,133 @@ fn apply_config(target: &TargetConfig) -> io::Result<()> {
         read_file_or_default(path, PAM_HEADER)?
     };
 
+    render_on(target, &base_content)
+}
+
+/// Inserts the rendered block for `target` into `base_content`
+fn render_on(target: &TargetConfig, base_content: &str) -> io::Result<String> {
+    // Read the patch template and fill in the pam_fprintd parameters
+    let patch = anchor::Patch::parse(&read_patch_content(&target.file)?)?;
+    let patch_content = render_patch_template(&patch.body, target)?;
+
     // Remove any existing blocks and insert the new one
-    let cleaned_content = remove_fenced_blocks(&base_content);
-    let final_content = insert_block_after_header(cleaned_content, &patch_content);
+    let cleaned_content = remove_fenced_blocks(base_content)?;
+    Ok(anchor::insert_block(
+        &cleaned_content,
+        &create_fenced_block(&patch_content),
+        &patch.anchor,
+    ))
+}
 
-    atomic_write(path, final_content.as_bytes())
+/// Applies configuration to the specified target
+fn apply_config(target: &TargetConfig) -> io::Result<()> {
+    let final_content = render_config(target)?;
+    atomic_write(&target_path(&target.file)?, final_content.as_bytes())
 }
 
 /// Removes configuration from the specified target path
 fn remove_config(target_path: &str) -> io::Result<()> {
-    let path = Path::new(target_path);
+    let path = &self::target_path(target_path)?;
+    require_patch(target_path)?;
 
-    if !path.exists() || !is_allowlisted_path(path) {
+    if !path.exists() {
         return Ok(()); // Nothing to do
     }
 
+    // Only write if there is a block to remove, so untouched files keep their line endings
     let original_content = fs::read_to_string(path)?;
-    let cleaned_content = remove_fenced_blocks(&original_content);
-
-    // Only write if content changed
-    if cleaned_content != original_content {
+    if has_fenced_block(&original_content) {
+        let cleaned_content = remove_fenced_blocks(&original_content)?;
         atomic_write(path, cleaned_content.as_bytes())?;
     }
 
     Ok(())
 }
 
-/// Checks if configuration is applied to the specified target path
-fn is_config_applied(target_path: &str) -> io::Result<bool> {
-    let path = Path::new(target_path);
+/// Re-applies a recorded target whose block is missing, returning whether it was
+fn reapply_config(target: &TargetConfig) -> io::Result<bool> {
+    if applied_config(&target.file)?.is_some() {
+        return Ok(false);
+    }
+
+    audit::record(audit::Action::Reapply, &target.file, || {
+        apply_config(target)
+    })?;
+    Ok(true)
+}
+
+/// Finds the new configuration file a package manager left next to the target
+fn new_config_path(path: &Path) -> io::Result<PathBuf> {
+    for suffix in NEW_CONFIG_SUFFIXES {
+        let mut candidate = path.as_os_str().to_owned();
+        candidate.push(suffix);
+        let candidate = PathBuf::from(candidate);
+
+        match fs::symlink_metadata(&candidate) {
+            Ok(meta) if meta.file_type().is_file() => return Ok(candidate),
+            Ok(_) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("{} is not a regular file", candidate.display()),
+                ))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e),
+        }
+    }
+
+    Err(io::Error::new(
+        io::ErrorKind::NotFound,
+        format!("No .pacnew or .rpmnew file for {}", path.display()),
+    ))
+}
+
+/// Replaces the target with the packaged version, keeping the fingerprint block if enabled.
+/// Returns the adopted file.
+fn merge_config(target_path: &str, state: &state::State) -> io::Result<PathBuf> {
+    let path = &self::target_path(target_path)?;
+    require_patch(target_path)?;
+
+    let new_path = new_config_path(path)?;
+    let new_content = fs::read_to_string(&new_path)?;
+
+    // Enabled if the current file has the block or the user enabled it before an upgrade dropped it
+    let recorded = state.target(target_path).cloned();
+    let target = match applied_config(target_path)? {
+        Some((max_tries, timeout)) => {
+            let mut target = recorded.unwrap_or_else(|| TargetConfig::new(target_path.to_string()));
+            target.max_tries = max_tries.or(target.max_tries);
+            target.timeout = timeout.or(target.timeout);
+            Some(target)
+        }
+        None => recorded,
+    };
+
+    let merged = match target {
+        Some(target) => render_on(&target, &new_content)?,
+        None => new_content,
+    };
+
+    atomic_write(path, merged.as_bytes())?;
+    fs::remove_file(&new_path)?;
+    Ok(new_path)
+}
+
+/// Checks if configuration is applied to the specified target path,
+/// returning the applied pam_fprintd parameters if it is
+fn applied_config(target_path: &str) -> io::Result<Option<(Option<u32>, Option<u32>)>> {
+    let path = &root::path(target_path);
 
     if !path.exists() {
-        return Ok(false);
+        return Ok(None);
     }
 
     let content = fs::read_to_string(path)?;
-    Ok(content.contains(BEGIN_MARK))
+    if content.contains(BEGIN_MARK) {
+        Ok(Some(applied_parameters(&content)))
+    } else {
+        Ok(None)
+    }
 }
 
 /// Checks if the current process is running as root
@@ -322,34 +649,79 @@ fn is_root() -> bool {
     unsafe { libc::geteuid() == 0 }
 }
 
-/// Requires root privileges for the operation, exits with error code 126 if not root
+/// Requires root privileges for the operation, exits with error code 126 if not root.
+/// A relocated root (test harness) only touches the caller's own files.
 fn require_root() {
-    if !is_root() {
+    if !is_root() && !root::is_set() {
         eprintln!("Permission denied: must be run as root (via pkexec)");
         std::process::exit(126);
     }
 }
 
+/// Takes the helper lock for a modifying command, exiting with a distinct code on timeout
+fn lock_or_exit(timeout: Duration) -> lock::HelperLock {
+    match lock::HelperLock::acquire(timeout) {
+        Ok(lock) => lock,
+        Err(e) if e.kind() == io::ErrorKind::TimedOut => {
+            eprintln!(
+                "{}",
+                serde_json::json!({ "error": "lock-timeout", "message": e.to_string() })
+            );
+            std::process::exit(lock::EXIT_LOCK_TIMEOUT);
+        }
+        Err(e) => {
+            eprintln!("Error acquiring helper lock: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Parses a target argument as JSON, falling back to a plain file path
+fn parse_target(target_str: &str) -> TargetConfig {
+    match TargetConfig::from_json(target_str) {
+        Ok(t) => t,
+        Err(_) => {
+            // If JSON parsing fails, treat as simple file path for backwards compatibility
+            TargetConfig::new(target_str.to_string())
+        }
+    }
+}
+
 fn main() {
     let cli = Cli::parse();
 
+    if let Some(root_dir) = cli.root {
+        if let Err(e) = root::set(root_dir) {
+            eprintln!("Error: {}", e);
+            std::process::exit(2);
+        }
+    }
+    let lock_timeout = cli
+        .lock_timeout
+        .map_or(lock::LOCK_TIMEOUT, Duration::from_secs);
+
     match cli.cmd {
-        Command::Apply { targets } => {
+        Command::Apply {
+            max_tries,
+            timeout,
+            targets,
+        } => {
             require_root();
+            // Targets are read and written while holding the lock
+            let _lock = lock_or_exit(lock_timeout);
             let mut errors = Vec::new();
 
             for target_str in &targets {
-                // Try to parse as JSON first
-                let target = match TargetConfig::from_json(target_str) {
-                    Ok(t) => t,
-                    Err(_) => {
-                        // If JSON parsing fails, treat as simple file path for backwards compatibility
-                        TargetConfig::new(target_str.clone())
+                // Per-target JSON values take precedence over the command line options
+                let mut target = parse_target(target_str);
+                target.max_tries = target.max_tries.or(max_tries);
+                target.timeout = target.timeout.or(timeout);
+
+                match audit::record(audit::Action::Apply, &target.file, || apply_config(&target)) {
+                    Ok(()) => {
+                        state::update(|state| state.enable(&target));
+                        println!("Success: applied configuration to {}", target.file)
                     }
-                };
-
-                match apply_config(&target) {
-                    Ok(()) => println!("Success: applied configuration to {}", target.file),
                     Err(e) => {
                         let error =
                             format!("Error applying configuration to {}: {}", target.file, e);
@@ -366,11 +738,15 @@ fn main() {
 
         Command::Remove { paths } => {
             require_root();
+            let _lock = lock_or_exit(lock_timeout);
             let mut errors = Vec::new();
 
             for path in &paths {
-                match remove_config(path) {
-                    Ok(()) => println!("Success: removed configuration from {}", path),
+                match audit::record(audit::Action::Remove, path, || remove_config(path)) {
+                    Ok(()) => {
+                        state::update(|state| state.disable(path));
+                        println!("Success: removed configuration from {}", path)
+                    }
                     Err(e) => {
                         let error = format!("Error removing configuration from {}: {}", path, e);
                         eprintln!("{}", error);
@@ -384,15 +760,121 @@ fn main() {
             }
         }
 
+        Command::Reapply => {
+            require_root();
+            let _lock = lock_or_exit(lock_timeout);
+
+            let state = match state::State::load() {
+                Ok(state) => state,
+                Err(e) => {
+                    eprintln!("Error reading state: {}", e);
+                    std::process::exit(1);
+                }
+            };
+
+            let mut failed = false;
+            for target in &state.targets {
+                match reapply_config(target) {
+                    Ok(true) => println!("reapplied: {}", target.file),
+                    Ok(false) => println!("present: {}", target.file),
+                    Err(e) => {
+                        eprintln!("Error reapplying configuration to {}: {}", target.file, e);
+                        failed = true;
+                    }
+                }
+            }
+
+            if failed {
+                std::process::exit(1);
+            }
+        }
+
+        Command::Merge { paths } => {
+            require_root();
+            let _lock = lock_or_exit(lock_timeout);
+            let state = state::State::load().unwrap_or_default();
+            let mut failed = false;
+
+            for path in &paths {
+                match audit::record(audit::Action::Merge, path, || merge_config(path, &state)) {
+                    Ok(adopted) => println!("merged: {} from {}", path, adopted.display()),
+                    Err(e) => {
+                        eprintln!("Error merging {}: {}", path, e);
+                        failed = true;
+                    }
+                }
+            }
+
+            if failed {
+                std::process::exit(1);
+            }
+        }
+
+        Command::Validate { targets } => {
+            let mut has_errors = false;
+
+            for target_str in &targets {
+                let target = parse_target(target_str);
+                let findings = validate::validate_target(&target);
+
+                if findings.is_empty() {
+                    println!("ok: {}", target.file);
+                }
+
+                for finding in &findings {
+                    match finding.severity {
+                        Severity::Warning => {
+                            println!("warning: {}: {}", target.file, finding.message)
+                        }
+                        Severity::Error => {
+                            has_errors = true;
+                            println!("error: {}: {}", target.file, finding.message);
+                        }
+                    }
+                }
+            }
+
+            std::process::exit(if has_errors { 1 } else { 0 });
+        }
+
+        Command::Inspect { targets } => {
+            for target_str in &targets {
+                let target = parse_target(target_str);
+                let default = target.default.as_deref().map(root::path);
+
+                match stack::auth_stack_for_target(&root::path(&target.file), default.as_deref()) {
+                    Ok(entries) => {
+                        let sources = stack::system_fprintd_sources(&entries);
+                        if sources.is_empty() {
+                            println!("not-enabled: {}", target.file);
+                        }
+                        for source in sources {
+                            println!("system-enabled: {} via {}", target.file, source.display());
+                        }
+                    }
+                    Err(e) => {
+                        eprintln!("Error inspecting {}: {}", target.file, e);
+                        std::process::exit(2);
+                    }
+                }
+            }
+        }
+
         Command::Check { paths } => {
             let mut all_applied = true;
 
             for path in &paths {
-                match is_config_applied(path) {
-                    Ok(true) => {
+                match applied_config(path) {
+                    Ok(Some((max_tries, timeout))) => {
                         println!("applied: {}", path);
+                        if let (Some(max_tries), Some(timeout)) = (max_tries, timeout) {
+                            println!(
+                                "params: {} max-tries={} timeout={}",
+                                path, max_tries, timeout
+                            );
+                        }
                     }
-                    Ok(false) => {
+                    Ok(None) => {
                         println!("not-applied: {}", path);
                         all_applied = false;
                     }
@@ -405,5 +887,17 @@ fn main() {
 
             std::process::exit(if all_applied { 0 } else { 1 });
         }
+
+        Command::Log { limit } => match audit::tail(limit) {
+            Ok(entries) => {
+                for entry in entries {
+                    println!("{}", entry);
+                }
+            }
+            Err(e) => {
+                eprintln!("Error reading audit log: {}", e);
+                std::process::exit(2);
+            }
+        },
     }
 }
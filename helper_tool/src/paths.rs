@@ -0,0 +1,228 @@
+//! Validation of target paths handed to the privileged helper.
+//!
+//! Paths are compared component by component against the allowlisted
+//! directories instead of by string prefix, and symlinks are resolved so
+//! that a link inside an allowlisted directory cannot redirect a write to
+//! an arbitrary file.
+
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+fn rejected(kind: io::ErrorKind, path: &str, reason: &str) -> io::Error {
+    io::Error::new(kind, format!("Rejected target path {:?}: {}", path, reason))
+}
+
+/// Checks whether a canonical path lives directly inside one of the (canonicalized) allowed directories
+pub fn is_within_allowed(canonical: &Path, allowed_dirs: &[PathBuf]) -> bool {
+    let Some(parent) = canonical.parent() else {
+        return false;
+    };
+
+    allowed_dirs.iter().any(|dir| {
+        fs::canonicalize(dir)
+            .map(|dir| dir == parent)
+            .unwrap_or(false)
+    })
+}
+
+/// Validates a raw target path against the allowlisted directories.
+///
+/// Rejects NUL bytes, relative paths, `.`/`..` segments, files outside
+/// (or nested below) the allowlisted directories and symlinks whose
+/// target escapes them.
+pub fn validate_target_path(raw: &str, allowed_dirs: &[PathBuf]) -> io::Result<PathBuf> {
+    if raw.contains('\0') {
+        return Err(rejected(
+            io::ErrorKind::InvalidInput,
+            raw,
+            "contains a NUL byte",
+        ));
+    }
+
+    let path = Path::new(raw);
+    if !path.is_absolute() {
+        return Err(rejected(
+            io::ErrorKind::InvalidInput,
+            raw,
+            "must be absolute",
+        ));
+    }
+
+    // Path::components() silently drops interior "." segments, so check the raw string
+    if raw
+        .split('/')
+        .any(|segment| segment == "." || segment == "..")
+    {
+        return Err(rejected(
+            io::ErrorKind::PermissionDenied,
+            raw,
+            "must not contain '.' or '..' segments",
+        ));
+    }
+
+    let in_allowed_dir = path.file_name().is_some()
+        && path
+            .parent()
+            .is_some_and(|parent| allowed_dirs.iter().any(|dir| dir.as_path() == parent));
+    if !in_allowed_dir {
+        return Err(rejected(
+            io::ErrorKind::PermissionDenied,
+            raw,
+            "not inside an allowlisted directory",
+        ));
+    }
+
+    let is_symlink = fs::symlink_metadata(path)
+        .map(|meta| meta.file_type().is_symlink())
+        .unwrap_or(false);
+    if is_symlink {
+        let resolved = fs::canonicalize(path).map_err(|e| {
+            rejected(
+                io::ErrorKind::PermissionDenied,
+                raw,
+                &format!("symlink cannot be resolved ({})", e),
+            )
+        })?;
+
+        if !is_within_allowed(&resolved, allowed_dirs) {
+            return Err(rejected(
+                io::ErrorKind::PermissionDenied,
+                raw,
+                &format!(
+                    "symlink points outside the allowlisted directories ({})",
+                    resolved.display()
+                ),
+            ));
+        }
+    }
+
+    Ok(path.to_path_buf())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    struct Fixture {
+        _temp: tempfile::TempDir,
+        allowed: PathBuf,
+        outside: PathBuf,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let temp = tempfile::tempdir().unwrap();
+            let allowed = temp.path().join("pam.d");
+            let outside = temp.path().join("secret");
+            fs::create_dir(&allowed).unwrap();
+            fs::write(&outside, "root:x:0:0\n").unwrap();
+            fs::write(allowed.join("sudo"), "#%PAM-1.0\n").unwrap();
+            Self {
+                _temp: temp,
+                allowed,
+                outside,
+            }
+        }
+
+        fn allowed_dirs(&self) -> Vec<PathBuf> {
+            vec![self.allowed.clone()]
+        }
+
+        fn path(&self, name: &str) -> String {
+            format!("{}/{}", self.allowed.display(), name)
+        }
+
+        fn check(&self, raw: &str) -> io::Result<PathBuf> {
+            validate_target_path(raw, &self.allowed_dirs())
+        }
+    }
+
+    #[test]
+    fn accepts_regular_file_in_allowed_dir() {
+        let fixture = Fixture::new();
+        assert!(fixture.check(&fixture.path("sudo")).is_ok());
+    }
+
+    #[test]
+    fn accepts_missing_file_in_allowed_dir() {
+        let fixture = Fixture::new();
+        assert!(fixture.check(&fixture.path("polkit-1")).is_ok());
+    }
+
+    #[test]
+    fn rejects_parent_dir_traversal() {
+        let fixture = Fixture::new();
+        let err = fixture.check(&fixture.path("../secret")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn rejects_current_dir_segments() {
+        let fixture = Fixture::new();
+        assert!(fixture.check(&fixture.path("./sudo")).is_err());
+    }
+
+    #[test]
+    fn rejects_prefix_sibling_directory() {
+        let fixture = Fixture::new();
+        let sibling = format!("{}x/sudo", fixture.allowed.display());
+        assert!(fixture.check(&sibling).is_err());
+    }
+
+    #[test]
+    fn rejects_nested_directories() {
+        let fixture = Fixture::new();
+        fs::create_dir(fixture.allowed.join("nested")).unwrap();
+        assert!(fixture.check(&fixture.path("nested/sudo")).is_err());
+    }
+
+    #[test]
+    fn rejects_the_directory_itself() {
+        let fixture = Fixture::new();
+        let dir = fixture.allowed.display().to_string();
+        assert!(fixture.check(&dir).is_err());
+        assert!(fixture.check(&format!("{}/", dir)).is_err());
+    }
+
+    #[test]
+    fn rejects_nul_bytes() {
+        let fixture = Fixture::new();
+        let err = fixture.check(&fixture.path("sudo\0.bak")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn rejects_relative_paths() {
+        let fixture = Fixture::new();
+        assert!(fixture.check("pam.d/sudo").is_err());
+    }
+
+    #[test]
+    fn rejects_symlink_escaping_allowed_dir() {
+        let fixture = Fixture::new();
+        symlink(&fixture.outside, fixture.allowed.join("evil")).unwrap();
+        let err = fixture.check(&fixture.path("evil")).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn rejects_dangling_symlink() {
+        let fixture = Fixture::new();
+        symlink(
+            fixture.allowed.join("missing"),
+            fixture.allowed.join("dangling"),
+        )
+        .unwrap();
+        assert!(fixture.check(&fixture.path("dangling")).is_err());
+    }
+
+    #[test]
+    fn accepts_symlink_within_allowed_dir() {
+        let fixture = Fixture::new();
+        symlink(fixture.allowed.join("sudo"), fixture.allowed.join("sudo-i")).unwrap();
+        assert!(fixture.check(&fixture.path("sudo-i")).is_ok());
+    }
+}
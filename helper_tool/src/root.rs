@@ -0,0 +1,56 @@
+//! Optional root prefix relocating every path the helper touches.
+//!
+//! The hidden `--root` option exists for the test suite: it lets the file
+//! logic run against a temporary directory tree without privileges. It is
+//! refused when the helper runs through pkexec or setuid.
+
+use std::{
+    env, io,
+    path::{Path, PathBuf},
+    sync::OnceLock,
+};
+
+static ROOT: OnceLock<PathBuf> = OnceLock::new();
+
+/// Checks whether the process was started with elevated privileges
+fn is_elevated() -> bool {
+    let setuid = unsafe { libc::getuid() != libc::geteuid() || libc::getgid() != libc::getegid() };
+    setuid || env::var_os("PKEXEC_UID").is_some()
+}
+
+/// Installs the root prefix; must be called before any path is resolved
+pub fn set(root: PathBuf) -> io::Result<()> {
+    if is_elevated() {
+        return Err(io::Error::new(
+            io::ErrorKind::PermissionDenied,
+            "--root is not allowed when running via pkexec or setuid",
+        ));
+    }
+
+    if !root.is_absolute() || !root.is_dir() {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!(
+                "--root must be an existing absolute directory: {}",
+                root.display()
+            ),
+        ));
+    }
+
+    ROOT.set(root)
+        .map_err(|_| io::Error::other("root prefix already set"))
+}
+
+/// Checks whether a root prefix is active
+pub fn is_set() -> bool {
+    ROOT.get().is_some()
+}
+
+/// Maps an absolute system path into the active root prefix
+pub fn path(absolute: impl AsRef<Path>) -> PathBuf {
+    let absolute = absolute.as_ref();
+    match ROOT.get() {
+        Some(root) => root.join(absolute.strip_prefix("/").unwrap_or(absolute)),
+        None => absolute.to_path_buf(),
+    }
+}
@@ -0,0 +1,239 @@
+//! Parsing of individual PAM configuration lines.
+//!
+//! Only the parts needed to reason about the `auth` stack are modelled:
+//! the management group, the control field, the module and its arguments,
+//! plus the Debian-style `@include` directive.
+
+/// A single parsed PAM directive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PamLine {
+    /// Regular `<type> <control> <module> [args...]` rule
+    Rule(PamRule),
+    /// Debian-style `@include <file>` directive (includes every group)
+    AtInclude(String),
+}
+
+/// A regular PAM rule.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PamRule {
+    /// Management group without the optional leading dash (e.g. "auth")
+    pub kind: String,
+    /// Whether the rule was prefixed with '-' (silently skipped if the module is missing)
+    pub optional: bool,
+    /// Control field, including brackets for the extended syntax
+    pub control: String,
+    /// Module path or, for include/substack, the included service name
+    pub module: String,
+    /// Remaining module arguments
+    pub args: Vec<String>,
+}
+
+impl PamRule {
+    /// Returns the module file name without any directory prefix.
+    pub fn module_name(&self) -> &str {
+        self.module.rsplit('/').next().unwrap_or(&self.module)
+    }
+
+    /// Checks if this rule pulls in another service file.
+    pub fn is_include(&self) -> bool {
+        self.control == "include" || self.control == "substack"
+    }
+}
+
+/// Splits content into logical lines, joining backslash continuations.
+pub fn logical_lines(content: &str) -> Vec<String> {
+    let mut result = Vec::new();
+    let mut pending: Option<String> = None;
+
+    for raw in content.lines() {
+        let line = raw.trim_end_matches('\r');
+        let (text, continues) = match line.strip_suffix('\\') {
+            Some(stripped) => (stripped, true),
+            None => (line, false),
+        };
+
+        let current = match pending.take() {
+            Some(mut existing) => {
+                existing.push(' ');
+                existing.push_str(text);
+                existing
+            }
+            None => text.to_string(),
+        };
+
+        if continues {
+            pending = Some(current);
+        } else {
+            result.push(current.trim().to_string());
+        }
+    }
+
+    if let Some(last) = pending {
+        result.push(last.trim().to_string());
+    }
+
+    result
+}
+
+/// Parses a single logical line, returning None for blank lines and comments.
+pub fn parse_line(line: &str) -> Option<PamLine> {
+    let without_comment = match line.find('#') {
+        Some(pos) => &line[..pos],
+        None => line,
+    };
+    let text = without_comment.trim();
+    if text.is_empty() {
+        return None;
+    }
+
+    if let Some(rest) = text.strip_prefix("@include") {
+        let target = rest.trim();
+        if target.is_empty() {
+            return None;
+        }
+        return Some(PamLine::AtInclude(target.to_string()));
+    }
+
+    let mut tokens = text.split_whitespace();
+    let first = tokens.next()?;
+    let (kind, optional) = match first.strip_prefix('-') {
+        Some(kind) => (kind, true),
+        None => (first, false),
+    };
+
+    // Extended control syntax may contain spaces: [success=1 default=ignore]
+    let mut control = tokens.next()?.to_string();
+    if control.starts_with('[') {
+        while !control.ends_with(']') {
+            let next = tokens.next()?;
+            control.push(' ');
+            control.push_str(next);
+        }
+    }
+
+    let module = tokens.next()?.to_string();
+    let args = tokens.map(str::to_string).collect();
+
+    Some(PamLine::Rule(PamRule {
+        kind: kind.to_ascii_lowercase(),
+        optional,
+        control,
+        module,
+        args,
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(line: &str) -> PamRule {
+        match parse_line(line) {
+            Some(PamLine::Rule(rule)) => rule,
+            other => panic!("not a rule: {:?}", other),
+        }
+    }
+
+    #[test]
+    fn parses_simple_rule() {
+        let rule = rule("auth\tsufficient\tpam_fprintd.so max-tries=3 timeout=10");
+        assert_eq!(rule.kind, "auth");
+        assert!(!rule.optional);
+        assert_eq!(rule.control, "sufficient");
+        assert_eq!(rule.module, "pam_fprintd.so");
+        assert_eq!(rule.args, ["max-tries=3", "timeout=10"]);
+    }
+
+    #[test]
+    fn parses_bracketed_control() {
+        let rule = rule("auth [success=1 default=ignore] pam_unix.so nullok");
+        assert_eq!(rule.control, "[success=1 default=ignore]");
+        assert_eq!(rule.module, "pam_unix.so");
+        assert_eq!(rule.args, ["nullok"]);
+    }
+
+    #[test]
+    fn parses_single_word_bracketed_control() {
+        let rule = rule("auth [default=die] pam_faillock.so authfail");
+        assert_eq!(rule.control, "[default=die]");
+        assert_eq!(rule.module, "pam_faillock.so");
+    }
+
+    #[test]
+    fn unterminated_bracketed_control_is_rejected() {
+        assert_eq!(
+            parse_line("auth [success=1 default=ignore pam_unix.so"),
+            None
+        );
+    }
+
+    #[test]
+    fn parses_optional_rule_and_module_path() {
+        let rule = rule("-Auth optional /usr/lib/security/pam_gnome_keyring.so");
+        assert_eq!(rule.kind, "auth");
+        assert!(rule.optional);
+        assert_eq!(rule.module_name(), "pam_gnome_keyring.so");
+    }
+
+    #[test]
+    fn include_and_substack_are_includes() {
+        assert!(rule("auth include system-auth").is_include());
+        assert!(rule("auth substack system-auth").is_include());
+        assert!(!rule("auth required pam_env.so").is_include());
+    }
+
+    #[test]
+    fn parses_at_include() {
+        assert_eq!(
+            parse_line("@include common-auth"),
+            Some(PamLine::AtInclude("common-auth".to_string()))
+        );
+        assert_eq!(parse_line("@include"), None);
+    }
+
+    #[test]
+    fn skips_blank_lines_and_comments() {
+        assert_eq!(parse_line(""), None);
+        assert_eq!(parse_line("   "), None);
+        assert_eq!(parse_line("#%PAM-1.0"), None);
+        assert_eq!(parse_line("# auth sufficient pam_fprintd.so"), None);
+    }
+
+    #[test]
+    fn strips_trailing_comments() {
+        let rule = rule("auth required pam_env.so # set up the environment");
+        assert_eq!(rule.module, "pam_env.so");
+        assert!(rule.args.is_empty());
+    }
+
+    #[test]
+    fn incomplete_rules_are_rejected() {
+        assert_eq!(parse_line("auth"), None);
+        assert_eq!(parse_line("auth required"), None);
+    }
+
+    #[test]
+    fn joins_continuation_lines() {
+        let lines = logical_lines(
+            "auth required \\\n    pam_env.so \\\n    readenv=1\nauth sufficient pam_unix.so\n",
+        );
+        assert_eq!(
+            lines,
+            [
+                "auth required      pam_env.so      readenv=1",
+                "auth sufficient pam_unix.so"
+            ]
+        );
+        let rule = rule(&lines[0]);
+        assert_eq!(rule.module, "pam_env.so");
+        assert_eq!(rule.args, ["readenv=1"]);
+    }
+
+    #[test]
+    fn keeps_trailing_continuation_and_strips_carriage_returns() {
+        assert_eq!(
+            logical_lines("auth required pam_env.so\r\nauth sufficient \\"),
+            ["auth required pam_env.so", "auth sufficient"]
+        );
+    }
+}
@@ -0,0 +1,399 @@
+//! Include-aware resolution of PAM `auth` stacks.
+//!
+//! Services frequently delegate to shared files through `include`,
+//! `substack` or Debian's `@include`, so looking at a single file is not
+//! enough to tell whether fingerprint authentication is already active.
+//! This module flattens the auth stack of a service across the
+//! configuration and vendor directories.
+
+use crate::rules::{self, PamLine, PamRule};
+use crate::{BEGIN_MARK, END_MARK};
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+/// Directories searched (in order) when resolving an included service name
+const SERVICE_DIRS: &[&str] = &["/etc/pam.d", "/usr/lib/pam.d"];
+
+/// Maximum include depth before giving up (guards against include cycles)
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+/// The fingerprint PAM module
+pub const FPRINTD_MODULE: &str = "pam_fprintd.so";
+
+/// One rule of a flattened auth stack
+#[derive(Debug, Clone)]
+pub struct StackEntry {
+    /// File the rule was read from
+    pub source: PathBuf,
+    /// The parsed rule
+    pub rule: PamRule,
+    /// Whether the rule lives inside an xfprintd-gui fenced block
+    pub in_block: bool,
+}
+
+impl StackEntry {
+    /// Checks whether this entry enables fingerprint authentication
+    pub fn is_fprintd(&self) -> bool {
+        self.rule.module_name() == FPRINTD_MODULE
+    }
+}
+
+/// The service directories under the active root prefix
+fn service_dirs() -> Vec<PathBuf> {
+    SERVICE_DIRS.iter().map(crate::root::path).collect()
+}
+
+/// Resolves an included service name or path to an existing file
+pub fn resolve_service(name: &str) -> Option<PathBuf> {
+    resolve_in(name, &service_dirs())
+}
+
+/// Resolves a service name against `dirs`, the first existing file winning
+fn resolve_in(name: &str, dirs: &[PathBuf]) -> Option<PathBuf> {
+    if name.starts_with('/') {
+        let path = crate::root::path(name);
+        return path.is_file().then_some(path);
+    }
+
+    dirs.iter()
+        .map(|dir| dir.join(name))
+        .find(|path| path.is_file())
+}
+
+/// Builds the flattened auth stack for in-memory content read from `source`
+pub fn auth_stack(source: &Path, content: &str) -> Vec<StackEntry> {
+    auth_stack_in(source, content, &service_dirs())
+}
+
+/// Builds the flattened auth stack, resolving includes against `dirs`
+fn auth_stack_in(source: &Path, content: &str, dirs: &[PathBuf]) -> Vec<StackEntry> {
+    let mut entries = Vec::new();
+    let mut chain = vec![source.to_path_buf()];
+    collect_auth(source, content, dirs, &mut chain, &mut entries);
+    entries
+}
+
+/// Builds the flattened auth stack for the service backing a target file.
+///
+/// Falls back to `default` and then to the vendor directory when the
+/// target itself does not exist yet (e.g. an unmodified polkit-1).
+pub fn auth_stack_for_target(path: &Path, default: Option<&Path>) -> io::Result<Vec<StackEntry>> {
+    let source = if path.is_file() {
+        Some(path.to_path_buf())
+    } else {
+        default
+            .filter(|d| d.is_file())
+            .map(Path::to_path_buf)
+            .or_else(|| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(resolve_service)
+            })
+    };
+
+    match source {
+        Some(source) => {
+            let content = fs::read_to_string(&source)?;
+            Ok(auth_stack(&source, &content))
+        }
+        None => Ok(Vec::new()),
+    }
+}
+
+/// Returns the files (outside our own fenced blocks) that already enable fingerprint auth
+pub fn system_fprintd_sources(entries: &[StackEntry]) -> Vec<PathBuf> {
+    let mut sources: Vec<PathBuf> = Vec::new();
+
+    for entry in entries {
+        if entry.is_fprintd() && !entry.in_block && !sources.contains(&entry.source) {
+            sources.push(entry.source.clone());
+        }
+    }
+
+    sources
+}
+
+/// Appends the auth rules of `content` to `entries`, expanding includes recursively
+fn collect_auth(
+    source: &Path,
+    content: &str,
+    dirs: &[PathBuf],
+    chain: &mut Vec<PathBuf>,
+    entries: &mut Vec<StackEntry>,
+) {
+    let mut in_block = false;
+
+    for line in rules::logical_lines(content) {
+        if line == BEGIN_MARK {
+            in_block = true;
+            continue;
+        }
+        if line == END_MARK {
+            in_block = false;
+            continue;
+        }
+
+        let included = match rules::parse_line(&line) {
+            Some(PamLine::AtInclude(name)) => name,
+            Some(PamLine::Rule(rule)) if rule.kind == "auth" => {
+                if rule.is_include() {
+                    rule.module
+                } else {
+                    entries.push(StackEntry {
+                        source: source.to_path_buf(),
+                        rule,
+                        in_block,
+                    });
+                    continue;
+                }
+            }
+            _ => continue,
+        };
+
+        let Some(path) = resolve_in(&included, dirs) else {
+            continue;
+        };
+        if chain.len() >= MAX_INCLUDE_DEPTH || chain.contains(&path) {
+            continue;
+        }
+        let Ok(included_content) = fs::read_to_string(&path) else {
+            continue;
+        };
+
+        chain.push(path.clone());
+        let before = entries.len();
+        collect_auth(&path, &included_content, dirs, chain, entries);
+        chain.pop();
+
+        // Rules pulled in from inside our block belong to us as well
+        if in_block {
+            for entry in &mut entries[before..] {
+                entry.in_block = true;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A relocated `/etc/pam.d` plus `/usr/lib/pam.d` pair
+    struct PamTree {
+        _temp: tempfile::TempDir,
+        etc: PathBuf,
+        vendor: PathBuf,
+    }
+
+    impl PamTree {
+        fn new() -> Self {
+            let temp = tempfile::tempdir().unwrap();
+            let etc = temp.path().join("etc/pam.d");
+            let vendor = temp.path().join("usr/lib/pam.d");
+            fs::create_dir_all(&etc).unwrap();
+            fs::create_dir_all(&vendor).unwrap();
+            Self {
+                _temp: temp,
+                etc,
+                vendor,
+            }
+        }
+
+        fn dirs(&self) -> Vec<PathBuf> {
+            vec![self.etc.clone(), self.vendor.clone()]
+        }
+
+        fn etc(&self, name: &str, content: &str) -> PathBuf {
+            let path = self.etc.join(name);
+            fs::write(&path, content).unwrap();
+            path
+        }
+
+        fn vendor(&self, name: &str, content: &str) -> PathBuf {
+            let path = self.vendor.join(name);
+            fs::write(&path, content).unwrap();
+            path
+        }
+
+        fn stack(&self, source: &Path) -> Vec<StackEntry> {
+            let content = fs::read_to_string(source).unwrap();
+            auth_stack_in(source, &content, &self.dirs())
+        }
+    }
+
+    fn modules(entries: &[StackEntry]) -> Vec<&str> {
+        entries.iter().map(|e| e.rule.module_name()).collect()
+    }
+
+    #[test]
+    fn include_and_substack_are_expanded_in_place() {
+        let tree = PamTree::new();
+        tree.etc(
+            "system-auth",
+            "auth required pam_env.so\nauth sufficient pam_unix.so\n",
+        );
+        tree.etc("extra", "auth optional pam_gnome_keyring.so\n");
+        let sudo = tree.etc(
+            "sudo",
+            "#%PAM-1.0\nauth sufficient pam_rootok.so\nauth include system-auth\nauth substack extra\naccount include system-auth\n",
+        );
+
+        let entries = tree.stack(&sudo);
+        assert_eq!(
+            modules(&entries),
+            [
+                "pam_rootok.so",
+                "pam_env.so",
+                "pam_unix.so",
+                "pam_gnome_keyring.so"
+            ]
+        );
+        assert_eq!(entries[1].source, tree.etc.join("system-auth"));
+    }
+
+    #[test]
+    fn at_include_pulls_in_auth_rules_only() {
+        let tree = PamTree::new();
+        tree.etc(
+            "common-auth",
+            "auth [success=1 default=ignore] pam_unix.so nullok\nauth requisite pam_deny.so\naccount required pam_unix.so\n",
+        );
+        let sudo = tree.etc("sudo", "#%PAM-1.0\n@include common-auth\n");
+
+        let entries = tree.stack(&sudo);
+        assert_eq!(modules(&entries), ["pam_unix.so", "pam_deny.so"]);
+        assert_eq!(entries[0].rule.control, "[success=1 default=ignore]");
+    }
+
+    #[test]
+    fn etc_shadows_vendor_directory() {
+        let tree = PamTree::new();
+        tree.vendor("system-auth", "auth required pam_vendor.so\n");
+        tree.etc("system-auth", "auth required pam_admin.so\n");
+        let sudo = tree.etc("sudo", "auth include system-auth\n");
+
+        assert_eq!(modules(&tree.stack(&sudo)), ["pam_admin.so"]);
+    }
+
+    #[test]
+    fn falls_back_to_vendor_directory() {
+        let tree = PamTree::new();
+        tree.vendor("system-auth", "auth sufficient pam_fprintd.so\n");
+        let sudo = tree.etc("sudo", "auth include system-auth\n");
+
+        let entries = tree.stack(&sudo);
+        assert_eq!(modules(&entries), ["pam_fprintd.so"]);
+        assert_eq!(entries[0].source, tree.vendor.join("system-auth"));
+    }
+
+    #[test]
+    fn missing_includes_are_skipped() {
+        let tree = PamTree::new();
+        let sudo = tree.etc("sudo", "auth include nowhere\nauth required pam_unix.so\n");
+
+        assert_eq!(modules(&tree.stack(&sudo)), ["pam_unix.so"]);
+    }
+
+    #[test]
+    fn include_cycles_terminate() {
+        let tree = PamTree::new();
+        tree.etc("a", "auth required pam_a.so\nauth include b\n");
+        tree.etc("b", "auth required pam_b.so\nauth include a\n");
+        let sudo = tree.etc("sudo", "auth include a\n");
+
+        assert_eq!(modules(&tree.stack(&sudo)), ["pam_a.so", "pam_b.so"]);
+    }
+
+    #[test]
+    fn self_include_terminates() {
+        let tree = PamTree::new();
+        let sudo = tree.etc("sudo", "auth required pam_env.so\nauth include sudo\n");
+
+        assert_eq!(modules(&tree.stack(&sudo)), ["pam_env.so"]);
+    }
+
+    #[test]
+    fn include_depth_is_limited() {
+        let tree = PamTree::new();
+        let depth = MAX_INCLUDE_DEPTH + 4;
+        for level in 0..depth {
+            tree.etc(
+                &format!("level{}", level),
+                &format!(
+                    "auth required pam_level{}.so\nauth include level{}\n",
+                    level,
+                    level + 1
+                ),
+            );
+        }
+        let sudo = tree.etc("sudo", "auth include level0\n");
+
+        // The service itself takes the first place in the include chain
+        let entries = tree.stack(&sudo);
+        assert_eq!(entries.len(), MAX_INCLUDE_DEPTH - 1);
+        assert_eq!(
+            entries.last().unwrap().rule.module,
+            format!("pam_level{}.so", MAX_INCLUDE_DEPTH - 2)
+        );
+    }
+
+    #[test]
+    fn fenced_block_status_propagates_into_includes() {
+        let tree = PamTree::new();
+        tree.etc("fingerprint-auth", "auth sufficient pam_fprintd.so\n");
+        tree.etc("system-auth", "auth sufficient pam_unix.so\n");
+        let sudo = tree.etc(
+            "sudo",
+            &format!(
+                "#%PAM-1.0\n{}\nauth include fingerprint-auth\n{}\nauth include system-auth\n",
+                BEGIN_MARK, END_MARK
+            ),
+        );
+
+        let entries = tree.stack(&sudo);
+        assert_eq!(modules(&entries), ["pam_fprintd.so", "pam_unix.so"]);
+        assert!(entries[0].in_block);
+        assert!(!entries[1].in_block);
+        assert!(system_fprintd_sources(&entries).is_empty());
+    }
+
+    #[test]
+    fn system_sources_list_files_outside_our_block() {
+        let tree = PamTree::new();
+        let common = tree.etc(
+            "common-auth",
+            "auth sufficient pam_fprintd.so\nauth required pam_unix.so\n",
+        );
+        let sudo = tree.etc(
+            "sudo",
+            &format!(
+                "{}\nauth sufficient pam_fprintd.so\n{}\n@include common-auth\n@include common-auth\n",
+                BEGIN_MARK, END_MARK
+            ),
+        );
+
+        let entries = tree.stack(&sudo);
+        assert_eq!(entries.iter().filter(|e| e.is_fprintd()).count(), 3);
+        assert_eq!(system_fprintd_sources(&entries), [common]);
+    }
+
+    #[test]
+    fn stack_for_missing_target_uses_default_file() {
+        let tree = PamTree::new();
+        let default = tree.vendor("polkit-1", "auth sufficient pam_fprintd.so\n");
+
+        let entries = auth_stack_for_target(&tree.etc.join("polkit-1"), Some(&default)).unwrap();
+        assert_eq!(modules(&entries), ["pam_fprintd.so"]);
+        assert_eq!(entries[0].source, default);
+    }
+
+    #[test]
+    fn stack_for_missing_target_without_default_is_empty() {
+        let tree = PamTree::new();
+        let missing = tree.etc.join("xfprintd-gui-test-missing-service");
+
+        assert!(auth_stack_for_target(&missing, None).unwrap().is_empty());
+    }
+}
@@ -0,0 +1,109 @@
+//! Persisted record of the targets fingerprint authentication was enabled for.
+//!
+//! Package upgrades can replace a PAM file and drop the fenced block. The
+//! record in `/var/lib/xfprintd-gui/state.json` lets `reapply` restore the
+//! configuration the user asked for, including its parameters.
+
+use crate::{root, TargetConfig};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    os::unix::fs::{DirBuilderExt, OpenOptionsExt},
+    path::PathBuf,
+};
+
+/// Directory holding the state file
+const STATE_DIR: &str = "/var/lib/xfprintd-gui";
+
+/// State file name inside `STATE_DIR`
+const STATE_FILE: &str = "state.json";
+
+/// Targets with fingerprint authentication enabled
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct State {
+    #[serde(default)]
+    pub targets: Vec<TargetConfig>,
+}
+
+impl State {
+    /// Loads the state, treating a missing file as empty
+    pub fn load() -> io::Result<Self> {
+        match fs::read_to_string(state_path()) {
+            Ok(content) => serde_json::from_str(&content).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("Invalid state file {}: {}", state_path().display(), e),
+                )
+            }),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Stored configuration for a target file
+    pub fn target(&self, file: &str) -> Option<&TargetConfig> {
+        self.targets.iter().find(|t| t.file == file)
+    }
+
+    /// Records that a target was enabled, replacing an earlier record
+    pub fn enable(&mut self, target: &TargetConfig) {
+        self.disable(&target.file);
+        self.targets.push(target.clone());
+    }
+
+    /// Forgets a target
+    pub fn disable(&mut self, file: &str) {
+        self.targets.retain(|t| t.file != file);
+    }
+
+    /// Writes the state atomically
+    pub fn save(&self) -> io::Result<()> {
+        let dir = root::path(STATE_DIR);
+        fs::DirBuilder::new()
+            .recursive(true)
+            .mode(0o755)
+            .create(&dir)?;
+
+        let mut content = serde_json::to_string_pretty(self).map_err(io::Error::other)?;
+        content.push('\n');
+
+        let temp_path = dir.join(format!(".{}.{}.tmp", STATE_FILE, std::process::id()));
+        let result = (|| {
+            let mut file = fs::OpenOptions::new()
+                .create(true)
+                .truncate(true)
+                .write(true)
+                .mode(0o644)
+                .open(&temp_path)?;
+            file.write_all(content.as_bytes())?;
+            file.sync_all()?;
+            fs::rename(&temp_path, dir.join(STATE_FILE))
+        })();
+
+        if result.is_err() {
+            let _ = fs::remove_file(&temp_path);
+        }
+        result
+    }
+}
+
+fn state_path() -> PathBuf {
+    root::path(STATE_DIR).join(STATE_FILE)
+}
+
+/// Applies `change` to the stored state, warning instead of failing the command
+pub fn update(change: impl FnOnce(&mut State)) {
+    let result = State::load().and_then(|mut state| {
+        change(&mut state);
+        state.save()
+    });
+
+    if let Err(e) = result {
+        eprintln!(
+            "Warning: could not update {}: {}",
+            state_path().display(),
+            e
+        );
+    }
+}
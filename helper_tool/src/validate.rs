@@ -0,0 +1,235 @@
+//! Pre-flight checks run before fingerprint authentication is enabled.
+//!
+//! Validation never modifies anything. It renders the configuration that
+//! `apply` would write and inspects it together with the module and
+//! service availability, so the GUI can refuse changes that could lock
+//! the user out.
+
+use crate::stack::{self, StackEntry, FPRINTD_MODULE};
+use crate::TargetConfig;
+
+/// Directories searched for PAM modules across common distributions
+const MODULE_DIRS: &[&str] = &[
+    "/usr/lib/security",
+    "/usr/lib64/security",
+    "/lib/security",
+    "/lib64/security",
+    "/usr/lib/x86_64-linux-gnu/security",
+    "/lib/x86_64-linux-gnu/security",
+    "/usr/lib/aarch64-linux-gnu/security",
+];
+
+/// D-Bus activation files through which the system bus starts fprintd on demand
+const FPRINTD_SERVICE_FILES: &[&str] = &[
+    "/usr/share/dbus-1/system-services/net.reactivated.Fprint.service",
+    "/etc/dbus-1/system-services/net.reactivated.Fprint.service",
+];
+
+/// Modules that prompt for (or verify) a password and therefore act as fallback
+const PASSWORD_MODULES: &[&str] = &[
+    "pam_unix.so",
+    "pam_unix2.so",
+    "pam_sss.so",
+    "pam_ldap.so",
+    "pam_krb5.so",
+    "pam_systemd_home.so",
+];
+
+/// Severity of a validation finding
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Severity {
+    /// Applying is possible but the result may not behave as expected
+    Warning,
+    /// Applying would be unsafe and must be refused
+    Error,
+}
+
+/// A single validation finding
+#[derive(Debug, Clone)]
+pub struct Finding {
+    pub severity: Severity,
+    pub message: String,
+}
+
+impl Finding {
+    fn warning(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Warning,
+            message: message.into(),
+        }
+    }
+
+    fn error(message: impl Into<String>) -> Self {
+        Self {
+            severity: Severity::Error,
+            message: message.into(),
+        }
+    }
+}
+
+/// Checks whether a PAM module is installed in any of the known module directories
+pub fn module_available(module: &str) -> bool {
+    MODULE_DIRS
+        .iter()
+        .any(|dir| crate::root::path(dir).join(module).is_file())
+}
+
+/// Checks whether fprintd is installed as an activatable D-Bus service.
+///
+/// fprintd is started on demand, so whether it currently runs says nothing;
+/// this only looks for the activation file, not at the live system bus.
+fn fprintd_activatable() -> bool {
+    FPRINTD_SERVICE_FILES
+        .iter()
+        .any(|file| crate::root::path(file).is_file())
+}
+
+/// Checks that our fingerprint rule is followed by a password fallback in the resolved stack
+fn check_password_fallback(entries: &[StackEntry]) -> Option<Finding> {
+    let last_fprintd = entries
+        .iter()
+        .rposition(|entry| entry.in_block && entry.is_fprintd());
+
+    let index = match last_fprintd {
+        Some(index) => index,
+        None => {
+            return Some(Finding::error(format!(
+                "patch does not contain an auth rule for {}",
+                FPRINTD_MODULE
+            )))
+        }
+    };
+
+    let has_fallback = entries[index + 1..]
+        .iter()
+        .any(|entry| PASSWORD_MODULES.contains(&entry.rule.module_name()));
+
+    if has_fallback {
+        None
+    } else {
+        Some(Finding::error(format!(
+            "no password fallback follows {} in the resolved auth stack",
+            FPRINTD_MODULE
+        )))
+    }
+}
+
+/// Warns when the system configuration already enables fingerprint auth for the service
+fn check_system_fprintd(entries: &[StackEntry]) -> Option<Finding> {
+    let sources = stack::system_fprintd_sources(entries);
+    if sources.is_empty() {
+        return None;
+    }
+
+    let sources: Vec<String> = sources.iter().map(|s| s.display().to_string()).collect();
+    Some(Finding::warning(format!(
+        "{} is already enabled by system configuration ({}); enabling here would prompt twice",
+        FPRINTD_MODULE,
+        sources.join(", ")
+    )))
+}
+
+/// Validates a target, returning every finding (an empty list means it is safe to apply)
+pub fn validate_target(target: &TargetConfig) -> Vec<Finding> {
+    let mut findings = Vec::new();
+
+    if !module_available(FPRINTD_MODULE) {
+        findings.push(Finding::error(format!(
+            "{} is not installed (install the fprintd package)",
+            FPRINTD_MODULE
+        )));
+    }
+
+    if !fprintd_activatable() {
+        findings.push(Finding::warning(
+            "no D-Bus activation file for fprintd was found, so the system bus cannot start it; fingerprint prompts will fall back to password",
+        ));
+    }
+
+    match crate::render_config(target) {
+        Ok(content) => {
+            let entries = stack::auth_stack(&crate::root::path(&target.file), &content);
+            findings.extend(check_system_fprintd(&entries));
+            findings.extend(check_password_fallback(&entries));
+        }
+        Err(e) => findings.push(Finding::error(e.to_string())),
+    }
+
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::rules::{parse_line, PamLine};
+    use std::path::PathBuf;
+
+    /// Stack entries from `(line, in_block)` pairs, as if read from one file
+    fn entries(lines: &[(&str, bool)]) -> Vec<StackEntry> {
+        lines
+            .iter()
+            .map(|(line, in_block)| match parse_line(line) {
+                Some(PamLine::Rule(rule)) => StackEntry {
+                    source: PathBuf::from("/etc/pam.d/sudo"),
+                    rule,
+                    in_block: *in_block,
+                },
+                other => panic!("not a rule: {:?}", other),
+            })
+            .collect()
+    }
+
+    #[test]
+    fn password_fallback_after_fprintd_passes() {
+        let stack = entries(&[
+            ("auth sufficient pam_fprintd.so", true),
+            ("auth required pam_unix.so try_first_pass nullok", false),
+        ]);
+        assert!(check_password_fallback(&stack).is_none());
+    }
+
+    #[test]
+    fn password_fallback_must_follow_fprintd() {
+        let stack = entries(&[
+            ("auth sufficient pam_unix.so", false),
+            ("auth sufficient pam_fprintd.so", true),
+            ("auth required pam_deny.so", false),
+        ]);
+        let finding = check_password_fallback(&stack).expect("missing fallback");
+        assert_eq!(finding.severity, Severity::Error);
+        assert!(finding.message.contains("no password fallback"));
+    }
+
+    #[test]
+    fn password_fallback_accepts_other_password_modules() {
+        let stack = entries(&[
+            ("auth sufficient pam_fprintd.so", true),
+            (
+                "auth [success=done default=ignore] /usr/lib/security/pam_sss.so",
+                false,
+            ),
+        ]);
+        assert!(check_password_fallback(&stack).is_none());
+    }
+
+    #[test]
+    fn password_fallback_ignores_fprintd_outside_our_block() {
+        let stack = entries(&[
+            ("auth sufficient pam_fprintd.so", false),
+            ("auth required pam_unix.so", false),
+        ]);
+        let finding = check_password_fallback(&stack).expect("no block rule");
+        assert_eq!(finding.severity, Severity::Error);
+        assert!(finding.message.contains("does not contain"));
+    }
+
+    #[test]
+    fn password_fallback_checks_the_last_block_rule() {
+        let stack = entries(&[
+            ("auth sufficient pam_fprintd.so", true),
+            ("auth required pam_unix.so", false),
+            ("auth sufficient pam_fprintd.so", true),
+        ]);
+        assert!(check_password_fallback(&stack).is_some());
+    }
+}
@@ -0,0 +1,159 @@
+//! Extended attribute copying for atomic replacements.
+//!
+//! A freshly created temporary file gets the default security context of
+//! its directory, so labels such as `security.selinux` have to be carried
+//! over explicitly before it replaces the original file.
+
+use std::{
+    ffi::CString,
+    fs, io,
+    os::unix::{ffi::OsStrExt, io::AsRawFd},
+    path::Path,
+    ptr,
+};
+
+/// Checks whether an error means the filesystem has no xattr support
+fn is_unsupported(err: &io::Error) -> bool {
+    err.raw_os_error() == Some(libc::ENOTSUP)
+}
+
+/// Checks whether a failure to set `name` can be ignored.
+///
+/// The filesystem may lack a namespace, or only root may set it. Losing a
+/// `security.*` attribute such as the SELinux label can lock users out, so
+/// that is only tolerated under the unprivileged `--root` test harness.
+fn may_skip(name: &CString, err: &io::Error) -> bool {
+    let refused = is_unsupported(err) || err.raw_os_error() == Some(libc::EPERM);
+    refused && (crate::root::is_set() || !name.as_bytes().starts_with(b"security."))
+}
+
+/// Lists the extended attribute names of `path` (without following symlinks)
+fn list_names(path: &CString) -> io::Result<Vec<CString>> {
+    let size = unsafe { libc::llistxattr(path.as_ptr(), ptr::null_mut(), 0) };
+    if size < 0 {
+        return Err(io::Error::last_os_error());
+    }
+    if size == 0 {
+        return Ok(Vec::new());
+    }
+
+    let mut buffer = vec![0u8; size as usize];
+    let size = unsafe {
+        libc::llistxattr(
+            path.as_ptr(),
+            buffer.as_mut_ptr() as *mut libc::c_char,
+            buffer.len(),
+        )
+    };
+    if size < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    buffer[..size as usize]
+        .split(|byte| *byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| CString::new(name).map_err(io::Error::from))
+        .collect()
+}
+
+/// Reads a single extended attribute value
+fn get_value(path: &CString, name: &CString) -> io::Result<Vec<u8>> {
+    let size = unsafe { libc::lgetxattr(path.as_ptr(), name.as_ptr(), ptr::null_mut(), 0) };
+    if size < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    let mut value = vec![0u8; size as usize];
+    let size = unsafe {
+        libc::lgetxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_mut_ptr() as *mut libc::c_void,
+            value.len(),
+        )
+    };
+    if size < 0 {
+        return Err(io::Error::last_os_error());
+    }
+
+    value.truncate(size as usize);
+    Ok(value)
+}
+
+/// Copies every extended attribute (including `security.selinux`) from `source` to `dest`
+pub fn copy_all(source: &Path, dest: &fs::File) -> io::Result<()> {
+    let c_path = CString::new(source.as_os_str().as_bytes())?;
+
+    let names = match list_names(&c_path) {
+        Ok(names) => names,
+        Err(e) if is_unsupported(&e) => return Ok(()),
+        Err(e) => return Err(e),
+    };
+
+    for name in names {
+        let value = get_value(&c_path, &name)?;
+        let result = unsafe {
+            libc::fsetxattr(
+                dest.as_raw_fd(),
+                name.as_ptr(),
+                value.as_ptr() as *const libc::c_void,
+                value.len(),
+                0,
+            )
+        };
+        if result != 0 {
+            let err = io::Error::last_os_error();
+            if may_skip(&name, &err) {
+                eprintln!(
+                    "Warning: could not copy extended attribute {}: {}",
+                    name.to_string_lossy(),
+                    err
+                );
+                continue;
+            }
+            return Err(io::Error::new(
+                err.kind(),
+                format!(
+                    "Failed to copy extended attribute {}: {}",
+                    name.to_string_lossy(),
+                    err
+                ),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(value: &str) -> CString {
+        CString::new(value).unwrap()
+    }
+
+    #[test]
+    fn security_labels_must_be_copied() {
+        let denied = io::Error::from_raw_os_error(libc::EPERM);
+        let unsupported = io::Error::from_raw_os_error(libc::ENOTSUP);
+        assert!(!may_skip(&name("security.selinux"), &denied));
+        assert!(!may_skip(&name("security.selinux"), &unsupported));
+    }
+
+    #[test]
+    fn other_namespaces_may_be_skipped() {
+        let denied = io::Error::from_raw_os_error(libc::EPERM);
+        assert!(may_skip(&name("trusted.overlay.origin"), &denied));
+        assert!(may_skip(
+            &name("user.mime_type"),
+            &io::Error::from_raw_os_error(libc::ENOTSUP)
+        ));
+    }
+
+    #[test]
+    fn other_errors_are_never_skipped() {
+        let error = io::Error::from_raw_os_error(libc::EIO);
+        assert!(!may_skip(&name("user.mime_type"), &error));
+    }
+}
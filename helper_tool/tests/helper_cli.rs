@@ -0,0 +1,580 @@
+//! End-to-end tests running the helper binary against a relocated root.
+//!
+//! Each test builds a throwaway system tree containing the shipped patches,
+//! an `/etc/pam.d` directory and the PAM modules needed by `validate`, then
+//! drives the helper through the hidden `--root` option. Golden files live
+//! in `tests/fixtures`: `<case>.pam` is the input, `<case>.applied` the
+//! expected result of `apply` and `<case>.removed` the expected result of
+//! `remove` on the applied file.
+
+use std::{
+    ffi::CString,
+    fs,
+    os::unix::{
+        ffi::OsStrExt,
+        fs::{MetadataExt, PermissionsExt},
+        io::AsRawFd,
+    },
+    path::{Path, PathBuf},
+    process::{Command, Output},
+};
+
+const SUDO: &str = "/etc/pam.d/sudo";
+const POLKIT: &str = "/etc/pam.d/polkit-1";
+const POLKIT_DEFAULT: &str = "/usr/lib/pam.d/polkit-1";
+
+struct TestRoot {
+    dir: tempfile::TempDir,
+}
+
+impl TestRoot {
+    fn new() -> Self {
+        let dir = tempfile::tempdir().unwrap();
+        let root = Self { dir };
+
+        let patches = Path::new(env!("CARGO_MANIFEST_DIR")).join("patches/etc/pam.d");
+        for entry in fs::read_dir(patches).unwrap() {
+            let entry = entry.unwrap();
+            root.write(
+                &format!(
+                    "/opt/xfprintd-gui/patches/etc/pam.d/{}",
+                    entry.file_name().to_string_lossy()
+                ),
+                &fs::read_to_string(entry.path()).unwrap(),
+            );
+        }
+
+        fs::create_dir_all(root.path("/etc/pam.d")).unwrap();
+        fs::create_dir_all(root.path("/usr/lib/pam.d")).unwrap();
+        root
+    }
+
+    fn path(&self, absolute: &str) -> PathBuf {
+        self.dir.path().join(absolute.trim_start_matches('/'))
+    }
+
+    fn write(&self, absolute: &str, content: &str) {
+        let path = self.path(absolute);
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, content).unwrap();
+    }
+
+    fn read(&self, absolute: &str) -> String {
+        fs::read_to_string(self.path(absolute)).unwrap()
+    }
+
+    fn install_modules(&self) {
+        for module in ["pam_fprintd.so", "pam_unix.so", "pam_succeed_if.so"] {
+            self.write(&format!("/usr/lib/security/{}", module), "");
+        }
+    }
+
+    fn helper(&self, args: &[&str]) -> Output {
+        Command::new(env!("CARGO_BIN_EXE_xfprintd-gui-helper"))
+            .arg("--root")
+            .arg(self.dir.path())
+            .args(args)
+            .env_remove("PKEXEC_UID")
+            .output()
+            .unwrap()
+    }
+}
+
+fn fixture(name: &str) -> String {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("tests/fixtures")
+        .join(name);
+    fs::read_to_string(&path).unwrap_or_else(|e| panic!("{}: {}", path.display(), e))
+}
+
+/// Sets an extended attribute, returning false where the filesystem has no support
+fn set_xattr(path: &Path, name: &str, value: &[u8]) -> bool {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let name = CString::new(name).unwrap();
+    let result = unsafe {
+        libc::setxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_ptr() as *const libc::c_void,
+            value.len(),
+            0,
+        )
+    };
+    result == 0
+}
+
+fn get_xattr(path: &Path, name: &str) -> Option<Vec<u8>> {
+    let path = CString::new(path.as_os_str().as_bytes()).unwrap();
+    let name = CString::new(name).unwrap();
+    let mut value = vec![0u8; 256];
+    let size = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            name.as_ptr(),
+            value.as_mut_ptr() as *mut libc::c_void,
+            value.len(),
+        )
+    };
+    (size >= 0).then(|| {
+        value.truncate(size as usize);
+        value
+    })
+}
+
+fn stdout(output: &Output) -> String {
+    String::from_utf8_lossy(&output.stdout).into_owned()
+}
+
+/// Applies and removes the configuration on a fixture, comparing both results to the golden files
+fn assert_round_trip(case: &str) {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture(&format!("{}.pam", case)));
+
+    let output = root.helper(&["apply", SUDO]);
+    assert!(output.status.success(), "apply failed: {:?}", output);
+    assert_eq!(root.read(SUDO), fixture(&format!("{}.applied", case)));
+
+    let output = root.helper(&["remove", SUDO]);
+    assert!(output.status.success(), "remove failed: {:?}", output);
+    assert_eq!(root.read(SUDO), fixture(&format!("{}.removed", case)));
+}
+
+#[test]
+fn empty_file() {
+    assert_round_trip("empty");
+}
+
+#[test]
+fn missing_header() {
+    assert_round_trip("missing-header");
+}
+
+#[test]
+fn crlf_line_endings() {
+    assert_round_trip("crlf");
+}
+
+#[test]
+fn duplicate_blocks_collapse_into_one() {
+    assert_round_trip("duplicate-blocks");
+}
+
+#[test]
+fn debian_block_goes_before_included_auth() {
+    assert_round_trip("debian");
+}
+
+#[test]
+fn apply_is_idempotent() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+
+    assert!(root.helper(&["apply", SUDO]).status.success());
+    assert!(root.helper(&["apply", SUDO]).status.success());
+    assert_eq!(root.read(SUDO), fixture("missing-header.applied"));
+}
+
+#[test]
+fn unterminated_block_is_left_untouched() {
+    let root = TestRoot::new();
+    let original = fixture("unterminated-block.pam");
+    root.write(SUDO, &original);
+
+    for command in ["apply", "remove"] {
+        let output = root.helper(&[command, SUDO]);
+        assert_eq!(output.status.code(), Some(1), "{} succeeded", command);
+        assert!(String::from_utf8_lossy(&output.stderr).contains("is not closed"));
+        assert_eq!(root.read(SUDO), original, "{} modified the file", command);
+    }
+}
+
+#[test]
+fn remove_without_block_keeps_file_byte_for_byte() {
+    let root = TestRoot::new();
+    let original = fixture("crlf.pam");
+    root.write(SUDO, &original);
+
+    assert!(root.helper(&["remove", SUDO]).status.success());
+    assert_eq!(root.read(SUDO), original);
+}
+
+#[test]
+fn remove_missing_file_is_a_no_op() {
+    let root = TestRoot::new();
+
+    assert!(root.helper(&["remove", SUDO]).status.success());
+    assert!(!root.path(SUDO).exists());
+}
+
+#[test]
+fn apply_missing_target_copies_default_file() {
+    let root = TestRoot::new();
+    root.write(POLKIT_DEFAULT, &fixture("missing-header.pam"));
+    let target = format!(r#"{{"file":"{}","default":"{}"}}"#, POLKIT, POLKIT_DEFAULT);
+
+    assert!(root.helper(&["apply", &target]).status.success());
+    let applied = root.read(POLKIT);
+    assert!(applied.contains("# BEGIN xfprintd-gui"));
+    assert!(applied.contains("pam_unix.so try_first_pass likeauth nullok"));
+    assert!(applied.ends_with("session\t\tinclude\t\tsystem-auth\n"));
+    assert_eq!(root.read(POLKIT_DEFAULT), fixture("missing-header.pam"));
+}
+
+#[test]
+fn apply_keeps_owner_group_and_mode() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+    let path = root.path(SUDO);
+    fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+    let before = fs::metadata(&path).unwrap();
+
+    assert!(root.helper(&["apply", SUDO]).status.success());
+
+    let after = fs::metadata(&path).unwrap();
+    assert_ne!(after.ino(), before.ino(), "file was not replaced");
+    assert_eq!(after.mode() & 0o7777, 0o600);
+    assert_eq!(after.uid(), before.uid());
+    assert_eq!(after.gid(), before.gid());
+}
+
+#[test]
+fn apply_copies_extended_attributes() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+    let path = root.path(SUDO);
+    // Unprivileged tests cannot relabel, so the label travels in the user namespace
+    let label = b"system_u:object_r:etc_t:s0";
+    if !set_xattr(&path, "user.xfprintd-test", label) {
+        eprintln!("skipping: no user xattr support on the test filesystem");
+        return;
+    }
+    // Keep whatever label the test filesystem assigned, if any
+    let selinux = get_xattr(&path, "security.selinux");
+
+    assert!(root.helper(&["apply", SUDO]).status.success());
+
+    assert_eq!(
+        get_xattr(&path, "user.xfprintd-test").as_deref(),
+        Some(&label[..])
+    );
+    assert_eq!(get_xattr(&path, "security.selinux"), selinux);
+}
+
+#[test]
+fn apply_writes_through_symlink_inside_allowed_dirs() {
+    let root = TestRoot::new();
+    let real = "/etc/pam.d/sudo.real";
+    root.write(real, &fixture("missing-header.pam"));
+    std::os::unix::fs::symlink("sudo.real", root.path(SUDO)).unwrap();
+
+    let output = root.helper(&["apply", SUDO]);
+    assert!(output.status.success(), "{:?}", output);
+
+    assert!(fs::symlink_metadata(root.path(SUDO))
+        .unwrap()
+        .file_type()
+        .is_symlink());
+    assert_eq!(root.read(real), fixture("missing-header.applied"));
+}
+
+#[test]
+fn apply_refuses_symlink_escaping_allowed_dirs() {
+    let root = TestRoot::new();
+    let outside = "/var/lib/sudo-outside";
+    root.write(outside, &fixture("missing-header.pam"));
+    std::os::unix::fs::symlink(root.path(outside), root.path(SUDO)).unwrap();
+
+    let output = root.helper(&["apply", SUDO]);
+    assert!(!output.status.success());
+    assert!(
+        String::from_utf8_lossy(&output.stderr).contains("outside the allowlisted directories"),
+        "{:?}",
+        output
+    );
+    assert_eq!(root.read(outside), fixture("missing-header.pam"));
+}
+
+#[test]
+fn check_reports_state_and_parameters() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+
+    let output = root.helper(&["check", SUDO]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(stdout(&output), format!("not-applied: {}\n", SUDO));
+
+    let output = root.helper(&["apply", "--max-tries", "5", "--timeout", "60", SUDO]);
+    assert!(output.status.success());
+
+    let output = root.helper(&["check", SUDO]);
+    assert_eq!(output.status.code(), Some(0));
+    assert_eq!(
+        stdout(&output),
+        format!("applied: {0}\nparams: {0} max-tries=5 timeout=60\n", SUDO)
+    );
+}
+
+#[test]
+fn inspect_reports_fprintd_enabled_through_includes() {
+    let root = TestRoot::new();
+    root.write(SUDO, "#%PAM-1.0\n@include common-auth\n");
+    root.write(
+        "/usr/lib/pam.d/common-auth",
+        "auth\tsufficient\tpam_fprintd.so\nauth\trequired\tpam_unix.so\n",
+    );
+
+    let output = root.helper(&["inspect", SUDO]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(
+        stdout(&output),
+        format!(
+            "system-enabled: {} via {}\n",
+            SUDO,
+            root.path("/usr/lib/pam.d/common-auth").display()
+        )
+    );
+
+    // Our own block does not count as system configuration
+    root.write(
+        "/usr/lib/pam.d/common-auth",
+        "auth\trequired\tpam_unix.so\n",
+    );
+    assert!(root.helper(&["apply", SUDO]).status.success());
+    let output = root.helper(&["inspect", SUDO]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(stdout(&output), format!("not-enabled: {}\n", SUDO));
+}
+
+#[test]
+fn out_of_range_parameters_are_rejected() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+
+    for args in [
+        ["--max-tries", "0"],
+        ["--max-tries", "11"],
+        ["--timeout", "9"],
+        ["--timeout", "301"],
+    ] {
+        let output = root.helper(&["apply", args[0], args[1], SUDO]);
+        assert_eq!(output.status.code(), Some(2), "{:?}", args);
+    }
+    assert_eq!(root.read(SUDO), fixture("missing-header.pam"));
+
+    let output = root.helper(&["apply", "--max-tries", "10", "--timeout", "300", SUDO]);
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn paths_outside_the_allowlist_are_rejected() {
+    let root = TestRoot::new();
+    root.write("/etc/shadow", "root:*:19000::::::\n");
+
+    let output = root.helper(&["apply", "/etc/shadow"]);
+    assert_eq!(output.status.code(), Some(1));
+    assert_eq!(root.read("/etc/shadow"), "root:*:19000::::::\n");
+
+    let output = root.helper(&["apply", "/etc/pam.d/../shadow"]);
+    assert_eq!(output.status.code(), Some(1));
+}
+
+#[test]
+fn validate_requires_installed_module() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+    root.write(
+        "/etc/pam.d/system-auth",
+        "auth\t\trequired\t\tpam_unix.so try_first_pass nullok\n",
+    );
+
+    let output = root.helper(&["validate", SUDO]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("pam_fprintd.so is not installed"));
+
+    root.install_modules();
+    let output = root.helper(&["validate", SUDO]);
+    assert_eq!(output.status.code(), Some(0), "{}", stdout(&output));
+    assert!(!stdout(&output).contains("error:"));
+}
+
+#[test]
+fn validate_refuses_stack_without_password_fallback() {
+    let root = TestRoot::new();
+    root.install_modules();
+    root.write(SUDO, "#%PAM-1.0\naccount\t\tinclude\t\tsystem-auth\n");
+
+    let output = root.helper(&["validate", SUDO]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(stdout(&output).contains("no password fallback"));
+}
+
+#[test]
+fn root_must_be_an_existing_directory() {
+    let output = Command::new(env!("CARGO_BIN_EXE_xfprintd-gui-helper"))
+        .args(["--root", "/nonexistent/xfprintd-gui-root", "check", SUDO])
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn held_lock_times_out_with_tempfail() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+
+    // Hold the lock the way a concurrent helper run would
+    root.write("/run/xfprintd-gui/helper.lock", "");
+    let held = fs::File::open(root.path("/run/xfprintd-gui/helper.lock")).unwrap();
+    assert_eq!(unsafe { libc::flock(held.as_raw_fd(), libc::LOCK_EX) }, 0);
+
+    let output = root.helper(&["--lock-timeout", "0", "apply", SUDO]);
+    assert_eq!(output.status.code(), Some(75));
+    let error: serde_json::Value = serde_json::from_slice(&output.stderr).unwrap();
+    assert_eq!(error["error"], "lock-timeout");
+    assert!(error["message"].as_str().unwrap().contains("helper.lock"));
+    assert_eq!(root.read(SUDO), fixture("missing-header.pam"));
+
+    drop(held);
+    let output = root.helper(&["--lock-timeout", "0", "apply", SUDO]);
+    assert!(output.status.success(), "{:?}", output);
+}
+
+#[test]
+fn lock_timeout_requires_root() {
+    let output = Command::new(env!("CARGO_BIN_EXE_xfprintd-gui-helper"))
+        .args(["--lock-timeout", "0", "apply", SUDO])
+        .env_remove("PKEXEC_UID")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+}
+
+#[test]
+fn root_is_refused_under_pkexec() {
+    let root = TestRoot::new();
+    let output = Command::new(env!("CARGO_BIN_EXE_xfprintd-gui-helper"))
+        .arg("--root")
+        .arg(root.dir.path())
+        .args(["check", SUDO])
+        .env("PKEXEC_UID", "1000")
+        .output()
+        .unwrap();
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("not allowed"));
+}
+
+#[test]
+fn changes_are_recorded_in_the_audit_log() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+
+    assert!(root.helper(&["apply", SUDO]).status.success());
+    assert!(root.helper(&["remove", SUDO]).status.success());
+    assert_eq!(
+        root.helper(&["remove", "/etc/shadow"]).status.code(),
+        Some(1)
+    );
+
+    let output = root.helper(&["log"]);
+    assert!(output.status.success());
+    let entries: Vec<serde_json::Value> = stdout(&output)
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(entries.len(), 3);
+
+    let (apply, remove, rejected) = (&entries[0], &entries[1], &entries[2]);
+    assert_eq!(apply["action"], "apply");
+    assert_eq!(apply["target"], SUDO);
+    assert_eq!(apply["result"], "success");
+    assert_ne!(apply["before"], apply["after"]);
+    assert_eq!(remove["action"], "remove");
+    assert_eq!(remove["before"], apply["after"]);
+    assert!(remove["after"].is_string());
+    assert_eq!(rejected["result"], "error");
+    assert!(rejected["error"].is_string());
+    assert!(rejected["before"].is_null());
+
+    // The newest entries are kept
+    let output = root.helper(&["log", "--limit", "1"]);
+    assert_eq!(stdout(&output).lines().count(), 1);
+    assert!(stdout(&output).contains("/etc/shadow"));
+}
+
+#[test]
+fn log_is_empty_before_any_change() {
+    let root = TestRoot::new();
+
+    let output = root.helper(&["log"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), "");
+}
+
+#[test]
+fn reapply_restores_blocks_dropped_by_an_upgrade() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+
+    let output = root.helper(&["apply", "--max-tries", "5", "--timeout", "60", SUDO]);
+    assert!(output.status.success());
+    let applied = root.read(SUDO);
+
+    let output = root.helper(&["reapply"]);
+    assert!(output.status.success());
+    assert_eq!(stdout(&output), format!("present: {}\n", SUDO));
+
+    // A package upgrade installs a fresh file
+    root.write(SUDO, &fixture("missing-header.pam"));
+    let output = root.helper(&["reapply"]);
+    assert!(output.status.success(), "{:?}", output);
+    assert_eq!(stdout(&output), format!("reapplied: {}\n", SUDO));
+    assert_eq!(root.read(SUDO), applied);
+
+    // Disabled targets are forgotten
+    assert!(root.helper(&["remove", SUDO]).status.success());
+    let removed = root.read(SUDO);
+    let output = root.helper(&["reapply"]);
+    assert_eq!(stdout(&output), "");
+    assert_eq!(root.read(SUDO), removed);
+
+    let log = stdout(&root.helper(&["log"]));
+    assert_eq!(log.matches(r#""action":"reapply""#).count(), 1);
+}
+
+#[test]
+fn merge_adopts_pacnew_and_keeps_the_block() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("missing-header.pam"));
+    assert!(root
+        .helper(&["apply", "--timeout", "45", SUDO])
+        .status
+        .success());
+
+    let packaged = "#%PAM-1.0\nauth\t\tinclude\t\tsystem-auth\naccount\t\tinclude\t\tsystem-auth\n";
+    root.write(&format!("{}.pacnew", SUDO), packaged);
+
+    let output = root.helper(&["merge", SUDO]);
+    assert!(output.status.success(), "{:?}", output);
+    assert!(!root.path(&format!("{}.pacnew", SUDO)).exists());
+
+    let merged = root.read(SUDO);
+    assert!(merged.contains("# BEGIN xfprintd-gui"));
+    assert!(merged.contains("timeout=45"));
+    assert!(merged.ends_with("account\t\tinclude\t\tsystem-auth\n"));
+
+    // Nothing left to merge
+    let output = root.helper(&["merge", SUDO]);
+    assert_eq!(output.status.code(), Some(1));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("No .pacnew or .rpmnew"));
+}
+
+#[test]
+fn merge_without_fingerprint_block_takes_the_new_file_verbatim() {
+    let root = TestRoot::new();
+    root.write(SUDO, &fixture("crlf.pam"));
+    let packaged = "#%PAM-1.0\nauth\t\tinclude\t\tsystem-auth\n";
+    root.write(&format!("{}.rpmnew", SUDO), packaged);
+
+    assert!(root.helper(&["merge", SUDO]).status.success());
+    assert_eq!(root.read(SUDO), packaged);
+}
@@ -113,7 +113,7 @@ sudo pacman -S --needed --noconfirm \
     gtk4 libadwaita \
     glib2 pkgconf \
     polkit fprintd \
-    base-devel
+    gettext base-devel
 
 print_success "All dependencies installed"
 echo ""
@@ -215,6 +215,13 @@ if [ -n "$HELPER_BINARY" ] && [ -f "$HELPER_BINARY" ]; then
         sudo cp -r "$SCRIPT_DIR/helper_tool/patches" /opt/xfprintd-gui/
         sudo chmod -R 755 /opt/xfprintd-gui/patches
     fi
+
+    # Re-apply the PAM configuration after sudo/polkit upgrades
+    if [ -f "$SCRIPT_DIR/packaging/xfprintd-gui-reapply.hook" ]; then
+        print_info "Installing pacman hook..."
+        sudo install -Dm644 "$SCRIPT_DIR/packaging/xfprintd-gui-reapply.hook" \
+            /usr/share/libalpm/hooks/xfprintd-gui-reapply.hook
+    fi
 fi
 
 # Create symlink in /usr/bin
@@ -228,6 +235,16 @@ if [ -f "$SCRIPT_DIR/packaging/xfprintd-gui.desktop" ]; then
     sudo install -Dm644 "$SCRIPT_DIR/packaging/xfprintd-gui.desktop" /usr/share/applications/xfprintd-gui.desktop
 fi
 
+# Install translations
+if [ -f "$SCRIPT_DIR/po/LINGUAS" ]; then
+    grep -v '^#' "$SCRIPT_DIR/po/LINGUAS" | while read -r lang; do
+        [ -n "$lang" ] || continue
+        print_info "Installing $lang translation..."
+        sudo mkdir -p "/usr/share/locale/$lang/LC_MESSAGES"
+        sudo msgfmt -o "/usr/share/locale/$lang/LC_MESSAGES/xfprintd-gui.mo" "$SCRIPT_DIR/po/$lang.po"
+    done
+fi
+
 # Install icon
 print_info "Installing application icon..."
 sudo mkdir -p /usr/share/icons/hicolor/scalable/apps
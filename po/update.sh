@@ -0,0 +1,25 @@
+#!/bin/bash
+# Regenerate the translation template and merge it into every translation.
+# Requires gettext 0.24 or newer for Rust support.
+
+set -e
+
+cd "$(dirname "${BASH_SOURCE[0]}")/.."
+
+xgettext \
+    --files-from=po/POTFILES.in \
+    --output=po/xfprintd-gui.pot \
+    --from-code=UTF-8 \
+    --add-comments=Translators \
+    --package-name=xfprintd-gui \
+    --msgid-bugs-address=https://github.com/BananikXenos/xfprintd-gui/issues \
+    --keyword=gettext \
+    --keyword=gettext_f \
+    --keyword=gettext_noop \
+    --keyword=ngettext:1,2 \
+    --keyword=ngettext_f:1,2
+
+grep -v '^#' po/LINGUAS | while read -r lang; do
+    [ -n "$lang" ] || continue
+    msgmerge --quiet --update --backup=none "po/$lang.po" po/xfprintd-gui.pot
+done